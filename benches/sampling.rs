@@ -0,0 +1,51 @@
+//! Compares checked and unchecked leaf access in a tight sampling loop.
+//!
+//! Run with `cargo bench --bench sampling`.
+
+use std::{hint::black_box, time::Instant};
+
+use flat_octree::{layout::DepthFirst, typenum::U6, Octree};
+
+const SIDE: usize = 1 << 6;
+const ROUNDS: usize = 20;
+
+fn sample(name: &str, mut f: impl FnMut() -> u64) {
+    // Warm up caches before timing.
+    black_box(f());
+    let start = Instant::now();
+    let mut sum = 0;
+    for _ in 0..ROUNDS {
+        sum += f();
+    }
+    let elapsed = start.elapsed() / ROUNDS as u32;
+    println!("{name:>16}: {elapsed:?} per pass (checksum {sum})");
+}
+
+fn main() {
+    let tree = Octree::<u32, U6, DepthFirst>::new_with(|i| i as u32);
+
+    sample("get", || {
+        let mut sum = 0u64;
+        for z in 0..SIDE {
+            for y in 0..SIDE {
+                for x in 0..SIDE {
+                    sum += *black_box(&tree).get(x, y, z).unwrap() as u64;
+                }
+            }
+        }
+        sum
+    });
+
+    sample("get_unchecked", || {
+        let mut sum = 0u64;
+        for z in 0..SIDE {
+            for y in 0..SIDE {
+                for x in 0..SIDE {
+                    // SAFETY: all coordinates are less than SIDE.
+                    sum += unsafe { *black_box(&tree).get_unchecked(x, y, z) } as u64;
+                }
+            }
+        }
+        sum
+    });
+}
@@ -0,0 +1,68 @@
+/// A trait for values that can be compared with a tolerance.
+///
+/// Two values are considered approximately equal if their absolute difference
+/// is within `abs_tol`, or within `rel_tol` of the larger absolute value.
+/// Arrays are compared component-wise, with tolerances matching their shape.
+pub trait ApproxElem: Clone {
+    /// Returns `true` if `self` and `other` are within tolerance.
+    fn approx_eq(&self, other: &Self, abs_tol: &Self, rel_tol: &Self) -> bool;
+
+    /// Returns the (component-wise) absolute difference of two values.
+    fn abs_diff(&self, other: &Self) -> Self;
+
+    /// Returns the (component-wise) maximum of two values.
+    fn max_elem(self, other: Self) -> Self;
+}
+
+macro_rules! impl_approx_elem_float {
+    ($($float: ty),*) => {$(
+        impl ApproxElem for $float {
+            fn approx_eq(&self, other: &Self, abs_tol: &Self, rel_tol: &Self) -> bool {
+                let diff = (self - other).abs();
+                diff <= *abs_tol || diff <= rel_tol * self.abs().max(other.abs())
+            }
+
+            fn abs_diff(&self, other: &Self) -> Self {
+                (self - other).abs()
+            }
+
+            fn max_elem(self, other: Self) -> Self {
+                self.max(other)
+            }
+        }
+    )*};
+}
+impl_approx_elem_float!(f32, f64);
+
+impl<E: ApproxElem, const N: usize> ApproxElem for [E; N] {
+    fn approx_eq(&self, other: &Self, abs_tol: &Self, rel_tol: &Self) -> bool {
+        (0..N).all(|i| self[i].approx_eq(&other[i], &abs_tol[i], &rel_tol[i]))
+    }
+
+    fn abs_diff(&self, other: &Self) -> Self {
+        std::array::from_fn(|i| self[i].abs_diff(&other[i]))
+    }
+
+    fn max_elem(self, other: Self) -> Self {
+        let mut other = other.into_iter();
+        self.map(|it| it.max_elem(other.next().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn approx_elem_test() {
+        assert!(1.0f32.approx_eq(&1.0000001, &1e-6, &0.0));
+        assert!(!1.0f32.approx_eq(&1.0000001, &0.0, &0.0));
+        assert!(100.0f64.approx_eq(&101.0, &0.0, &0.01));
+        assert!(!f64::NAN.approx_eq(&f64::NAN, &1.0, &1.0));
+        assert!([1.0f32, 2.0].approx_eq(&[1.5, 2.0], &[0.5, 0.0], &[0.0; 2]));
+        assert_eq!(
+            [1.0f32, 5.0].abs_diff(&[3.0, 4.0]).max_elem([1.0, 2.0]),
+            [2.0, 2.0]
+        );
+    }
+}
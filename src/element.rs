@@ -0,0 +1,112 @@
+use std::mem::size_of;
+
+/// Marker for octree values that can be viewed as plain bytes.
+///
+/// Types implementing this trait can be exposed as byte slices (e.g. with
+/// [`Octree::as_bytes`](crate::Octree::as_bytes)) to be uploaded to the GPU
+/// or written to a file, and read back from bytes without any validation.
+///
+/// This is implemented for primitive integer and floating point types, as
+/// well as arrays of `PlainElement` values. With the `bytemuck` feature
+/// enabled, it's instead implemented for all
+/// [`bytemuck::Pod`](https://docs.rs/bytemuck/latest/bytemuck/trait.Pod.html)
+/// types.
+///
+/// # Safety
+///
+/// Implementing types must have no padding bytes, no pointers, and every
+/// bit pattern of `size_of::<Self>()` bytes must be a valid value.
+pub unsafe trait PlainElement: Copy + 'static {}
+
+#[cfg(not(feature = "bytemuck"))]
+macro_rules! impl_plain_element {
+    ($($t: ty),*) => {$(
+        unsafe impl PlainElement for $t {}
+    )*};
+}
+
+#[cfg(not(feature = "bytemuck"))]
+impl_plain_element!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+#[cfg(not(feature = "bytemuck"))]
+unsafe impl<T: PlainElement, const N: usize> PlainElement for [T; N] {}
+
+#[cfg(feature = "bytemuck")]
+unsafe impl<T: bytemuck::Pod> PlainElement for T {}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Octree values that may be NaN.
+///
+/// With the `nan_check` feature enabled,
+/// [`Octree::assert_invariants`](crate::Octree::assert_invariants) panics if
+/// any value is NaN. This is implemented for primitive types, for which only
+/// `f32` and `f64` values can be NaN, and arrays, which are never considered
+/// NaN.
+pub trait MaybeNan: sealed::Sealed {
+    /// Returns `true` if this value is NaN.
+    fn is_nan(&self) -> bool {
+        false
+    }
+}
+
+macro_rules! impl_maybe_nan {
+    ($($t: ty),*) => {$(
+        impl sealed::Sealed for $t {}
+        impl MaybeNan for $t {}
+    )*};
+}
+
+impl_maybe_nan!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, bool, char);
+
+impl sealed::Sealed for f32 {}
+impl MaybeNan for f32 {
+    fn is_nan(&self) -> bool {
+        f32::is_nan(*self)
+    }
+}
+
+impl sealed::Sealed for f64 {}
+impl MaybeNan for f64 {
+    fn is_nan(&self) -> bool {
+        f64::is_nan(*self)
+    }
+}
+
+impl<T: MaybeNan, const N: usize> sealed::Sealed for [T; N] {}
+impl<T: MaybeNan, const N: usize> MaybeNan for [T; N] {}
+
+/// Returns bytes of all `values`.
+pub(crate) fn slice_bytes<T: PlainElement>(values: &[T]) -> &[u8] {
+    unsafe {
+        // SAFETY: T has no padding, so all bytes of the slice are
+        // initialized.
+        std::slice::from_raw_parts(values.as_ptr() as *const u8, std::mem::size_of_val(values))
+    }
+}
+
+/// Returns mutable bytes of all `values`.
+pub(crate) fn slice_bytes_mut<T: PlainElement>(values: &mut [T]) -> &mut [u8] {
+    unsafe {
+        // SAFETY: T has no padding, and any bytes written are a valid T.
+        std::slice::from_raw_parts_mut(
+            values.as_mut_ptr() as *mut u8,
+            std::mem::size_of_val(values),
+        )
+    }
+}
+
+/// Reads the `i`-th value from `bytes`, which don't need to be aligned.
+///
+/// # Panics
+///
+/// Panics if `bytes` are too short to contain the value.
+pub(crate) fn read_value<T: PlainElement>(bytes: &[u8], i: usize) -> T {
+    let value = &bytes[i * size_of::<T>()..(i + 1) * size_of::<T>()];
+    unsafe {
+        // SAFETY: the value is in bounds, and any bytes are a valid T.
+        std::ptr::read_unaligned(value.as_ptr() as *const T)
+    }
+}
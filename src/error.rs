@@ -0,0 +1,120 @@
+use std::{error::Error, fmt};
+
+/// Error returned when a provided buffer length doesn't match the length
+/// required by the octree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LenMismatch {
+    /// Required length.
+    pub expected: usize,
+    /// Provided length.
+    pub got: usize,
+}
+
+impl fmt::Display for LenMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "length mismatch: expected {} values, got {}",
+            self.expected, self.got
+        )
+    }
+}
+
+impl Error for LenMismatch {}
+
+/// Error returned when per-layer values don't match the octree structure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayerLenMismatch {
+    /// The number of provided layers doesn't match octree depth.
+    LayerCount {
+        /// Required number of layers.
+        expected: usize,
+        /// Provided number of layers.
+        got: usize,
+    },
+    /// The number of values in a layer doesn't match the layer length.
+    LayerLength {
+        /// Depth of the mismatched layer.
+        layer: usize,
+        /// Required number of values.
+        expected: usize,
+        /// Provided number of values.
+        got: usize,
+    },
+}
+
+impl fmt::Display for LayerLenMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayerLenMismatch::LayerCount { expected, got } => {
+                write!(f, "expected {} layers, got {}", expected, got)
+            }
+            LayerLenMismatch::LayerLength {
+                layer,
+                expected,
+                got,
+            } => write!(
+                f,
+                "layer {} length mismatch: expected {} values, got {}",
+                layer, expected, got
+            ),
+        }
+    }
+}
+
+impl Error for LayerLenMismatch {}
+
+/// Error returned when octree values aren't approximately equal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApproxMismatch {
+    /// Flat data index of the first node whose values differ.
+    pub index: usize,
+}
+
+impl fmt::Display for ApproxMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "values at index {} are not approximately equal",
+            self.index
+        )
+    }
+}
+
+impl Error for ApproxMismatch {}
+
+/// Error returned when a region extends outside of the octree grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutOfBounds {
+    /// Coordinates of the farthest corner of the region.
+    pub coords: [usize; 3],
+    /// Number of leaves along each side of the grid.
+    pub side: usize,
+}
+
+impl fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "coordinates {:?} are outside of the grid with side {}",
+            self.coords, self.side
+        )
+    }
+}
+
+impl Error for OutOfBounds {}
+
+/// Error returned when a stride step along some axis is zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZeroStep {
+    /// Index of the first axis with a zero step.
+    pub axis: usize,
+}
+
+impl fmt::Display for ZeroStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "step along axis {} is zero", self.axis)
+    }
+}
+
+impl Error for ZeroStep {}
@@ -0,0 +1,236 @@
+use std::{mem::size_of, ops::Range};
+
+use crate::octant::Octant;
+
+/// A trait for managing different octree memory layouts.
+///
+/// Layouts are stateless markers, so they're required to be [`Send`] and
+/// [`Sync`] to keep them from affecting thread safety of octrees.
+pub trait OctreeLayout: Send + Sync {
+    /// Name of the layout, used for logging and serialization tagging.
+    const NAME: &'static str;
+
+    /// Whether nodes of each layer are stored contiguously, ordered by their
+    /// index, within [`BreathFirst::layer_range`].
+    ///
+    /// Octrees use this to access whole layers as slices. Defaults to
+    /// `false`.
+    const CONTIGUOUS_LAYERS: bool = false;
+
+    /// Fills the subtree at the given `base` pointer with the given
+    /// `value`.
+    ///
+    /// # Safety
+    ///
+    /// For this function to be safe, the `base` pointer must be valid and
+    /// aligned for the given `T` type, the `size` must be the size of the
+    /// whole octree, the `depth` must be the (remaining) depth of the
+    /// subtree, and the `index` must be the index of `base` node at the
+    /// current layer (`size - depth`).
+    ///
+    /// Additionally, the surrounding layout of `base` must follow the
+    /// layout described by the [`OctreeLayout`] implementation.
+    unsafe fn fill<T: Clone>(base: *mut T, value: T, size: usize, depth: usize, index: usize);
+    /// Returns the offset of the `octant` child from node location
+    /// described by:
+    /// - `size` - the size of the whole octree,
+    /// - `depth` - the (remaining) depth of the subtree,
+    /// - `index` - the index of the node at the current (`size - depth`)
+    ///   layer.
+    fn child_offset<T>(octant: Octant, size: usize, depth: usize, index: usize) -> usize;
+}
+
+/// A depth-first memory layout.
+///
+/// In this layout, octree values are stored such that the first value is
+/// the root octant, which is followed by all first octant children until
+/// the last layer which is tightly packed. After the last layer, the
+/// second-to-last layer second octant value is stored, followed by all of
+/// its children, and so on...
+///
+/// This representation is better for CPU processing and collision
+/// detection.
+pub struct DepthFirst;
+impl DepthFirst {
+    /// Const version of [`OctreeLayout::child_offset`].
+    pub const fn child_offset<T>(
+        octant: Octant,
+        _size: usize,
+        depth: usize,
+        _index: usize,
+    ) -> usize {
+        if depth == 0 {
+            return 1;
+        }
+        let end_of_current = 1;
+        let start_of_next = crate::util::subtree_length(depth - 1) * octant.as_usize();
+        end_of_current + start_of_next
+    }
+
+    /// Returns the range of data indices of the subtree rooted at the node
+    /// described by:
+    /// - `size` - the size of the whole octree,
+    /// - `depth` - the (remaining) depth of the subtree,
+    /// - `index` - the index of the node at the current (`size - depth`)
+    ///   layer.
+    ///
+    /// Subtrees are stored contiguously in this layout, starting with their
+    /// root.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `depth` is larger than `size` or `index` is outside of the
+    /// layer.
+    pub const fn subtree_range(size: usize, depth: usize, index: usize) -> Range<usize> {
+        assert!(depth <= size, "depth is out of range");
+        let layer = size - depth;
+        assert!(
+            index < crate::util::layer_length(layer),
+            "index out of range"
+        );
+        let start = crate::util::node_offset_df(layer, index, size);
+        start..start + crate::util::subtree_length(depth)
+    }
+}
+impl OctreeLayout for DepthFirst {
+    const NAME: &'static str = "depth_first";
+
+    unsafe fn fill<T: Clone>(base: *mut T, value: T, _size: usize, depth: usize, _index: usize) {
+        let tailing = crate::util::subtree_size::<T>(depth) / size_of::<T>();
+        for i in 0..tailing {
+            base.add(i).write(value.clone())
+        }
+    }
+
+    fn child_offset<T>(octant: Octant, size: usize, depth: usize, index: usize) -> usize {
+        DepthFirst::child_offset::<T>(octant, size, depth, index)
+    }
+}
+
+/// A breath-first memory layout.
+///
+/// In this layout, octree values are stored such that every layer values
+/// are stored together, starting from the root layer (1 value), followed by
+/// the first layer (8 values), then third (64 values), and so on...
+///
+/// This representation is ideal for parallel processing and LOD streaming.
+///
+/// Additionally, it allows accessing each layer directly as a slice of
+/// memory.
+pub struct BreathFirst;
+impl BreathFirst {
+    /// Const version of [`OctreeLayout::child_offset`].
+    pub const fn child_offset<T>(octant: Octant, size: usize, depth: usize, index: usize) -> usize {
+        if depth == 0 {
+            return size_of::<T>();
+        }
+        let height = size - depth;
+        let layer_size = crate::util::layer_length(height);
+
+        let end_of_current = layer_size - index;
+        let start_of_next = index * 8 + octant.as_usize();
+        end_of_current + start_of_next
+    }
+
+    /// Returns the range of data indices of nodes at the given `depth` (layer,
+    /// with `0` being the root) of an octree with the given `tree_depth`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `depth` is larger than `tree_depth`.
+    pub const fn layer_range(depth: usize, tree_depth: usize) -> Range<usize> {
+        assert!(depth <= tree_depth, "depth is out of range");
+        let end = crate::util::subtree_length(depth);
+        end - crate::util::layer_length(depth)..end
+    }
+}
+impl OctreeLayout for BreathFirst {
+    const NAME: &'static str = "breadth_first";
+    const CONTIGUOUS_LAYERS: bool = true;
+
+    unsafe fn fill<T: Clone>(base: *mut T, value: T, size: usize, depth: usize, index: usize) {
+        let height = size - depth;
+        let mut start = base;
+
+        for i in 0..=depth {
+            let fill_size = crate::util::layer_length(i);
+            for j in 0..fill_size {
+                start.add(j).write(value.clone());
+            }
+
+            let layer_i = height + i;
+            let layer_size = crate::util::layer_length(layer_i);
+            let end_of_current = layer_size - (index + 1) * fill_size;
+
+            let skip_leading = index * fill_size * 8;
+            start = start.add(fill_size + end_of_current + skip_leading);
+        }
+    }
+
+    fn child_offset<T>(octant: Octant, size: usize, depth: usize, index: usize) -> usize {
+        BreathFirst::child_offset::<T>(octant, size, depth, index)
+    }
+}
+
+/// A shorthand type alias for [`DepthFirst`].
+pub type DF = DepthFirst;
+/// A shorthand type alias for [`BreathFirst`].
+pub type BF = BreathFirst;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::subtree_length;
+
+    #[test]
+    fn layer_range_test() {
+        assert_eq!(BreathFirst::layer_range(0, 3), 0..1);
+        assert_eq!(BreathFirst::layer_range(1, 3), 1..9);
+        assert_eq!(BreathFirst::layer_range(2, 3), 9..73);
+        assert_eq!(BreathFirst::layer_range(3, 3).end, subtree_length(3));
+        assert_eq!(BreathFirst::layer_range(0, 0), 0..1);
+        for depth in 1..=4 {
+            assert_eq!(
+                BreathFirst::layer_range(depth, 4).start,
+                BreathFirst::layer_range(depth - 1, 4).end
+            );
+        }
+    }
+
+    #[test]
+    fn subtree_range_test() {
+        use crate::octant::{OctantLUF, OctantRDB};
+        use typenum::U3;
+
+        assert_eq!(DepthFirst::subtree_range(3, 3, 0), 0..subtree_length(3));
+        assert_eq!(DepthFirst::subtree_range(0, 0, 0), 0..1);
+
+        let children: Vec<_> = (0..8).map(|i| DepthFirst::subtree_range(3, 2, i)).collect();
+        assert_eq!(children[0].start, 1);
+        for pair in children.windows(2) {
+            assert_eq!(pair[0].len(), subtree_length(2));
+            assert_eq!(pair[0].end, pair[1].start);
+        }
+        assert_eq!(children[7].end, subtree_length(3));
+
+        let test = crate::Octree::<usize, U3, DepthFirst>::new_with(|i| i);
+        let rdb = DepthFirst::subtree_range(3, 2, 5);
+        assert_eq!(**test.child::<OctantRDB>(), rdb.start);
+        let luf = DepthFirst::subtree_range(3, 1, 5 * 8 + 2);
+        assert_eq!(**test.child::<OctantRDB>().child::<OctantLUF>(), luf.start);
+        assert!(rdb.start < luf.start && luf.end <= rdb.end);
+        assert_eq!(DepthFirst::subtree_range(3, 0, 0o777), 584..585);
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of range")]
+    fn subtree_range_out_of_range_test() {
+        let _ = DepthFirst::subtree_range(3, 2, 8);
+    }
+
+    #[test]
+    #[should_panic(expected = "depth is out of range")]
+    fn layer_range_out_of_range_test() {
+        let _ = BreathFirst::layer_range(4, 3);
+    }
+}
@@ -0,0 +1,163 @@
+/// Asserts that a condition over generic parameters holds at compile time.
+///
+/// The assertion is evaluated when the enclosing generic function is
+/// instantiated, so a failing one is reported as a compilation error.
+macro_rules! const_assert {
+    ($cond: expr, $msg: literal) => {
+        const { assert!($cond, $msg) }
+    };
+}
+
+/// Expands a depth literal into the matching `typenum` type.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __octree_depth {
+    (1) => {
+        $crate::typenum::U1
+    };
+    (2) => {
+        $crate::typenum::U2
+    };
+    (3) => {
+        $crate::typenum::U3
+    };
+    (4) => {
+        $crate::typenum::U4
+    };
+    (5) => {
+        $crate::typenum::U5
+    };
+    (6) => {
+        $crate::typenum::U6
+    };
+    (7) => {
+        $crate::typenum::U7
+    };
+    (8) => {
+        $crate::typenum::U8
+    };
+    (9) => {
+        $crate::typenum::U9
+    };
+    (10) => {
+        $crate::typenum::U10
+    };
+}
+
+/// Expands to an [`Octree`](crate::Octree) type with a literal depth.
+///
+/// Depths `1` to `10` are supported, and the layout defaults to
+/// [`BreathFirst`](crate::layout::BreathFirst).
+///
+/// ```
+/// use flat_octree::{layout::DepthFirst, octree_type};
+///
+/// struct Chunk {
+///     blocks: octree_type!(u8; 5),
+///     light: octree_type!(u8; 5; DepthFirst),
+/// }
+/// ```
+///
+/// Unsupported depths fail to compile:
+///
+/// ```compile_fail
+/// type Empty = flat_octree::octree_type!(u8; 0);
+/// ```
+#[macro_export]
+macro_rules! octree_type {
+    ($t: ty; $depth: tt) => {
+        $crate::Octree<$t, $crate::__octree_depth!($depth)>
+    };
+    ($t: ty; $depth: tt; $layout: ty) => {
+        $crate::Octree<$t, $crate::__octree_depth!($depth), $layout>
+    };
+}
+
+/// Creates an [`Octree`](crate::Octree) with a literal depth.
+///
+/// Depths `1` to `10` are supported. `layout` and `fill` are optional and
+/// default to [`BreathFirst`](crate::layout::BreathFirst) and
+/// `Default::default()`.
+///
+/// ```
+/// use flat_octree::{layout::BF, octree};
+///
+/// let blocks = octree!(u8; depth = 5; layout = BF; fill = 1);
+/// let light = octree!(u8; depth = 5);
+/// assert_eq!(**blocks, 1);
+/// assert_eq!(**light, 0);
+/// ```
+///
+/// Unsupported depths fail to compile:
+///
+/// ```compile_fail
+/// let tree = flat_octree::octree!(u8; depth = 0);
+/// ```
+///
+/// ```compile_fail
+/// let tree = flat_octree::octree!(u8; depth = 11; fill = 0);
+/// ```
+#[macro_export]
+macro_rules! octree {
+    ($t: ty; depth = $depth: tt) => {
+        <$crate::octree_type!($t; $depth)>::new(::core::default::Default::default())
+    };
+    ($t: ty; depth = $depth: tt; fill = $fill: expr) => {
+        <$crate::octree_type!($t; $depth)>::new($fill)
+    };
+    ($t: ty; depth = $depth: tt; layout = $layout: ty) => {
+        <$crate::octree_type!($t; $depth; $layout)>::new(::core::default::Default::default())
+    };
+    ($t: ty; depth = $depth: tt; layout = $layout: ty; fill = $fill: expr) => {
+        <$crate::octree_type!($t; $depth; $layout)>::new($fill)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use typenum::{U1, U10, U4};
+
+    use crate::{
+        layout::{BreathFirst, DepthFirst, DF},
+        Octree,
+    };
+
+    fn values<T: Clone, D: typenum::Unsigned, L: crate::layout::OctreeLayout>(
+        tree: &Octree<T, D, L>,
+    ) -> &[T] {
+        AsRef::<[T]>::as_ref(tree)
+    }
+
+    #[test]
+    fn octree_macro_test() {
+        let test: Octree<u8, U4, BreathFirst> = octree!(u8; depth = 4);
+        assert!(values(&test).iter().all(|it| *it == 0));
+
+        let test: Octree<u16, U1> = octree!(u16; depth = 1; fill = 7);
+        assert!(values(&test).iter().all(|it| *it == 7));
+
+        let test: Octree<bool, U4, DepthFirst> = octree!(bool; depth = 4; layout = DF);
+        assert!(values(&test).iter().all(|it| !*it));
+
+        let test = octree!(i32; depth = 1; layout = DepthFirst; fill = -1);
+        assert_eq!(Octree::<i32, U1, DepthFirst>::layout_name(), "depth_first");
+        assert!(values(&test).iter().all(|it| *it == -1));
+    }
+
+    #[test]
+    fn octree_type_macro_test() {
+        fn depth<T: Clone, D: typenum::Unsigned, L: crate::layout::OctreeLayout>(
+            _: &Octree<T, D, L>,
+        ) -> usize {
+            D::USIZE
+        }
+
+        assert_eq!(
+            <octree_type!(u8; 10)>::node_count(),
+            Octree::<u8, U10>::node_count()
+        );
+        let test: octree_type!(u8; 2; DF) = octree!(u8; depth = 2; layout = DF);
+        assert_eq!(depth(&test), 2);
+        assert_eq!(depth(&<octree_type!(u8; 7)>::new(0)), 7);
+    }
+}
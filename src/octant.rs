@@ -39,8 +39,63 @@ impl Octant {
     pub const fn as_usize(&self) -> usize {
         *self as usize
     }
+
+    /// Returns the octant this octant is moved to by a 90° counter-clockwise
+    /// rotation around the `axis` (looking from its positive end).
+    pub const fn rotate90(self, axis: Axis) -> Octant {
+        let i = self as usize;
+        let (x, y, z) = (i & 1, (i >> 1) & 1, (i >> 2) & 1);
+        let (x, y, z) = match axis {
+            Axis::X => (x, z ^ 1, y),
+            Axis::Y => (z, y, x ^ 1),
+            Axis::Z => (y ^ 1, x, z),
+        };
+        Octant::ALL[x | (y << 1) | (z << 2)]
+    }
+
+    /// Returns the octant on the opposite side of the node center, flipped
+    /// along all axes.
+    pub const fn opposite(self) -> Octant {
+        Octant::ALL[self as usize ^ 0b111]
+    }
+
+    /// Returns all octants ordered by the distance of their sub-cube centers
+    /// from `origin`, closest first.
+    ///
+    /// The parent node is treated as a unit cube with its left-down-front
+    /// corner at the coordinate origin. Octants at the same distance are kept
+    /// in [`Octant::ALL`] order.
+    pub fn sort_by_distance(origin: [f32; 3]) -> [Octant; 8] {
+        let distance = |octant: &Octant| {
+            let i = octant.as_usize();
+            (0..3)
+                .map(|axis| {
+                    let center = 0.25 + 0.5 * ((i >> axis) & 1) as f32;
+                    (center - origin[axis]).powi(2)
+                })
+                .sum::<f32>()
+        };
+        let mut result = Octant::ALL;
+        result.sort_by(|a, b| distance(a).total_cmp(&distance(b)));
+        result
+    }
 }
 
+/// Coordinate axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Axis {
+    /// The `x` (left-right) axis.
+    X,
+    /// The `y` (down-up) axis.
+    Y,
+    /// The `z` (front-back) axis.
+    Z,
+}
+
+/// Path of octants leading from the root to a node, with an empty path
+/// denoting the root itself.
+pub type OctantPath = Vec<Octant>;
+
 impl TryFrom<u8> for Octant {
     type Error = ();
 
@@ -48,7 +103,7 @@ impl TryFrom<u8> for Octant {
         match value {
             0b000..=0b111 => Ok(unsafe {
                 // SAFETY: `value` is in the range `0b000..=0b111`.
-                std::mem::transmute(value)
+                std::mem::transmute::<u8, Octant>(value)
             }),
             _ => Err(()),
         }
@@ -63,6 +118,16 @@ impl TryFrom<usize> for Octant {
     }
 }
 
+impl std::ops::Not for Octant {
+    type Output = Octant;
+
+    /// Returns the [`opposite`](Octant::opposite) octant.
+    #[inline(always)]
+    fn not(self) -> Octant {
+        self.opposite()
+    }
+}
+
 /// A trait for type representations of an octant.
 pub trait OctantT {
     /// The octant value.
@@ -136,3 +201,129 @@ impl OctantT for OctantRUB {
     const USIZE: usize = 7;
     type IndexT = U7;
 }
+
+/// Returns the order in which children should be visited by a ray with the
+/// given direction signs, so that closer children are visited first.
+///
+/// Each of `dir_signs` is `true` if the ray direction is negative along the
+/// corresponding (`x`, `y`, `z`) axis.
+pub const fn visit_order(dir_signs: [bool; 3]) -> [Octant; 8] {
+    let mask =
+        (dir_signs[0] as usize) | ((dir_signs[1] as usize) << 1) | ((dir_signs[2] as usize) << 2);
+    let mut result = Octant::ALL;
+    let mut i = 0;
+    while i < 8 {
+        result[i] = Octant::ALL[i ^ mask];
+        i += 1;
+    }
+    result
+}
+
+/// Returns the octant containing the given point, where each component of
+/// `entry_point_fraction` is a position within the node's unit cube.
+///
+/// Points on the center planes belong to the right, up and back octants.
+pub fn entry_child(entry_point_fraction: [f32; 3]) -> Octant {
+    let [x, y, z] = entry_point_fraction;
+    let index = (x >= 0.5) as usize | ((y >= 0.5) as usize) << 1 | ((z >= 0.5) as usize) << 2;
+    Octant::ALL[index]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn visit_order_test() {
+        const POSITIVE: [Octant; 8] = visit_order([false; 3]);
+        assert_eq!(POSITIVE, Octant::ALL);
+
+        for mask in 0..8 {
+            let signs = [mask & 1 != 0, mask & 2 != 0, mask & 4 != 0];
+            let order = visit_order(signs);
+            assert_eq!(order[0].as_usize(), mask);
+            assert_eq!(order[7].as_usize(), mask ^ 0b111);
+
+            // Along each axis, the child the ray enters first must come
+            // before its neighbour.
+            for (i, a) in order.iter().enumerate() {
+                for b in &order[i + 1..] {
+                    let diff = a.as_usize() ^ b.as_usize();
+                    if diff.count_ones() == 1 {
+                        let axis = diff.trailing_zeros() as usize;
+                        let a_far = a.as_usize() & diff != 0;
+                        assert_eq!(a_far, signs[axis]);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rotate90_test() {
+        assert_eq!(Octant::RDF.rotate90(Axis::Z), Octant::RUF);
+        assert_eq!(Octant::LUF.rotate90(Axis::X), Octant::LUB);
+        assert_eq!(Octant::LDB.rotate90(Axis::Y), Octant::RDB);
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            for octant in Octant::ALL {
+                let full = (0..4).fold(octant, |it, _| it.rotate90(axis));
+                assert_eq!(full, octant);
+                assert_ne!(octant.rotate90(axis), octant);
+            }
+        }
+    }
+
+    #[test]
+    fn opposite_test() {
+        assert_eq!(Octant::LDF.opposite(), Octant::RUB);
+        assert_eq!(!Octant::LDF, Octant::RUB);
+        assert_eq!(!Octant::RDB, Octant::LUF);
+        for octant in Octant::ALL {
+            assert_eq!(!octant, octant.opposite());
+            assert_eq!(!!octant, octant);
+            assert_eq!(octant.as_usize() + (!octant).as_usize(), 7);
+        }
+    }
+
+    #[test]
+    fn sort_by_distance_test() {
+        let sorted = Octant::sort_by_distance([-1.0, -1.0, -1.0]);
+        assert_eq!(sorted[0], Octant::LDF);
+        assert_eq!(sorted[7], Octant::RUB);
+        assert_eq!(&sorted[1..4], &[Octant::RDF, Octant::LUF, Octant::LDB]);
+
+        let sorted = Octant::sort_by_distance([0.9, 2.0, 0.1]);
+        assert_eq!(sorted[0], Octant::RUF);
+        assert_eq!(sorted[7], Octant::LDB);
+
+        for (i, octant) in Octant::ALL.into_iter().enumerate() {
+            let corner = [0, 1, 2].map(|axis| ((i >> axis) & 1) as f32 * 3.0 - 1.0);
+            let sorted = Octant::sort_by_distance(corner);
+            assert_eq!(sorted[0], octant);
+            assert_eq!(sorted[7], Octant::ALL[7 - i]);
+        }
+        assert_eq!(Octant::sort_by_distance([0.5; 3]), Octant::ALL);
+    }
+
+    #[test]
+    fn entry_child_test() {
+        assert_eq!(entry_child([0.0, 0.0, 0.0]), Octant::LDF);
+        assert_eq!(entry_child([1.0, 1.0, 1.0]), Octant::RUB);
+        assert_eq!(entry_child([0.75, 0.25, 0.25]), Octant::RDF);
+        assert_eq!(entry_child([0.25, 0.75, 0.25]), Octant::LUF);
+        assert_eq!(entry_child([0.25, 0.25, 0.75]), Octant::LDB);
+        assert_eq!(entry_child([0.5, 0.49, 0.5]), Octant::RDB);
+
+        for octant in Octant::ALL {
+            let i = octant.as_usize();
+            for offset in [0.01, 0.25, 0.49] {
+                let point = [
+                    (i & 1) as f32 * 0.5 + offset,
+                    ((i >> 1) & 1) as f32 * 0.5 + offset,
+                    ((i >> 2) & 1) as f32 * 0.5 + offset,
+                ];
+                assert_eq!(entry_child(point), octant);
+            }
+        }
+    }
+}
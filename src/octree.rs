@@ -1,9 +1,9 @@
 use std::{
     alloc::Layout,
     marker::PhantomData,
-    mem::{forget, size_of},
+    mem::ManuallyDrop,
     ops::{Add, Deref, DerefMut, Mul, Sub},
-    ptr::{addr_of, addr_of_mut, null_mut},
+    ptr::{addr_of, addr_of_mut},
 };
 
 use typenum::{
@@ -12,11 +12,58 @@ use typenum::{
 };
 
 use crate::{
+    element::{slice_bytes, slice_bytes_mut, PlainElement},
     layout::{BreathFirst, OctreeLayout},
     octant::*,
+    propagate::{Bounded, BoundingBox, Propagator},
     util::{subtree_length, subtree_size},
 };
 
+mod analysis;
+mod approx;
+#[cfg(feature = "ndarray")]
+mod array;
+mod bit;
+mod buffer;
+mod column;
+mod debug;
+mod dyn_node;
+mod dyn_octree;
+mod edit;
+mod grid;
+mod invariants;
+mod layer;
+mod logged;
+mod min_max;
+mod occupancy;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod raycast;
+mod resample;
+mod search;
+mod stencil;
+mod stream;
+mod svo;
+mod transform;
+mod uniform;
+
+pub use analysis::OctreeStats;
+#[cfg(feature = "ndarray")]
+pub use array::FillMode;
+pub use bit::BitOctree;
+pub use buffer::Dealloc;
+pub use column::ColumnMut;
+pub use dyn_node::DynNode;
+pub use dyn_octree::DynOctree;
+pub use grid::{Connectivity, Metric};
+pub use logged::{ChangeEvent, ChangeKind, LoggedOctree};
+pub use min_max::MinMaxOctree;
+pub use occupancy::OccupancyPyramid;
+pub use resample::Filter;
+pub use stencil::Boundary;
+pub use stream::StreamingWriter;
+pub use uniform::UniformMask;
+
 mod sealed {
     use typenum::{NonZero, Unsigned, B1, U8};
 
@@ -70,8 +117,36 @@ pub type ChildrenRefMut<'a, T, Size, L, Depth, Index> = (
 
 /// Octree node structure.
 ///
-/// Shouldn't be constructed/dropped directly, use [`Octree`] instead - calling
-/// a drop on this type will result in UB.
+/// Nodes are only accessible through references into an [`Octree`], as the
+/// rest of the subtree is stored after the node value. They have no public
+/// constructors, and dropping a node by value fails to compile, so nodes
+/// can't be created or moved out of an octree even with unsafe code:
+///
+/// ```compile_fail,E0080
+/// use flat_octree::{typenum::U1, OctreeNode, BF};
+///
+/// let node: OctreeNode<u8, U1, BF> = unsafe { std::mem::zeroed() };
+/// ```
+///
+/// ```compile_fail,E0080
+/// use flat_octree::{octant::OctantLDF, typenum::U1, Octree};
+///
+/// let tree = Octree::<String, U1>::new(String::new());
+/// let node = unsafe { std::ptr::read(tree.child::<OctantLDF>()) };
+/// ```
+///
+/// ```compile_fail,E0080
+/// use flat_octree::{octant::OctantLDF, typenum::U1, Octree};
+///
+/// let mut tree = Octree::<u8, U1>::new(0);
+/// let other = Octree::<u8, U1>::new(1);
+/// let node = unsafe { std::ptr::read(other.child::<OctantLDF>()) };
+/// let old = std::mem::replace(tree.child_mut::<OctantLDF>(), node);
+/// ```
+///
+/// The check happens during code generation, so `cargo check` doesn't report
+/// it. Nodes read out with unsafe code can still be passed to
+/// [`std::mem::forget`], which never drops their value.
 #[derive(Debug)]
 #[repr(transparent)]
 pub struct OctreeNode<
@@ -81,11 +156,38 @@ pub struct OctreeNode<
     Depth: Unsigned = Size,
     LayerIndex: Unsigned = U0,
 > {
-    value: T,
-    _phantom: PhantomData<(L, Size, Depth, LayerIndex)>,
+    value: ManuallyDrop<T>,
+    // Type parameters only describe node position, so they don't affect
+    // auto traits or variance.
+    #[allow(clippy::type_complexity)]
+    _phantom: PhantomData<fn() -> (L, Size, Depth, LayerIndex)>,
+}
+
+impl<T: Clone, S: Unsigned, L: OctreeLayout, D: Unsigned, I: Unsigned> Drop
+    for OctreeNode<T, S, L, D, I>
+{
+    fn drop(&mut self) {
+        // Only evaluated if a node is dropped by value somewhere, which then
+        // fails to compile.
+        const { panic!("OctreeNode was dropped by value, nodes are only valid within an Octree") }
+    }
 }
 
 impl<T: Clone, S: Unsigned, L: OctreeLayout, D: Unsigned, I: Unsigned> OctreeNode<T, S, L, D, I> {
+    /// Returns a pointer to the node value, which the rest of the subtree
+    /// is stored after.
+    #[inline(always)]
+    fn value_ptr(&self) -> *const T {
+        addr_of!(self.value) as *const T
+    }
+
+    /// Returns a mutable pointer to the node value, which the rest of the
+    /// subtree is stored after.
+    #[inline(always)]
+    fn value_ptr_mut(&mut self) -> *mut T {
+        addr_of_mut!(self.value) as *mut T
+    }
+
     /// Returns the current node octant relative to parent.
     pub const fn octant(&self) -> Octant
     where
@@ -100,17 +202,21 @@ impl<T: Clone, S: Unsigned, L: OctreeLayout, D: Unsigned, I: Unsigned> OctreeNod
         &self.value
     }
 
+    /// Returns a clone of the node value.
+    ///
+    /// Nodes currently always hold a value, so `T::default()` is never
+    /// returned, but code using this method won't need changes once node
+    /// values can be absent.
+    pub fn value_or_default(&self) -> T
+    where
+        T: Default,
+    {
+        T::clone(&self.value)
+    }
+
     /// Sets the `value` of this node as well as its descendants.
     pub fn set_value(&mut self, value: T) {
-        unsafe {
-            L::fill(
-                addr_of_mut!(self.value),
-                value,
-                S::USIZE,
-                D::USIZE,
-                I::USIZE,
-            )
-        }
+        unsafe { L::fill(self.value_ptr_mut(), value, S::USIZE, D::USIZE, I::USIZE) }
     }
 
     /// Returns the child node at the given `octant`.
@@ -125,7 +231,7 @@ impl<T: Clone, S: Unsigned, L: OctreeLayout, D: Unsigned, I: Unsigned> OctreeNod
         <<I as Mul<U8>>::Output as Add<ChildOctant::IndexT>>::Output: Unsigned,
     {
         unsafe {
-            let pos = addr_of!(self.value).add(L::child_offset::<T>(
+            let pos = self.value_ptr().add(L::child_offset::<T>(
                 ChildOctant::VALUE,
                 S::USIZE,
                 D::USIZE,
@@ -148,7 +254,7 @@ impl<T: Clone, S: Unsigned, L: OctreeLayout, D: Unsigned, I: Unsigned> OctreeNod
         <<I as Mul<U8>>::Output as Add<ChildOctant::IndexT>>::Output: Unsigned,
     {
         unsafe {
-            let pos = addr_of_mut!(self.value).add(L::child_offset::<T>(
+            let pos = self.value_ptr_mut().add(L::child_offset::<T>(
                 ChildOctant::VALUE,
                 S::USIZE,
                 D::USIZE,
@@ -179,7 +285,7 @@ impl<T: Clone, S: Unsigned, L: OctreeLayout, D: Unsigned, I: Unsigned> OctreeNod
                 S::USIZE,
                 depth,
                 index,
-            )) as *const T;
+            ));
             pos.as_ref().unwrap_unchecked()
         }
 
@@ -197,13 +303,13 @@ impl<T: Clone, S: Unsigned, L: OctreeLayout, D: Unsigned, I: Unsigned> OctreeNod
             let value = base.as_mut().unwrap_unchecked();
             let mut counts = [0u8; 8];
             'outer: for child_i in 0..8 {
-                let child = child_ref::<T, S, L>(&value, child_i, layer_depth, layer_index);
+                let child = child_ref::<T, S, L>(value, child_i, layer_depth, layer_index);
 
                 // TODO: If Hash this is implemented inner loop can be a hash lookup
-                for compared_i in 0..child_i {
-                    let other = child_ref::<T, S, L>(&value, child_i, layer_depth, layer_index);
-                    if matches!(child.eq(other), true) {
-                        counts[compared_i] += 1;
+                for (compared_i, count) in counts.iter_mut().enumerate().take(child_i) {
+                    let other = child_ref::<T, S, L>(value, compared_i, layer_depth, layer_index);
+                    if child.eq(other) {
+                        *count += 1;
                         continue 'outer;
                     }
                 }
@@ -217,11 +323,64 @@ impl<T: Clone, S: Unsigned, L: OctreeLayout, D: Unsigned, I: Unsigned> OctreeNod
                 .map(|it| it.0)
                 .unwrap();
 
-            let largest = child_ref::<T, S, L>(&value, largest_i, layer_depth, layer_index);
+            let largest = child_ref::<T, S, L>(value, largest_i, layer_depth, layer_index);
             *value = largest.clone();
         }
 
-        unsafe { propagate_layer::<T, S, L>(&mut self.value, D::USIZE, I::USIZE) }
+        unsafe { propagate_layer::<T, S, L>(&mut *self.value, D::USIZE, I::USIZE) }
+    }
+
+    /// Propagates values from leaves to the top of this subtree, computing
+    /// each interior node value from its children using `p`.
+    ///
+    /// This is a no-op when the subtree depth is 0 (a single/leaf value).
+    pub fn propagate_up(&mut self, p: &impl Propagator<T>) {
+        unsafe fn propagate_node<T, L: OctreeLayout>(
+            base: *mut T,
+            size: usize,
+            depth: usize,
+            index: usize,
+            p: &impl Propagator<T>,
+        ) {
+            if depth == 0 {
+                return;
+            }
+            let children = Octant::ALL
+                .map(|octant| base.add(L::child_offset::<T>(octant, size, depth, index)));
+            for (octant, child) in Octant::ALL.into_iter().zip(children) {
+                propagate_node::<T, L>(child, size, depth - 1, index * 8 + octant.as_usize(), p);
+            }
+            *base = p.propagate(children.map(|child| &*child));
+        }
+
+        unsafe { propagate_node::<T, L>(self.value_ptr_mut(), S::USIZE, D::USIZE, I::USIZE, p) }
+    }
+
+    /// Sets interior node values of this subtree to boxes enclosing bounds of
+    /// their children, using the [`BoundingBox`] propagator.
+    pub fn reduce_bounding_box(&mut self)
+    where
+        T: Bounded,
+    {
+        self.propagate_up(&BoundingBox)
+    }
+
+    /// Sets every interior node of this subtree to the minimum of its
+    /// children values, bottom-up.
+    pub fn propagate_min(&mut self)
+    where
+        T: Ord,
+    {
+        self.propagate_up(&|children: [&T; 8]| children.into_iter().min().unwrap().clone())
+    }
+
+    /// Sets every interior node of this subtree to the maximum of its
+    /// children values, bottom-up.
+    pub fn propagate_max(&mut self)
+    where
+        T: Ord,
+    {
+        self.propagate_up(&|children: [&T; 8]| children.into_iter().max().unwrap().clone())
     }
 
     /// Returns a tuple of all the children nodes.
@@ -329,6 +488,62 @@ impl<T: Clone, S: Unsigned, L: OctreeLayout, D: Unsigned, I: Unsigned> OctreeNod
     }
 }
 
+/// Calls `f` with a pointer to every leaf of the subtree at `base` along
+/// with the leaf index within the leaf layer.
+///
+/// # Safety
+///
+/// Same requirements as for [`OctreeLayout::fill`] apply.
+unsafe fn visit_leaves<T, L: OctreeLayout>(
+    base: *const T,
+    size: usize,
+    depth: usize,
+    index: usize,
+    f: &mut impl FnMut(*const T, usize),
+) {
+    if depth == 0 {
+        return f(base, index);
+    }
+    for octant in Octant::ALL {
+        let child = base.add(L::child_offset::<T>(octant, size, depth, index));
+        visit_leaves::<T, L>(child, size, depth - 1, index * 8 + octant.as_usize(), f);
+    }
+}
+
+/// Calls `f` with a pointer to every node of the subtree at `base` in
+/// depth-first order, along with the node layer relative to `base`.
+///
+/// # Safety
+///
+/// Same requirements as for [`OctreeLayout::fill`] apply.
+unsafe fn visit_nodes<T, L: OctreeLayout>(
+    base: *const T,
+    size: usize,
+    depth: usize,
+    index: usize,
+    f: &mut impl FnMut(*const T, usize),
+) {
+    unsafe fn visit<T, L: OctreeLayout>(
+        base: *const T,
+        size: usize,
+        depth: usize,
+        index: usize,
+        layer: usize,
+        f: &mut impl FnMut(*const T, usize),
+    ) {
+        f(base, layer);
+        if depth == 0 {
+            return;
+        }
+        for octant in Octant::ALL {
+            let child = base.add(L::child_offset::<T>(octant, size, depth, index));
+            let child_index = index * 8 + octant.as_usize();
+            visit::<T, L>(child, size, depth - 1, child_index, layer + 1, f);
+        }
+    }
+    visit::<T, L>(base, size, depth, index, 0, f)
+}
+
 impl<T: Clone, Size: Unsigned, L: OctreeLayout, Depth: Unsigned, Index: Unsigned> Deref
     for OctreeNode<T, Size, L, Depth, Index>
 {
@@ -339,6 +554,23 @@ impl<T: Clone, Size: Unsigned, L: OctreeLayout, Depth: Unsigned, Index: Unsigned
     }
 }
 
+// A blanket `From<&OctreeNode<T, ..>> for T` isn't allowed by the orphan rules
+// as `T` is uncovered, so conversions are provided for primitive types only.
+macro_rules! impl_value_from_node {
+    ($($t: ty),*) => {$(
+        impl<S: Unsigned, L: OctreeLayout, D: Unsigned, I: Unsigned>
+            From<&OctreeNode<$t, S, L, D, I>> for $t
+        {
+            fn from(node: &OctreeNode<$t, S, L, D, I>) -> Self {
+                *node.value
+            }
+        }
+    )*};
+}
+impl_value_from_node!(
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, bool, char
+);
+
 // TODO: Add a reference wrapper for Octree to allow reading data without copying
 // it first.
 
@@ -346,31 +578,106 @@ impl<T: Clone, Size: Unsigned, L: OctreeLayout, Depth: Unsigned, Index: Unsigned
 ///
 /// This structure is a smart wrapper of `Vec<T>` that provides safe octree
 /// access semantics checked at compile time.
+///
+/// Like `Vec<T>`, it is `Send` and `Sync` whenever `T` is, regardless of
+/// `Depth` and layout.
 #[derive(Debug)]
 #[repr(transparent)]
 pub struct Octree<T: Clone, Depth: Unsigned, L: OctreeLayout = BreathFirst> {
-    data: Vec<T>,
-    _phantom: PhantomData<(Depth, L)>,
+    data: buffer::Buffer<T>,
+    _phantom: PhantomData<fn() -> (Depth, L)>,
 }
 
 impl<T: Clone + Default, Depth: Unsigned, L: OctreeLayout> Default for Octree<T, Depth, L> {
     fn default() -> Self {
-        Self::new(T::default())
+        Self::new_with(|_| T::default())
     }
 }
 
 impl<T: Clone, Depth: Unsigned, L: OctreeLayout> Octree<T, Depth, L> {
     /// Creates an octree with all nodes having the initial `value`.
     pub fn new(value: T) -> Self {
+        Octree {
+            data: vec![value; subtree_length(Depth::USIZE)].into(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Creates an octree with the node at flat data index `i` set to
+    /// `init(i)`.
+    ///
+    /// Unlike [`Octree::new`], this doesn't clone a single prototype value.
+    pub fn new_with(init: impl FnMut(usize) -> T) -> Self {
+        Octree {
+            data: (0..subtree_length(Depth::USIZE)).map(init).collect(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Creates an octree with allocated, but uninitialized values.
+    ///
+    /// This skips the fill done by [`Octree::new`] when all values are going
+    /// to be overwritten anyway, see [`Octree::from_uninit`].
+    ///
+    /// # Safety
+    ///
+    /// The caller must initialize every value (e.g. with [`std::ptr::write`]
+    /// through [`Octree::as_mut_ptr`]) before the octree is read from or
+    /// dropped, or pass it to [`Octree::from_uninit`].
+    pub unsafe fn new_uninit() -> Self {
         let entry_count = subtree_length(Depth::USIZE);
-        let mut result = Octree {
-            data: Vec::with_capacity(entry_count),
+        let mut data = Vec::with_capacity(entry_count);
+        // SAFETY: the caller is responsible for initializing values.
+        #[allow(clippy::uninit_vec)]
+        data.set_len(entry_count);
+        Octree {
+            data: data.into(),
             _phantom: PhantomData,
-        };
-        for i in 0..entry_count {
-            result.data.push(value.clone());
         }
-        result
+    }
+
+    /// Initializes all values of an `uninit` octree, writing `init(i)` into
+    /// the node with flat data index `i`, without reading or dropping the
+    /// previous values.
+    ///
+    /// If `init` panics, the octree memory is leaked.
+    ///
+    /// # Safety
+    ///
+    /// `uninit` must have been created with [`Octree::new_uninit`], or have
+    /// values that don't need to be dropped.
+    pub unsafe fn from_uninit(uninit: Self, mut init: impl FnMut(usize) -> T) -> Self {
+        let mut uninit = std::mem::ManuallyDrop::new(uninit);
+        let base = uninit.data.as_mut_ptr();
+        for i in 0..uninit.data.len() {
+            base.add(i).write(init(i));
+        }
+        std::mem::ManuallyDrop::into_inner(uninit)
+    }
+
+    /// Returns a raw pointer to the root value, followed by all other values
+    /// in `L` layout.
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        self.data.as_mut_ptr()
+    }
+
+    /// Returns the number of nodes in the octree.
+    pub const fn node_count() -> usize {
+        subtree_length(Depth::USIZE)
+    }
+
+    /// Returns the number of interior (non-leaf) nodes in the octree.
+    pub const fn inner_nodes_count() -> usize {
+        subtree_length(Depth::USIZE) - crate::util::layer_length(Depth::USIZE)
+    }
+
+    /// Returns an iterator over values of interior (non-leaf) nodes, ordered
+    /// by layer starting with the root, and by index within each layer.
+    pub fn inner_nodes_iter(&self) -> impl Iterator<Item = &T> {
+        (0..Depth::USIZE).flat_map(move |layer| {
+            (0..crate::util::layer_length(layer))
+                .map(move |index| &self.data[Self::node_data_index(layer, index)])
+        })
     }
 
     /// Returns the byte size of the octree.
@@ -383,6 +690,11 @@ impl<T: Clone, Depth: Unsigned, L: OctreeLayout> Octree<T, Depth, L> {
         crate::util::subtree_layout::<T>(Depth::USIZE)
     }
 
+    /// Returns the name of the octree memory layout.
+    pub const fn layout_name() -> &'static str {
+        L::NAME
+    }
+
     /// Returns a reference to the root node of the octree (first value).
     pub fn root(&self) -> &OctreeNode<T, Depth, L> {
         unsafe {
@@ -403,69 +715,331 @@ impl<T: Clone, Depth: Unsigned, L: OctreeLayout> Octree<T, Depth, L> {
 
     /// Fills the octree with the provided `value`.
     pub fn fill(&mut self, value: T) {
-        self.data.clear();
-        let count = subtree_length(Depth::USIZE);
-        for i in 0..count {
-            self.data.push(value.clone());
+        self.data.fill(value);
+    }
+
+    /// Overwrites all node values with values of the `other` octree.
+    ///
+    /// Unlike assigning a clone, this reuses the existing allocation.
+    pub fn copy_from(&mut self, other: &Self) {
+        self.data.clone_from_slice(&other.data);
+    }
+
+    /// Swaps contents of two octrees without copying node values.
+    pub fn swap(&mut self, other: &mut Self) {
+        std::mem::swap(&mut self.data, &mut other.data);
+    }
+
+    /// Creates an octree that takes ownership of values at `ptr`.
+    ///
+    /// When the octree is dropped, its values are dropped in place and the
+    /// memory is released with `dealloc`, or by the global allocator if
+    /// `dealloc` is `None`. Octrees that don't need to adopt memory allocated
+    /// elsewhere should be created with [`Octree::new`] instead.
+    ///
+    /// # Safety
+    ///
+    /// - `ptr` must be non-null, aligned for `T`, and point to
+    ///   [`Octree::node_count`] initialized values arranged in `L` layout.
+    /// - If `dealloc` is `None`, the memory must have been allocated by the
+    ///   global allocator with [`Octree::layout`].
+    /// - Otherwise, calling `dealloc` with `ptr` and [`Octree::layout`] must
+    ///   release the memory, and nothing else may release it.
+    /// - The memory must not be accessed through other pointers for the
+    ///   lifetime of the octree.
+    pub unsafe fn from_raw_parts(ptr: *mut T, dealloc: Option<Dealloc<T>>) -> Self {
+        Octree {
+            data: buffer::Buffer::from_raw_parts(ptr, Self::node_count(), dealloc),
+            _phantom: PhantomData,
         }
     }
 
-    /*
-    /// Creates a new octree structure with root at the provided `position`.
+    /// Decomposes the octree into a pointer to its values and the function
+    /// that has to be used to release its memory.
+    ///
+    /// The caller becomes responsible for dropping the values and releasing
+    /// the memory, with `dealloc` or the global allocator if it's `None`, as
+    /// described in [`Octree::from_raw_parts`]. Passing both back to
+    /// [`Octree::from_raw_parts`] restores the octree.
+    pub fn into_raw_parts(self) -> (*mut T, Option<Dealloc<T>>) {
+        self.data.into_raw_parts()
+    }
+
+    /// Returns a reference to the leaf value at the given coordinates, or
+    /// `None` if they're outside of the `2^Depth` grid.
+    pub fn get(&self, x: usize, y: usize, z: usize) -> Option<&T> {
+        let side = 1 << Depth::USIZE;
+        if x >= side || y >= side || z >= side {
+            return None;
+        }
+        let index = crate::util::leaf_index_from_coords(x, y, z);
+        Some(&self.data[Self::leaf_data_index(index)])
+    }
+
+    /// Returns the value of the node at the given `path` from the root, or
+    /// `None` if it's longer than `Depth`.
+    pub fn get_path(&self, path: &[Octant]) -> Option<&T> {
+        if path.len() > Depth::USIZE {
+            return None;
+        }
+        let index = crate::util::path_to_index(path);
+        Some(&self.data[Self::node_data_index(path.len(), index)])
+    }
+
+    /// Returns a mutable reference to the leaf value at the given coordinates,
+    /// or `None` if they're outside of the `2^Depth` grid.
+    ///
+    /// Changing a leaf value doesn't update values of its ancestors.
+    pub fn get_mut(&mut self, x: usize, y: usize, z: usize) -> Option<&mut T> {
+        let side = 1 << Depth::USIZE;
+        if x >= side || y >= side || z >= side {
+            return None;
+        }
+        let index = crate::util::leaf_index_from_coords(x, y, z);
+        Some(&mut self.data[Self::leaf_data_index(index)])
+    }
+
+    /// Returns a reference to the leaf value at the given coordinates,
+    /// without checking that they're inside of the `2^Depth` grid.
     ///
     /// # Safety
     ///
-    /// The caller must ensure that the given `position` is a valid pointer to
-    /// the root octree node followed by data arranged in `L` layout.
+    /// All coordinates must be less than `2^Depth`. This is checked with a
+    /// debug assertion.
+    #[inline(always)]
+    pub unsafe fn get_unchecked(&self, x: usize, y: usize, z: usize) -> &T {
+        debug_assert!(
+            x < Self::SIDE && y < Self::SIDE && z < Self::SIDE,
+            "coordinates out of range"
+        );
+        let index = crate::util::leaf_index_from_coords(x, y, z);
+        self.data.get_unchecked(Self::leaf_data_index(index))
+    }
+
+    /// Returns a mutable reference to the leaf value at the given
+    /// coordinates, without checking that they're inside of the `2^Depth`
+    /// grid.
     ///
-    /// It's considered UB if data doesn't follow the layout described by `L`.
+    /// # Safety
     ///
-    /// It is allowed for the data to be uninitialized, but the caller must call
-    /// [`Octree::fill`] before accessing or using any functions that rely on
-    /// the octree being initialized.
-    pub unsafe fn from_root_address(position: *mut T) -> Self {
-        Octree {
-            root: position as *mut OctreeNode<T, Depth, L>,
+    /// All coordinates must be less than `2^Depth`. This is checked with a
+    /// debug assertion.
+    #[inline(always)]
+    pub unsafe fn get_unchecked_mut(&mut self, x: usize, y: usize, z: usize) -> &mut T {
+        debug_assert!(
+            x < Self::SIDE && y < Self::SIDE && z < Self::SIDE,
+            "coordinates out of range"
+        );
+        let index = crate::util::leaf_index_from_coords(x, y, z);
+        self.data.get_unchecked_mut(Self::leaf_data_index(index))
+    }
+
+    /// Returns the `data` index of the `octant` child of the node stored at
+    /// `offset`, with the given (remaining) `depth` and layer `index`.
+    #[inline]
+    fn child_data_index(offset: usize, octant: Octant, depth: usize, index: usize) -> usize {
+        offset + L::child_offset::<T>(octant, Depth::USIZE, depth, index)
+    }
+
+    /// Calls `f` with the `data` index, layer and layer index of every node,
+    /// in depth-first order.
+    fn for_each_node(f: &mut impl FnMut(usize, usize, usize)) {
+        fn visit<T: Clone, Depth: Unsigned, L: OctreeLayout>(
+            offset: usize,
+            depth: usize,
+            index: usize,
+            f: &mut impl FnMut(usize, usize, usize),
+        ) {
+            f(offset, Depth::USIZE - depth, index);
+            if depth == 0 {
+                return;
+            }
+            for octant in Octant::ALL {
+                let child = Octree::<T, Depth, L>::child_data_index(offset, octant, depth, index);
+                visit::<T, Depth, L>(child, depth - 1, index * 8 + octant.as_usize(), f);
+            }
         }
+        visit::<T, Depth, L>(0, Depth::USIZE, 0, f)
+    }
+
+    /// Returns the `data` index of the node at the given `layer` and layer
+    /// `index`.
+    fn node_data_index(layer: usize, index: usize) -> usize {
+        let mut offset = 0;
+        for parent_layer in 0..layer {
+            let shift = (layer - parent_layer) * 3;
+            let octant = Octant::ALL[(index >> (shift - 3)) & 0b111];
+            let depth = Depth::USIZE - parent_layer;
+            offset = Self::child_data_index(offset, octant, depth, index >> shift);
+        }
+        offset
+    }
+
+    /// Returns the `data` index of the leaf with the given morton `index`.
+    #[inline]
+    fn leaf_data_index(index: usize) -> usize {
+        Self::node_data_index(Depth::USIZE, index)
     }
-    */
 
     /// Returns a byte slice of data buffer.
-    pub fn as_bytes(&self) -> &[u8] {
+    ///
+    /// Only available for [`PlainElement`] values, which have no
+    /// uninitialized padding bytes:
+    ///
+    /// ```compile_fail
+    /// use flat_octree::{typenum::U2, Octree};
+    ///
+    /// let tree = Octree::<String, U2>::new(String::new());
+    /// let bytes = tree.as_bytes();
+    /// ```
+    pub fn as_bytes(&self) -> &[u8]
+    where
+        T: PlainElement,
+    {
+        slice_bytes(&self.data)
+    }
+
+    /// Returns a mutable byte slice of data buffer.
+    pub fn as_bytes_mut(&mut self) -> &mut [u8]
+    where
+        T: PlainElement,
+    {
+        slice_bytes_mut(&mut self.data)
+    }
+
+    /// Returns a byte slice of data buffer for any value type.
+    ///
+    /// # Safety
+    ///
+    /// `T` must not contain any padding bytes, as reading them is undefined
+    /// behavior. Bytes of pointers and other resources are returned as is.
+    pub unsafe fn as_bytes_unchecked(&self) -> &[u8] {
+        std::slice::from_raw_parts(
+            self.data.as_ptr() as *const u8,
+            subtree_size::<T>(Depth::USIZE),
+        )
+    }
+
+    /// Converts the octree into an owned byte buffer.
+    ///
+    /// The data buffer is reused when `T` has an alignment of `1`, otherwise
+    /// the bytes have to be copied into a new allocation as the allocator
+    /// requires deallocation with the same alignment.
+    pub fn into_boxed_bytes(self) -> Box<[u8]>
+    where
+        T: PlainElement,
+    {
+        if std::mem::align_of::<T>() != 1 {
+            return self.as_bytes().into();
+        }
+
+        let len = subtree_size::<T>(Depth::USIZE);
+        let data = std::mem::ManuallyDrop::new(self.data.into_vec().into_boxed_slice());
         unsafe {
-            std::slice::from_raw_parts(
-                self.data.as_ptr() as *const u8,
-                subtree_size::<T>(Depth::USIZE),
-            )
+            // SAFETY: T is Copy so it has no drop glue, it has no padding,
+            // and it has the same alignment as u8 so the allocation layout is preserved.
+            Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+                data.as_ptr() as *mut u8,
+                len,
+            ))
         }
     }
 }
 
 impl<T: Clone, D: Unsigned> Octree<T, D, BreathFirst> {
     /// Returns a slice of `T` values at the given `depth`.
-    pub fn layer_slice<Depth: Unsigned>(&self) -> &[T]
+    pub fn layer_slice<Depth>(&self) -> &[T]
     where
-        Depth: IsLessOrEqual<D>,
+        Depth: Unsigned + IsLessOrEqual<D>,
         LeEq<Depth, D>: Same<True>,
     {
-        let skip = (0..Depth::USIZE)
-            .map(|i| crate::util::layer_length(i))
-            .sum();
-        let len = crate::util::layer_length(Depth::USIZE);
-        &self.data[skip..skip + len]
+        &self.data[BreathFirst::layer_range(Depth::USIZE, D::USIZE)]
     }
 
     /// Returns a mutable slice of `T` values at the given `depth`.
-    pub fn layer_slice_mut<Depth: Unsigned>(&mut self) -> &mut [T]
+    pub fn layer_slice_mut<Depth>(&mut self) -> &mut [T]
     where
-        Depth: IsLessOrEqual<D>,
+        Depth: Unsigned + IsLessOrEqual<D>,
         LeEq<Depth, D>: Same<True>,
     {
-        let skip = (0..Depth::USIZE)
-            .map(|i| crate::util::layer_length(i))
-            .sum();
-        let len = crate::util::layer_length(Depth::USIZE);
-        &mut self.data[skip..skip + len]
+        &mut self.data[BreathFirst::layer_range(Depth::USIZE, D::USIZE)]
+    }
+
+    /// Returns a slice of `T` values at the given `depth`, or `None` if it's
+    /// larger than the octree depth.
+    ///
+    /// This is the runtime counterpart of [`Octree::layer_slice`].
+    pub fn layer(&self, depth: usize) -> Option<&[T]> {
+        if depth > D::USIZE {
+            return None;
+        }
+        Some(unsafe { self.layer_unchecked(depth) })
+    }
+
+    /// Returns a slice of `T` values at the given `depth`, without checking
+    /// that it's within the octree.
+    ///
+    /// # Safety
+    ///
+    /// `depth` must not be larger than the octree depth. This is checked with
+    /// a debug assertion.
+    #[inline(always)]
+    pub unsafe fn layer_unchecked(&self, depth: usize) -> &[T] {
+        debug_assert!(depth <= D::USIZE, "depth out of range");
+        // Computed without BreathFirst::layer_range to skip its depth check.
+        let end = crate::util::subtree_length(depth);
+        self.data
+            .get_unchecked(end - crate::util::layer_length(depth)..end)
+    }
+
+    /// Returns a mutable slice of `T` values at the `Parent` depth, along with
+    /// a slice of values at the depth following it.
+    ///
+    /// This allows writing propagation loops without unsafe code.
+    pub fn layer_pair_mut<Parent>(&mut self) -> (&mut [T], &[T])
+    where
+        Parent: Unsigned + IsLess<D>,
+        Le<Parent, D>: Same<True>,
+    {
+        let parent = BreathFirst::layer_range(Parent::USIZE, D::USIZE);
+        let len = parent.len();
+        let (parent, child) = self.data[parent.start..].split_at_mut(len);
+        (parent, &child[..len * 8])
+    }
+
+    /// Returns an iterator over nodes at the `Parent` depth, along with their
+    /// children ordered as [`Octant::ALL`].
+    ///
+    /// This is the mutable counterpart of [`Octree::families`], limited to a
+    /// single layer so that parents can be mutated while children are
+    /// borrowed.
+    pub fn families_mut<Parent>(&mut self) -> impl Iterator<Item = (&mut T, [&T; 8])>
+    where
+        Parent: Unsigned + IsLess<D>,
+        Le<Parent, D>: Same<True>,
+    {
+        let (parents, children) = self.layer_pair_mut::<Parent>();
+        parents
+            .iter_mut()
+            .zip(children.chunks_exact(8))
+            .map(|(parent, children)| (parent, std::array::from_fn(|i| &children[i])))
+    }
+
+    /// Splits the data buffer into values of layers above the given `depth`
+    /// and values of the remaining layers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `depth` is larger than the octree depth.
+    pub fn split_layers_mut(&mut self, depth: usize) -> (&mut [T], &mut [T]) {
+        assert!(
+            depth <= D::USIZE,
+            "depth {} is out of range for octree of depth {}",
+            depth,
+            D::USIZE
+        );
+        self.data
+            .split_at_mut(BreathFirst::layer_range(depth, D::USIZE).start)
     }
 }
 
@@ -473,13 +1047,13 @@ impl<T: Clone, Depth: Unsigned, L: OctreeLayout> Deref for Octree<T, Depth, L> {
     type Target = OctreeNode<T, Depth, L>;
 
     fn deref(&self) -> &Self::Target {
-        unsafe { self.root() }
+        self.root()
     }
 }
 
 impl<T: Clone, Depth: Unsigned, L: OctreeLayout> DerefMut for Octree<T, Depth, L> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        unsafe { self.root_mut() }
+        self.root_mut()
     }
 }
 
@@ -489,6 +1063,22 @@ impl<T: Clone, Depth: Unsigned, L: OctreeLayout> AsRef<[T]> for Octree<T, Depth,
     }
 }
 
+impl<T: Clone, Depth: Unsigned, L: OctreeLayout> AsRef<OctreeNode<T, Depth, L>>
+    for Octree<T, Depth, L>
+{
+    fn as_ref(&self) -> &OctreeNode<T, Depth, L> {
+        self.root()
+    }
+}
+
+impl<T: Clone, Depth: Unsigned, L: OctreeLayout> AsMut<OctreeNode<T, Depth, L>>
+    for Octree<T, Depth, L>
+{
+    fn as_mut(&mut self) -> &mut OctreeNode<T, Depth, L> {
+        self.root_mut()
+    }
+}
+
 /// Allows rearranging octree data between different layouts.
 pub trait FromLayout<Other: OctreeLayout> {
     /// Constructs this octree from a an octree with a different memory
@@ -500,10 +1090,57 @@ pub trait FromLayout<Other: OctreeLayout> {
 mod tests {
     use super::*;
 
+    /// Octree types are `Send`/`Sync` exactly when their values are, and
+    /// node references and views mirror `&T`/`&mut T`.
+    mod auto_traits {
+        use std::{cell::Cell, rc::Rc};
+
+        use static_assertions::{assert_impl_all, assert_not_impl_any};
+
+        use super::*;
+        use crate::{layout::DepthFirst, BitOctree, DynNode, DynOctree, LoggedOctree};
+        use crate::{MinMaxOctree, StreamingWriter};
+
+        assert_impl_all!(BreathFirst: Send, Sync);
+        assert_impl_all!(DepthFirst: Send, Sync);
+
+        assert_impl_all!(Octree<u8, U3>: Send, Sync);
+        assert_impl_all!(Octree<Cell<u8>, U3, DepthFirst>: Send);
+        assert_not_impl_any!(Octree<Cell<u8>, U3>: Sync);
+        assert_not_impl_any!(Octree<Rc<u8>, U3>: Send, Sync);
+
+        assert_impl_all!(OctreeNode<u8, U3, BreathFirst>: Send, Sync);
+        assert_not_impl_any!(OctreeNode<u8, U3, BreathFirst>: Clone, Copy, Default);
+        assert_not_impl_any!(OctreeNode<Rc<u8>, U3, BreathFirst, U1, U5>: Send, Sync);
+        assert_impl_all!(&'static mut OctreeNode<Cell<u8>, U3, DepthFirst>: Send);
+        assert_not_impl_any!(&'static OctreeNode<Cell<u8>, U3, DepthFirst>: Send, Sync);
+
+        assert_impl_all!(DynNode<'static, u8, DepthFirst>: Send, Sync, Copy);
+        assert_not_impl_any!(DynNode<'static, Cell<u8>, BreathFirst>: Send, Sync);
+        assert_impl_all!(DynOctree<u8, DepthFirst>: Send, Sync);
+        assert_not_impl_any!(DynOctree<Rc<u8>>: Send, Sync);
+
+        assert_impl_all!(BitOctree<U3>: Send, Sync);
+        assert_impl_all!(MinMaxOctree<u8, U3>: Send, Sync);
+        assert_impl_all!(LoggedOctree<u8, U3>: Send);
+        assert_not_impl_any!(LoggedOctree<u8, U3>: Sync);
+        assert_impl_all!(StreamingWriter<Vec<u8>, u8, U3>: Send, Sync);
+
+        #[allow(dead_code)]
+        fn independent_of_layout<T: Clone + Send + Sync + 'static, D: Unsigned, L: OctreeLayout>() {
+            fn check<X: Send + Sync>() {}
+            check::<Octree<T, D, L>>();
+            check::<OctreeNode<T, D, L>>();
+            check::<DynNode<'static, T, L>>();
+            check::<DynOctree<T, L>>();
+            check::<BitOctree<D, L>>();
+        }
+    }
+
     #[test]
     fn octree_index_bf_test() {
         let test = Octree::<usize, U3>::new(1);
-        let root = test.data.as_ptr() as *const usize;
+        let root = test.data.as_ptr();
 
         unsafe {
             assert_eq!(
@@ -556,6 +1193,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn octree_propagate_common_test() {
+        let mut test = Octree::<u8, U1>::new(0);
+        test.data[1] = 1;
+        for value in test.data[2..].iter_mut() {
+            *value = 2;
+        }
+        test.propagate_common();
+        assert_eq!(test.data[0], 2);
+
+        for (value, child) in test.data[1..].iter_mut().zip([1, 3, 3, 1, 1, 3, 3, 3]) {
+            *value = child;
+        }
+        test.propagate_common();
+        assert_eq!(test.data[0], 3);
+    }
+
     #[test]
     fn octree_get_set_bf_test() {
         let mut test = Octree::<usize, U2>::new(1);
@@ -653,6 +1307,381 @@ mod tests {
         assert_eq!(*test.child::<OctantRUB>().child::<OctantRUB>().value(), 2);
     }
 
+    #[test]
+    fn octree_value_or_default_test() {
+        let mut test = Octree::<u32, U1>::new(3);
+        test.child_mut::<OctantRDB>().set_value(5);
+        assert_eq!(test.value_or_default(), 3);
+        assert_eq!(test.child::<OctantRDB>().value_or_default(), 5);
+        assert_ne!(test.child::<OctantLDF>().value_or_default(), u32::default());
+    }
+
+    #[test]
+    fn octree_node_into_value_test() {
+        fn take_value<V>(value: impl Into<V>) -> V {
+            value.into()
+        }
+
+        let mut test = Octree::<u32, U1>::new(1);
+        test.child_mut::<OctantRUF>().set_value(2);
+        assert_eq!(u32::from(test.root()), 1);
+        assert_eq!(take_value::<u32>(test.child::<OctantRUF>()), 2);
+
+        let test = Octree::<f32, U1, crate::layout::DepthFirst>::new(0.5);
+        assert_eq!(f32::from(test.child::<OctantLDB>()), 0.5);
+    }
+
+    #[test]
+    fn octree_as_ref_node_test() {
+        fn root_value(node: impl AsRef<OctreeNode<u32, U2, BreathFirst>>) -> u32 {
+            *node.as_ref().value()
+        }
+        fn set_root(mut node: impl AsMut<OctreeNode<u32, U2, BreathFirst>>, value: u32) {
+            node.as_mut().set_value(value);
+        }
+
+        let mut test = Octree::<u32, U2>::new(1);
+        assert_eq!(root_value(&test), 1);
+
+        set_root(&mut test, 2);
+        assert_eq!(root_value(&test), 2);
+        assert_eq!(**test.child::<OctantRUB>().child::<OctantLDF>(), 2);
+    }
+
+    #[test]
+    fn octree_new_uninit_test() {
+        let uninit = unsafe { Octree::<u32, U3>::new_uninit() };
+        assert_eq!(uninit.data.len(), subtree_length(3));
+        assert_eq!(
+            std::mem::size_of_val(&*uninit.data),
+            Octree::<u32, U3>::size()
+        );
+
+        let test = unsafe { Octree::from_uninit(uninit, |i| i as u32) };
+        assert_eq!(**test, 0);
+        assert_eq!(**test.child::<OctantRDF>(), 2);
+        assert_eq!(test.data.last(), Some(&(subtree_length(3) as u32 - 1)));
+
+        let mut test = unsafe { Octree::<String, U1, crate::layout::DepthFirst>::new_uninit() };
+        for i in 0..9 {
+            unsafe { test.as_mut_ptr().add(i).write(i.to_string()) };
+        }
+        assert_eq!(test.child::<OctantRUB>().value(), "8");
+    }
+
+    #[test]
+    fn octree_raw_parts_test() {
+        use std::{
+            alloc::{alloc, dealloc},
+            rc::Rc,
+            sync::atomic::{AtomicUsize, Ordering},
+        };
+
+        static RELEASED: AtomicUsize = AtomicUsize::new(0);
+        unsafe fn counting_dealloc(ptr: *mut Rc<u8>, layout: Layout) {
+            RELEASED.fetch_add(1, Ordering::SeqCst);
+            dealloc(ptr as *mut u8, layout);
+        }
+
+        let shared = Rc::new(5u8);
+        let layout = Octree::<Rc<u8>, U1>::layout();
+        let test = unsafe {
+            let ptr = alloc(layout) as *mut Rc<u8>;
+            for i in 0..9 {
+                ptr.add(i).write(shared.clone());
+            }
+            Octree::<Rc<u8>, U1>::from_raw_parts(ptr, Some(counting_dealloc))
+        };
+        assert_eq!(Rc::strong_count(&shared), 10);
+        assert_eq!(**test.child::<OctantRUB>().value(), 5);
+
+        let (ptr, release) = test.into_raw_parts();
+        assert_eq!(RELEASED.load(Ordering::SeqCst), 0);
+        assert_eq!(Rc::strong_count(&shared), 10);
+
+        let test = unsafe { Octree::<Rc<u8>, U1>::from_raw_parts(ptr, release) };
+        drop(test);
+        assert_eq!(RELEASED.load(Ordering::SeqCst), 1);
+        assert_eq!(Rc::strong_count(&shared), 1);
+
+        let (ptr, release) = Octree::<u32, U2>::new(3).into_raw_parts();
+        assert!(release.is_none());
+        let test = unsafe { Octree::<u32, U2>::from_raw_parts(ptr, release) };
+        assert_eq!(**test.child::<OctantLUB>(), 3);
+    }
+
+    #[test]
+    fn octree_copy_from_test() {
+        let mut front = Octree::<u8, U2>::new(0);
+        let mut back = Octree::<u8, U2>::new(1);
+        back.child_mut::<OctantLUB>().set_value(2);
+
+        front.copy_from(&back);
+        assert_eq!(front.data, back.data);
+
+        back.fill(3);
+        assert_eq!(*front.child::<OctantLUB>().child::<OctantRDF>().value(), 2);
+        assert_eq!(*front.root().value(), 1);
+    }
+
+    #[test]
+    fn octree_swap_test() {
+        let mut current = Octree::<u8, U2, crate::layout::DepthFirst>::new(0);
+        let mut next = Octree::<u8, U2, crate::layout::DepthFirst>::new(1);
+        next.child_mut::<OctantRDB>().set_value(2);
+        let next_ptr = next.data.as_ptr();
+
+        current.swap(&mut next);
+        assert_eq!(current.data.as_ptr(), next_ptr);
+        assert_eq!(*current.root().value(), 1);
+        assert_eq!(*current.child::<OctantRDB>().value(), 2);
+        assert!(next.data.iter().all(|it| *it == 0));
+    }
+
+    #[test]
+    fn octree_node_forget_test() {
+        let test = Octree::<String, U1>::new("value".to_string());
+        let node = unsafe { std::ptr::read(test.child::<OctantRUB>()) };
+        std::mem::forget(node);
+        assert_eq!(**test.child::<OctantRUB>(), "value");
+    }
+
+    #[test]
+    fn octree_inner_nodes_test() {
+        type Df = Octree<usize, U3, crate::layout::DepthFirst>;
+        let mut test = Df::new(0);
+        for (i, value) in test.data.iter_mut().enumerate() {
+            *value = i;
+        }
+        assert_eq!(
+            Df::inner_nodes_count() + test.leaf_count(),
+            Df::node_count()
+        );
+        assert_eq!(Octree::<u8, U0>::inner_nodes_count(), 0);
+
+        let inner: Vec<_> = test.inner_nodes_iter().copied().collect();
+        assert_eq!(inner.len(), Df::inner_nodes_count());
+        assert_eq!(inner[..2], [0, 1]);
+        assert_eq!(inner[8], 1 + 7 * 73);
+        assert_eq!(inner[9], 2);
+        let mut sorted = inner.clone();
+        sorted.sort_unstable();
+        sorted.dedup();
+        assert_eq!(sorted.len(), inner.len());
+        assert!(inner.iter().all(|it| test.data[*it] == *it));
+
+        let bf = Octree::<u8, U3>::new(1);
+        assert_eq!(bf.inner_nodes_iter().count(), 73);
+    }
+
+    #[test]
+    fn octree_get_test() {
+        let mut bf = Octree::<usize, U2>::new(0);
+        let mut df = Octree::<usize, U2, crate::layout::DepthFirst>::new(0);
+        bf.child_mut::<OctantRDF>()
+            .child_mut::<OctantLUB>()
+            .set_value(1);
+        df.child_mut::<OctantRDF>()
+            .child_mut::<OctantLUB>()
+            .set_value(1);
+
+        assert_eq!(bf.get(2, 1, 1), Some(&1));
+        assert_eq!(df.get(2, 1, 1), Some(&1));
+        assert_eq!(bf.get(1, 2, 1), Some(&0));
+        assert_eq!(df.get(1, 2, 1), Some(&0));
+        assert_eq!(bf.get(4, 0, 0), None);
+
+        *bf.get_mut(3, 3, 3).unwrap() = 2;
+        *df.get_mut(3, 3, 3).unwrap() = 2;
+        assert_eq!(
+            **bf.child::<OctantRUB>().child::<OctantRUB>(),
+            **df.child::<OctantRUB>().child::<OctantRUB>()
+        );
+        assert_eq!(**df.child::<OctantRUB>().child::<OctantRUB>(), 2);
+    }
+
+    #[test]
+    fn octree_get_unchecked_test() {
+        let mut test = Octree::<usize, U2, crate::layout::DepthFirst>::new(0);
+        for (x, y, z) in [(0, 0, 0), (2, 1, 3), (3, 3, 3)] {
+            unsafe { *test.get_unchecked_mut(x, y, z) = x + y * 4 + z * 16 };
+        }
+        for (x, y, z) in [(0, 0, 0), (2, 1, 3), (3, 3, 3), (1, 2, 0)] {
+            assert_eq!(
+                unsafe { test.get_unchecked(x, y, z) },
+                test.get(x, y, z).unwrap()
+            );
+        }
+        assert_eq!(test.get(2, 1, 3), Some(&(2 + 4 + 48)));
+
+        let bf = Octree::<usize, U2>::new_with(|i| i);
+        assert_eq!(bf.layer(1), Some(&bf.data[1..9]));
+        assert_eq!(bf.layer(2), Some(bf.layer_slice::<U2>()));
+        assert_eq!(unsafe { bf.layer_unchecked(0) }, &[0]);
+        assert_eq!(bf.layer(3), None);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "coordinates out of range")]
+    fn octree_get_unchecked_debug_test() {
+        let test = Octree::<u8, U2>::new(0);
+        unsafe { test.get_unchecked(0, 4, 0) };
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "coordinates out of range")]
+    fn octree_get_unchecked_mut_debug_test() {
+        let mut test = Octree::<u8, U2>::new(0);
+        unsafe { *test.get_unchecked_mut(0, 0, 4) = 1 };
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "depth out of range")]
+    fn octree_layer_unchecked_debug_test() {
+        let test = Octree::<u8, U2>::new(0);
+        unsafe { test.layer_unchecked(3) };
+    }
+
+    #[test]
+    fn octree_layout_name_test() {
+        assert_eq!(Octree::<u8, U2>::layout_name(), "breadth_first");
+        assert_eq!(
+            Octree::<u8, U2, crate::layout::DepthFirst>::layout_name(),
+            "depth_first"
+        );
+        assert_eq!(<crate::layout::BF as OctreeLayout>::NAME, "breadth_first");
+    }
+
+    #[test]
+    fn octree_new_with_test() {
+        use std::sync::Arc;
+
+        let shared = Arc::new(5u8);
+        let test = Octree::<Arc<u8>, U3>::new_with(|_| shared.clone());
+        assert_eq!(Arc::strong_count(&shared), 1 + subtree_length(3));
+        drop(test);
+        assert_eq!(Arc::strong_count(&shared), 1);
+
+        let test = Octree::<Arc<usize>, U2, crate::layout::DepthFirst>::new_with(Arc::new);
+        assert!(test.data.iter().enumerate().all(|(i, it)| **it == i));
+        assert!(test.data.iter().all(|it| Arc::strong_count(it) == 1));
+
+        let test = Octree::<Arc<u8>, U2>::default();
+        assert!(test
+            .data
+            .iter()
+            .all(|it| Arc::strong_count(it) == 1 && **it == 0));
+    }
+
+    #[test]
+    fn octree_as_bytes_test() {
+        let mut test = Octree::<u16, U1>::new(0x0102);
+        test.child_mut::<OctantRDF>().set_value(0x0304);
+        let bytes = test.as_bytes();
+        assert_eq!(bytes.len(), 9 * 2);
+        assert_eq!(&bytes[..2], &0x0102u16.to_ne_bytes());
+        assert_eq!(&bytes[4..6], &0x0304u16.to_ne_bytes());
+        assert_eq!(unsafe { test.as_bytes_unchecked() }, bytes);
+
+        test.as_bytes_mut()[16..].copy_from_slice(&7u16.to_ne_bytes());
+        assert_eq!(**test.child::<OctantRUB>(), 7);
+
+        let test = Octree::<[f32; 2], U1>::new([1.0, -2.5]);
+        assert_eq!(&test.as_bytes()[4..8], &(-2.5f32).to_ne_bytes());
+        let test = Octree::<i64, U0>::new(-1);
+        assert!(test.as_bytes().iter().all(|it| *it == 0xFF));
+    }
+
+    #[test]
+    fn octree_into_boxed_bytes_test() {
+        let mut test = Octree::<u32, U1>::new(0x01020304);
+        test.child_mut::<OctantRUB>().set_value(7);
+        let expected = test.as_bytes().to_vec();
+        let bytes = test.into_boxed_bytes();
+        assert_eq!(bytes.len(), 9 * 4);
+        assert_eq!(&*bytes, &expected[..]);
+
+        let mut test = Octree::<[u8; 3], U1>::new([1, 2, 3]);
+        test.child_mut::<OctantLDF>().set_value([4, 5, 6]);
+        let expected = test.as_bytes().to_vec();
+        let bytes = test.into_boxed_bytes();
+        assert_eq!(bytes.len(), 9 * 3);
+        assert_eq!(&*bytes, &expected[..]);
+        assert_eq!(&bytes[..6], &[1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn octree_propagate_min_max_test() {
+        fn leaves<L: OctreeLayout>() -> Octree<u32, U2, L> {
+            let mut test = Octree::<u32, U2, L>::new(0);
+            for i in 0..64 {
+                let (x, y, z) = crate::util::leaf_index_to_coords(i);
+                *test.get_mut(x, y, z).unwrap() = (i as u32 * 37) % 64 + 1;
+            }
+            test
+        }
+
+        fn check<L: OctreeLayout>() {
+            let mut min = leaves::<L>();
+            min.propagate_min();
+            let mut max = leaves::<L>();
+            max.propagate_max();
+
+            assert_eq!(**min, 1);
+            assert_eq!(**max, 64);
+            for octant in Octant::ALL {
+                let (min, max) = (min.dyn_root(), max.dyn_root());
+                let (min, max) = (min.child(octant).unwrap(), max.child(octant).unwrap());
+                let children: Vec<_> = Octant::ALL
+                    .iter()
+                    .map(|it| *min.child(*it).unwrap().value())
+                    .collect();
+                assert_eq!(*min.value(), *children.iter().min().unwrap());
+                assert_eq!(*max.value(), *children.iter().max().unwrap());
+            }
+            assert_eq!(**min.child::<OctantLDF>(), 1);
+            assert_eq!(**max.child::<OctantLDF>(), 58);
+        }
+
+        check::<BreathFirst>();
+        check::<crate::layout::DepthFirst>();
+    }
+
+    #[test]
+    fn octree_layer_pair_mut_test() {
+        let mut test = Octree::<usize, U2>::new(0);
+        for (i, leaf) in test.layer_slice_mut::<U2>().iter_mut().enumerate() {
+            *leaf = i;
+        }
+
+        let (parent, child) = test.layer_pair_mut::<U1>();
+        assert_eq!(parent.len(), 8);
+        assert_eq!(child.len(), 64);
+        for (i, value) in parent.iter_mut().enumerate() {
+            *value = child[i * 8..(i + 1) * 8].iter().sum();
+        }
+        let (parent, child) = test.layer_pair_mut::<U0>();
+        assert_eq!((parent.len(), child.len()), (1, 8));
+        parent[0] = child.iter().sum();
+
+        assert_eq!(**test, (0..64).sum());
+        assert_eq!(**test.child::<OctantLDF>(), (0..8).sum());
+        assert_eq!(**test.child::<OctantRUB>(), (56..64).sum());
+
+        let (upper, lower) = test.split_layers_mut(2);
+        assert_eq!((upper.len(), lower.len()), (9, 64));
+        upper[1] = 0;
+        lower[0] = 1;
+        assert_eq!(**test.child::<OctantLDF>(), 0);
+        assert_eq!(**test.child::<OctantLDF>().child::<OctantLDF>(), 1);
+
+        let (upper, lower) = test.split_layers_mut(0);
+        assert_eq!((upper.len(), lower.len()), (0, 73));
+    }
+
     #[test]
     fn octree_layout_bf_test() {
         let mut test = Octree::<usize, U2>::new(1);
@@ -673,7 +1702,7 @@ mod tests {
             2, 2, 2, 2, 6, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2,
         ];
 
-        let base_addr = unsafe { std::mem::transmute::<_, *const usize>(test) };
+        let base_addr = test.data.as_ptr();
 
         for (i, value) in expected_data.into_iter().enumerate() {
             assert_eq!(unsafe { *(base_addr.add(i)) }, value);
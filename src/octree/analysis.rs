@@ -0,0 +1,600 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use typenum::Unsigned;
+
+use crate::{
+    layout::OctreeLayout,
+    octant::Octant,
+    util::{layer_length, leaf_index_from_coords},
+};
+
+use super::{visit_leaves, visit_nodes, Octree, OctreeNode, UniformMask};
+
+/// Summary of octree structure and content, returned by [`Octree::stats`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct OctreeStats {
+    /// Depth of the octree.
+    pub depth: usize,
+    /// Number of nodes, including leaves.
+    pub node_count: usize,
+    /// Number of leaves.
+    pub leaf_count: usize,
+    /// Size of the data buffer in bytes.
+    pub byte_size: usize,
+    /// Number of distinct values across all nodes.
+    pub distinct_values: usize,
+    /// Number of interior nodes whose subtree is uniform, as reported by
+    /// [`UniformMask::is_uniform`].
+    pub uniform_subtrees: usize,
+    /// Shannon entropy of each layer, as returned by
+    /// [`OctreeNode::layer_entropy`].
+    pub layer_entropy: Vec<f64>,
+}
+
+/// Returns the Shannon entropy (in bits) of a value distribution with the
+/// given `counts`.
+fn entropy<T>(counts: &HashMap<T, usize>) -> f64 {
+    let total: usize = counts.values().sum();
+    counts
+        .values()
+        .map(|count| {
+            let p = *count as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum::<f64>()
+        .max(0.0)
+}
+
+impl<T: Clone, S: Unsigned, L: OctreeLayout, D: Unsigned, I: Unsigned> OctreeNode<T, S, L, D, I> {
+    /// Returns the number of leaves in this subtree.
+    pub const fn leaf_count(&self) -> usize {
+        layer_length(D::USIZE)
+    }
+
+    /// Returns value counts for each layer of this subtree, starting with the
+    /// layer of this node.
+    pub fn layer_histogram(&self) -> Vec<HashMap<T, usize>>
+    where
+        T: Eq + Hash,
+    {
+        let mut result = vec![HashMap::new(); D::USIZE + 1];
+        unsafe {
+            visit_nodes::<T, L>(
+                self.value_ptr(),
+                S::USIZE,
+                D::USIZE,
+                I::USIZE,
+                &mut |node, layer| {
+                    *result[layer].entry((*node).clone()).or_insert(0) += 1;
+                },
+            );
+        }
+        result
+    }
+
+    /// Returns the Shannon entropy (in bits) of value distribution for each
+    /// layer of this subtree, starting with the layer of this node.
+    ///
+    /// Entropy is `0` for uniform layers, and grows to `log2(n)` for layers
+    /// with `n` equally frequent values.
+    pub fn layer_entropy(&self) -> Vec<f64>
+    where
+        T: Eq + Hash,
+    {
+        self.layer_histogram().iter().map(entropy).collect()
+    }
+
+    /// Returns the number of leaves in this subtree for which `pred` holds.
+    ///
+    /// This is a linear count which tests every leaf of the subtree, as values
+    /// of interior nodes aren't required to describe their leaves, so no
+    /// subtree can be skipped. For repeated queries, use
+    /// [`Octree::build_counts`] instead, which answers them from stored
+    /// counts.
+    pub fn count_in_subtree(&self, pred: impl Fn(&T) -> bool) -> usize {
+        let mut count = 0;
+        unsafe {
+            visit_leaves::<T, L>(
+                self.value_ptr(),
+                S::USIZE,
+                D::USIZE,
+                I::USIZE,
+                &mut |leaf, _| {
+                    if pred(&*leaf) {
+                        count += 1;
+                    }
+                },
+            );
+        }
+        count
+    }
+}
+
+impl<T: Clone, Depth: Unsigned, L: OctreeLayout> Octree<T, Depth, L> {
+    /// Returns a summary of the octree structure and content.
+    ///
+    /// Value metrics share a single histogram pass over all nodes, followed
+    /// by a pass building a [`UniformMask`].
+    pub fn stats(&self) -> OctreeStats
+    where
+        T: Eq + Hash,
+    {
+        let histogram = self.layer_histogram();
+        let distinct_values = histogram
+            .iter()
+            .flat_map(|counts| counts.keys())
+            .collect::<HashSet<_>>()
+            .len();
+        OctreeStats {
+            depth: Depth::USIZE,
+            node_count: Self::node_count(),
+            leaf_count: self.leaf_count(),
+            byte_size: std::mem::size_of_val::<[T]>(&self.data),
+            distinct_values,
+            uniform_subtrees: self.build_uniform_mask().uniform_count(),
+            layer_entropy: histogram.iter().map(entropy).collect(),
+        }
+    }
+
+    /// Returns an octree where each node stores the number of leaves in its
+    /// subtree for which `pred` holds.
+    ///
+    /// Counts are computed bottom-up in a single pass.
+    pub fn build_counts(&self, pred: impl Fn(&T) -> bool) -> Octree<u32, Depth, L> {
+        fn count_node<T: Clone, Depth: Unsigned, L: OctreeLayout>(
+            source: &Octree<T, Depth, L>,
+            counts: &mut Octree<u32, Depth, L>,
+            pred: &impl Fn(&T) -> bool,
+            offset: usize,
+            depth: usize,
+            index: usize,
+        ) -> u32 {
+            let count = if depth == 0 {
+                pred(&source.data[offset]) as u32
+            } else {
+                Octant::ALL
+                    .into_iter()
+                    .map(|octant| {
+                        let child =
+                            Octree::<T, Depth, L>::child_data_index(offset, octant, depth, index);
+                        let child_index = index * 8 + octant.as_usize();
+                        count_node(source, counts, pred, child, depth - 1, child_index)
+                    })
+                    .sum()
+            };
+            counts.data[offset] = count;
+            count
+        }
+
+        let mut counts = Octree::new(0);
+        count_node(self, &mut counts, &pred, 0, Depth::USIZE, 0);
+        counts
+    }
+
+    /// Returns the number of leaves for which `f` holds.
+    ///
+    /// Interior nodes aren't counted. This scans all leaves, see
+    /// [`Octree::build_counts`] for repeated queries over subtrees.
+    pub fn count_leaves_satisfying<F: Fn(&T) -> bool>(&self, f: F) -> usize {
+        self.root().count_in_subtree(f)
+    }
+
+    /// Returns the number of leaves equal to `value`.
+    ///
+    /// With a `mask` built for this octree, subtrees it marks as uniform are
+    /// counted by comparing only their root value.
+    pub fn count_leaves_eq(&self, value: &T, mask: Option<&UniformMask<Depth>>) -> usize
+    where
+        T: PartialEq,
+    {
+        self.count_eq_in(0, 0, 0, value, mask)
+    }
+
+    fn count_eq_in(
+        &self,
+        offset: usize,
+        layer: usize,
+        index: usize,
+        value: &T,
+        mask: Option<&UniformMask<Depth>>,
+    ) -> usize
+    where
+        T: PartialEq,
+    {
+        let depth = Depth::USIZE - layer;
+        if depth == 0 || mask.is_some_and(|it| it.is_uniform(layer, index)) {
+            return if self.data[offset] == *value {
+                layer_length(depth)
+            } else {
+                0
+            };
+        }
+        Octant::ALL
+            .into_iter()
+            .map(|octant| {
+                let child = Self::child_data_index(offset, octant, depth, index);
+                self.count_eq_in(child, layer + 1, index * 8 + octant.as_usize(), value, mask)
+            })
+            .sum()
+    }
+
+    /// Returns the number of parent-child edges where the child value differs
+    /// from the parent value.
+    pub fn count_level_transitions(&self) -> usize
+    where
+        T: PartialEq,
+    {
+        self.families()
+            .map(|(parent, children)| children.iter().filter(|it| **it != parent).count())
+            .sum()
+    }
+
+    /// Returns `(layer, index)` pairs of all nodes whose values differ from
+    /// values of the same nodes in `other`, in depth-first order.
+    pub fn diff(&self, other: &Self) -> Vec<(usize, usize)>
+    where
+        T: PartialEq,
+    {
+        let mut result = Vec::new();
+        self.diff_in(other, 0, Depth::USIZE, 0, &mut result);
+        result
+    }
+
+    pub(super) fn diff_in(
+        &self,
+        other: &Self,
+        offset: usize,
+        depth: usize,
+        index: usize,
+        result: &mut Vec<(usize, usize)>,
+    ) where
+        T: PartialEq,
+    {
+        if self.data[offset] != other.data[offset] {
+            result.push((Depth::USIZE - depth, index));
+        }
+        if depth == 0 {
+            return;
+        }
+        for octant in Octant::ALL {
+            let child = Self::child_data_index(offset, octant, depth, index);
+            self.diff_in(
+                other,
+                child,
+                depth - 1,
+                index * 8 + octant.as_usize(),
+                result,
+            );
+        }
+    }
+
+    /// Returns the highest `y` coordinate of a leaf for which `solid` holds in
+    /// each `(x, z)` column of the `2^Depth` grid, or `usize::MAX` for empty
+    /// columns.
+    ///
+    /// Columns are ordered with `x` changing fastest, i.e. the height of a
+    /// column is at `x + z * 2^Depth`. Each column is scanned top-down, until
+    /// the first solid leaf.
+    pub fn heightmap<F: Fn(&T) -> bool>(&self, solid: F) -> Vec<usize> {
+        let side = 1 << Depth::USIZE;
+        let mut result = Vec::with_capacity(side * side);
+        for z in 0..side {
+            for x in 0..side {
+                let height = (0..side).rev().find(|y| {
+                    solid(&self.data[Self::leaf_data_index(leaf_index_from_coords(x, *y, z))])
+                });
+                result.push(height.unwrap_or(usize::MAX));
+            }
+        }
+        result
+    }
+
+    /// Returns the inclusive `(min, max)` coordinates of the smallest box
+    /// enclosing all leaves for which `solid` holds, or `None` if there are no
+    /// such leaves.
+    ///
+    /// Subtrees fully within the bounds found so far are skipped.
+    pub fn occupied_bounds<F: Fn(&T) -> bool>(&self, solid: F) -> Option<([usize; 3], [usize; 3])> {
+        let mut bounds = None;
+        self.occupied_bounds_in(0, Depth::USIZE, 0, [0; 3], &solid, &mut bounds);
+        bounds
+    }
+
+    fn occupied_bounds_in(
+        &self,
+        offset: usize,
+        depth: usize,
+        index: usize,
+        origin: [usize; 3],
+        solid: &impl Fn(&T) -> bool,
+        bounds: &mut Option<([usize; 3], [usize; 3])>,
+    ) {
+        let size = 1 << depth;
+        if let Some((min, max)) = bounds {
+            if (0..3).all(|i| min[i] <= origin[i] && origin[i] + size - 1 <= max[i]) {
+                return;
+            }
+        }
+        if depth == 0 {
+            if solid(&self.data[offset]) {
+                *bounds = Some(match *bounds {
+                    Some((min, max)) => (
+                        [0, 1, 2].map(|i| min[i].min(origin[i])),
+                        [0, 1, 2].map(|i| max[i].max(origin[i])),
+                    ),
+                    None => (origin, origin),
+                });
+            }
+            return;
+        }
+
+        let half = size / 2;
+        for octant in Octant::ALL {
+            let i = octant.as_usize();
+            let child_origin = [
+                origin[0] + (i & 1) * half,
+                origin[1] + ((i >> 1) & 1) * half,
+                origin[2] + ((i >> 2) & 1) * half,
+            ];
+            let child = Self::child_data_index(offset, octant, depth, index);
+            self.occupied_bounds_in(child, depth - 1, index * 8 + i, child_origin, solid, bounds);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typenum::U3;
+
+    use super::*;
+    use crate::{
+        layout::DepthFirst,
+        octant::*,
+        util::{layer_length, subtree_length},
+    };
+
+    fn decorate<L: OctreeLayout>(test: &mut Octree<u8, U3, L>) {
+        test.child_mut::<OctantRDF>().set_value(1);
+        test.child_mut::<OctantLUB>()
+            .child_mut::<OctantRUF>()
+            .set_value(1);
+        test.child_mut::<OctantRUB>()
+            .child_mut::<OctantLDF>()
+            .child_mut::<OctantRDB>()
+            .set_value(1);
+        *test.get_mut(7, 0, 7).unwrap() = 1;
+    }
+
+    fn check_counts<L: OctreeLayout>() {
+        let mut test = Octree::<u8, U3, L>::new(0);
+        decorate(&mut test);
+        let counts = test.build_counts(|v| *v == 1);
+
+        let brute = (0..layer_length(3))
+            .filter(|i| test.data[Octree::<u8, U3, L>::leaf_data_index(*i)] == 1)
+            .count();
+        assert_eq!(brute, 64 + 8 + 1 + 1);
+        assert_eq!(**counts as usize, brute);
+        assert_eq!(test.count_in_subtree(|v| *v == 1), brute);
+        assert_eq!(test.child::<OctantLUB>().count_in_subtree(|v| *v == 1), 8);
+
+        fn check_node<L: OctreeLayout>(node: crate::DynNode<u32, L>) {
+            if node.is_leaf() {
+                return;
+            }
+            let children = Octant::ALL.map(|octant| node.child(octant).unwrap());
+            let sum: u32 = children.iter().map(|it| *it.value()).sum();
+            assert_eq!(*node.value(), sum);
+            children.into_iter().for_each(check_node);
+        }
+        check_node(counts.dyn_root());
+    }
+
+    fn check_level_transitions<L: OctreeLayout>() {
+        let mut test = Octree::<usize, U3, L>::new(4);
+        assert_eq!(test.count_level_transitions(), 0);
+
+        test.child_mut::<OctantLUB>().set_value(1);
+        assert_eq!(test.count_level_transitions(), 1);
+        *test.get_mut(0, 0, 0).unwrap() = 2;
+        assert_eq!(test.count_level_transitions(), 2);
+
+        for (i, value) in test.data.iter_mut().enumerate() {
+            *value = i;
+        }
+        assert_eq!(test.count_level_transitions(), subtree_length(3) - 1);
+    }
+
+    #[test]
+    fn level_transitions_test() {
+        check_level_transitions::<crate::layout::BreathFirst>();
+        check_level_transitions::<DepthFirst>();
+    }
+
+    fn check_diff<L: OctreeLayout>() {
+        let mut a = Octree::<u8, U3, L>::new(0);
+        decorate(&mut a);
+        let mut b = Octree::<u8, U3, L>::new(0);
+        decorate(&mut b);
+        assert!(a.diff(&b).is_empty());
+
+        *b.get_mut(0, 7, 0).unwrap() = 2;
+        b.child_mut::<OctantRDF>()
+            .child_mut::<OctantLDF>()
+            .set_value(0);
+        let diff = b.diff(&a);
+        assert_eq!(diff.len(), 1 + 9);
+        assert_eq!(diff[0], (2, 0o10));
+        assert_eq!(diff[1], (3, 0o100));
+        assert_eq!(diff[2], (3, 0o101));
+        assert_eq!(diff[9], (3, leaf_index_from_coords(0, 7, 0)));
+        for (layer, index) in diff {
+            let offset = Octree::<u8, U3, L>::node_data_index(layer, index);
+            assert_ne!(a.data[offset], b.data[offset]);
+        }
+    }
+
+    #[test]
+    fn diff_test() {
+        check_diff::<crate::layout::BreathFirst>();
+        check_diff::<DepthFirst>();
+    }
+
+    #[test]
+    fn count_leaves_satisfying_test() {
+        let mut test = Octree::<u8, typenum::U1, DepthFirst>::new(2);
+        test.child_mut::<OctantRDB>().set_value(1);
+        test.child_mut::<OctantLUF>().set_value(1);
+        test.child_mut::<OctantLDF>().set_value(3);
+
+        assert_eq!(test.count_leaves_satisfying(|v| *v == 1), 2);
+        assert_eq!(test.count_leaves_satisfying(|v| *v == 2), 5);
+        assert_eq!(test.count_leaves_satisfying(|v| *v == 3), 1);
+        assert_eq!(test.count_leaves_satisfying(|_| true), 8);
+    }
+
+    #[test]
+    fn layer_histogram_test() {
+        let mut test = Octree::<u8, U3, DepthFirst>::new(0);
+        decorate(&mut test);
+
+        let histogram = test.layer_histogram();
+        assert_eq!(histogram.len(), 4);
+        assert_eq!(histogram[0], HashMap::from([(0, 1)]));
+        assert_eq!(histogram[1], HashMap::from([(0, 7), (1, 1)]));
+        assert_eq!(histogram[2], HashMap::from([(0, 55), (1, 9)]));
+        assert_eq!(histogram[3].values().sum::<usize>(), test.leaf_count());
+        assert_eq!(histogram[3][&1], 74);
+
+        let histogram = test.child::<OctantRDF>().layer_histogram();
+        assert_eq!(histogram.len(), 3);
+        assert_eq!(histogram[2], HashMap::from([(1, 64)]));
+    }
+
+    #[test]
+    fn layer_entropy_test() {
+        let test = Octree::<u8, U3>::new(0);
+        assert_eq!(test.layer_entropy(), vec![0.0; 4]);
+
+        let mut test = Octree::<u16, U3, DepthFirst>::new(0);
+        Octree::<u16, U3, DepthFirst>::for_each_node(&mut |offset, layer, index| {
+            test.data[offset] = if layer == 3 { index as u16 % 16 } else { 0 };
+        });
+        // 16 equally frequent values.
+        assert!((test.layer_entropy()[3] - 4.0).abs() < 1e-12);
+
+        test.child_mut::<OctantRUB>().set_value(1);
+        let entropy = test.layer_entropy();
+        assert_eq!(entropy[0], 0.0);
+        // One of 8 nodes differs.
+        let expected = -(7.0 / 8.0 * (7.0f64 / 8.0).log2()) - 1.0 / 8.0 * (1.0f64 / 8.0).log2();
+        assert!((entropy[1] - expected).abs() < 1e-12);
+        assert!(entropy[3] < 4.0);
+    }
+
+    fn check_stats<L: OctreeLayout>() {
+        let mut test = Octree::<u8, U3, L>::new(0);
+        let stats = test.stats();
+        assert_eq!(stats.depth, 3);
+        assert_eq!(stats.node_count, subtree_length(3));
+        assert_eq!(stats.leaf_count, 512);
+        assert_eq!(stats.byte_size, subtree_length(3));
+        assert_eq!(stats.distinct_values, 1);
+        assert_eq!(stats.uniform_subtrees, 73);
+        assert_eq!(stats.layer_entropy, vec![0.0; 4]);
+
+        decorate(&mut test);
+        *test.get_mut(7, 0, 2).unwrap() = 9;
+        let stats = test.stats();
+        assert_eq!(stats.node_count, Octree::<u8, U3, L>::node_count());
+        assert_eq!(stats.leaf_count, test.leaf_count());
+        assert_eq!(stats.byte_size, test.as_bytes().len());
+        let values: HashSet<u8> = test.data.iter().copied().collect();
+        assert_eq!(stats.distinct_values, values.len());
+        let mask = test.build_uniform_mask();
+        let uniform = (0..3)
+            .map(|layer| {
+                (0..layer_length(layer))
+                    .filter(|index| mask.is_uniform(layer, *index))
+                    .count()
+            })
+            .sum::<usize>();
+        assert_eq!(stats.uniform_subtrees, uniform);
+        assert!(uniform > 0 && uniform < 73);
+        assert_eq!(stats.layer_entropy, test.layer_entropy());
+
+        let wide = Octree::<u32, U3, L>::new(0).stats();
+        assert_eq!(wide.byte_size, subtree_length(3) * 4);
+    }
+
+    #[test]
+    fn stats_test() {
+        check_stats::<crate::layout::BreathFirst>();
+        check_stats::<DepthFirst>();
+    }
+
+    fn check_occupied_bounds<L: OctreeLayout>() {
+        let mut test = Octree::<u8, U3, L>::new(0);
+        assert_eq!(test.occupied_bounds(|v| *v == 1), None);
+
+        *test.get_mut(5, 2, 6).unwrap() = 1;
+        assert_eq!(
+            test.occupied_bounds(|v| *v == 1),
+            Some(([5, 2, 6], [5, 2, 6]))
+        );
+
+        decorate(&mut test);
+        assert_eq!(
+            test.occupied_bounds(|v| *v == 1),
+            Some(([2, 0, 0], [7, 7, 7]))
+        );
+
+        let mut test = Octree::<u8, U3, L>::new(0);
+        *test.get_mut(1, 6, 3).unwrap() = 1;
+        *test.get_mut(4, 2, 5).unwrap() = 1;
+        *test.get_mut(3, 3, 2).unwrap() = 1;
+        assert_eq!(
+            test.occupied_bounds(|v| *v == 1),
+            Some(([1, 2, 2], [4, 6, 5]))
+        );
+    }
+
+    fn check_heightmap<L: OctreeLayout>() {
+        let mut test = Octree::<u8, U3, L>::new(0);
+        assert!(test
+            .heightmap(|v| *v == 1)
+            .iter()
+            .all(|it| *it == usize::MAX));
+
+        test.fill_box([0, 0, 0], [7, 2, 7], 1);
+        let heights = test.heightmap(|v| *v == 1);
+        assert_eq!(heights, vec![2; 64]);
+
+        *test.get_mut(5, 6, 1).unwrap() = 1;
+        *test.get_mut(5, 4, 1).unwrap() = 1;
+        let heights = test.heightmap(|v| *v == 1);
+        assert_eq!(heights[5 + 8], 6);
+        assert_eq!(heights.iter().filter(|it| **it == 2).count(), 63);
+    }
+
+    #[test]
+    fn heightmap_test() {
+        check_heightmap::<crate::layout::BreathFirst>();
+        check_heightmap::<DepthFirst>();
+    }
+
+    #[test]
+    fn occupied_bounds_test() {
+        check_occupied_bounds::<crate::layout::BreathFirst>();
+        check_occupied_bounds::<DepthFirst>();
+    }
+
+    #[test]
+    fn build_counts_test() {
+        check_counts::<crate::layout::BreathFirst>();
+        check_counts::<DepthFirst>();
+    }
+}
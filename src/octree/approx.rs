@@ -0,0 +1,128 @@
+use typenum::Unsigned;
+
+use crate::{approx::ApproxElem, error::ApproxMismatch, layout::OctreeLayout, util::layer_length};
+
+use super::Octree;
+
+impl<T: Clone, Depth: Unsigned, L: OctreeLayout> Octree<T, Depth, L> {
+    /// Returns `true` if `eq` holds for all pairs of leaf values at the same
+    /// position in both octrees.
+    ///
+    /// Values of interior nodes aren't compared.
+    pub fn eq_leaves<F: Fn(&T, &T) -> bool>(&self, other: &Self, eq: F) -> bool {
+        (0..layer_length(Depth::USIZE)).all(|index| {
+            let offset = Self::leaf_data_index(index);
+            eq(&self.data[offset], &other.data[offset])
+        })
+    }
+}
+
+impl<T: ApproxElem, Depth: Unsigned, L: OctreeLayout> Octree<T, Depth, L> {
+    /// Returns `true` if all node values are within tolerance of `other`
+    /// values.
+    ///
+    /// See [`ApproxElem`] for how tolerances are applied.
+    pub fn approx_eq(&self, other: &Self, abs_tol: T, rel_tol: T) -> bool {
+        self.try_approx_eq(other, abs_tol, rel_tol).is_ok()
+    }
+
+    /// Compares node values with `other` values like [`Octree::approx_eq`],
+    /// returning the flat index of the first mismatched node on failure.
+    pub fn try_approx_eq(
+        &self,
+        other: &Self,
+        abs_tol: T,
+        rel_tol: T,
+    ) -> Result<(), ApproxMismatch> {
+        match self
+            .data
+            .iter()
+            .zip(&other.data)
+            .position(|(a, b)| !a.approx_eq(b, &abs_tol, &rel_tol))
+        {
+            Some(index) => Err(ApproxMismatch { index }),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the largest (component-wise) absolute difference between node
+    /// values of two octrees.
+    pub fn max_abs_diff(&self, other: &Self) -> T {
+        let mut diffs = self
+            .data
+            .iter()
+            .zip(&other.data)
+            .map(|(a, b)| a.abs_diff(b));
+        let first = diffs.next().expect("octree is never empty");
+        diffs.fold(first, T::max_elem)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typenum::U2;
+
+    use super::*;
+    use crate::{layout::DepthFirst, test_support::xorshift};
+
+    #[test]
+    fn approx_eq_test() {
+        let a = Octree::<f32, U2, DepthFirst>::new(0.5);
+        let mut b = Octree::<f32, U2, DepthFirst>::new(0.5);
+        b.data[17] += 1e-7;
+
+        assert!(a.approx_eq(&b, 1e-6, 0.0));
+        assert!(a.approx_eq(&b, 0.0, 1e-6));
+        assert!(!a.approx_eq(&b, 0.0, 0.0));
+        assert_eq!(
+            a.try_approx_eq(&b, 0.0, 0.0),
+            Err(ApproxMismatch { index: 17 })
+        );
+        assert!(a.max_abs_diff(&b) > 0.0 && a.max_abs_diff(&b) < 1e-6);
+        assert_eq!(a.max_abs_diff(&a), 0.0);
+    }
+
+    fn check_eq_leaves<L: OctreeLayout>() {
+        let mut state = 0x2545_f491;
+        let mut a = Octree::<f32, U2, L>::new(0.0);
+        for value in a.data.iter_mut() {
+            *value = (xorshift(&mut state) % 1000) as f32 / 1000.0;
+        }
+        let mut b = a.map(|it| *it);
+        for value in b.data.iter_mut() {
+            let noise = (xorshift(&mut state) % 100) as f32 * 1e-8 - 5e-7;
+            *value += noise;
+        }
+        let close = |a: &f32, b: &f32| (a - b).abs() < 1e-4;
+
+        assert!(a.eq_leaves(&b, close));
+        assert!(!a.eq_leaves(&b, |a, b| a == b));
+
+        // Interior nodes are ignored.
+        b.data[Octree::<f32, U2, L>::node_data_index(1, 3)] = -1.0;
+        assert!(a.eq_leaves(&b, close));
+        b.data[Octree::<f32, U2, L>::leaf_data_index(42)] = -1.0;
+        assert!(!a.eq_leaves(&b, close));
+    }
+
+    #[test]
+    fn eq_leaves_test() {
+        check_eq_leaves::<crate::layout::BreathFirst>();
+        check_eq_leaves::<DepthFirst>();
+    }
+
+    #[test]
+    fn approx_eq_array_test() {
+        let a = Octree::<[f64; 2], U2>::new([1.0, 2.0]);
+        let mut b = Octree::<[f64; 2], U2>::new([1.0, 2.0]);
+        b.data[40][1] = 2.25;
+        b.data[3][0] = 0.5;
+
+        assert_eq!(a.max_abs_diff(&b), [0.5, 0.25]);
+        assert!(a.approx_eq(&b, [0.5, 0.25], [0.0; 2]));
+        assert_eq!(
+            a.try_approx_eq(&b, [0.0, 0.5], [0.0; 2]),
+            Err(ApproxMismatch { index: 3 })
+        );
+    }
+}
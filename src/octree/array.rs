@@ -0,0 +1,124 @@
+use ndarray::{Array3, ArrayView1, ArrayView3, ErrorKind, ShapeBuilder, ShapeError};
+use typenum::Unsigned;
+
+use crate::{
+    layout::{BreathFirst, OctreeLayout},
+    propagate::Propagator,
+    util::{layer_length, leaf_index_from_coords, subtree_length},
+};
+
+use super::Octree;
+
+/// Way of filling interior nodes of an octree constructed from leaf values.
+pub enum FillMode<'a, T> {
+    /// All interior nodes are set to the value.
+    Value(T),
+    /// Interior nodes are computed from their children with the propagator.
+    Propagate(&'a dyn Propagator<T>),
+}
+
+impl<T: Clone, Depth: Unsigned, L: OctreeLayout> Octree<T, Depth, L> {
+    /// Returns leaf values as an array indexed with `[x, y, z]`.
+    ///
+    /// The array uses column-major (Fortran) memory order, so `x` changes the
+    /// fastest in memory, matching [`Octree::to_dense_grid`].
+    pub fn to_array3(&self) -> Array3<T> {
+        let side = Self::SIDE;
+        Array3::from_shape_vec((side, side, side).f(), self.to_dense_grid())
+            .expect("dense grid has side cubed values")
+    }
+
+    /// Constructs an octree with leaves set to values of `array` indexed with
+    /// `[x, y, z]`, and interior nodes filled as described by `fill`.
+    ///
+    /// Returns an [`IncompatibleShape`](ErrorKind::IncompatibleShape) error if
+    /// `array` isn't a cube with `2^Depth` values along each side.
+    pub fn from_array3(array: &ArrayView3<T>, fill: FillMode<T>) -> Result<Self, ShapeError> {
+        let side = Self::SIDE;
+        if array.shape() != [side; 3] {
+            return Err(ShapeError::from_kind(ErrorKind::IncompatibleShape));
+        }
+
+        let (mut result, propagator) = match fill {
+            FillMode::Value(value) => (Self::new(value), None),
+            FillMode::Propagate(p) => (Self::new(array[[0, 0, 0]].clone()), Some(p)),
+        };
+        for ((x, y, z), value) in array.indexed_iter() {
+            result.data[Self::leaf_data_index(leaf_index_from_coords(x, y, z))] = value.clone();
+        }
+        if let Some(p) = propagator {
+            result.propagate_up(&|children: [&T; 8]| p.propagate(children));
+        }
+        Ok(result)
+    }
+}
+
+impl<T: Clone, Depth: Unsigned> Octree<T, Depth, BreathFirst> {
+    /// Returns a view of the leaf layer, without copying.
+    ///
+    /// Leaves are in morton order (see
+    /// [`leaf_index_from_coords`](crate::util::leaf_index_from_coords)),
+    /// which can't be described with strides of a 3D view, so this view is
+    /// one-dimensional. Use [`Octree::to_array3`] for an array indexed with
+    /// coordinates.
+    pub fn leaf_view(&self) -> ArrayView1<'_, T> {
+        let start = subtree_length(Depth::USIZE) - layer_length(Depth::USIZE);
+        ArrayView1::from(&self.data[start..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::Array3;
+    use typenum::{U2, U3};
+
+    use super::*;
+    use crate::{layout::DepthFirst, propagate::MostCommon};
+
+    fn check_round_trip<L: OctreeLayout>() {
+        let array = Array3::from_shape_fn((8, 8, 8), |(x, y, z)| (x + y * 10 + z * 100) as u16);
+        let test = Octree::<u16, U3, L>::from_array3(&array.view(), FillMode::Value(0)).unwrap();
+        assert_eq!(test.get(3, 5, 7), Some(&753));
+        assert_eq!(**test, 0);
+        assert_eq!(test.to_array3(), array);
+        assert_eq!(
+            test.to_array3().as_slice_memory_order().unwrap(),
+            &test.to_dense_grid()[..]
+        );
+
+        let test =
+            Octree::<u16, U3, L>::from_array3(&array.view(), FillMode::Propagate(&MostCommon))
+                .unwrap();
+        assert_eq!(**test, 0);
+        assert_eq!(**test.child::<crate::octant::OctantRUB>(), 444);
+    }
+
+    #[test]
+    fn array3_round_trip_test() {
+        check_round_trip::<BreathFirst>();
+        check_round_trip::<DepthFirst>();
+    }
+
+    #[test]
+    fn array3_shape_error_test() {
+        let fill = || FillMode::Value(0);
+        let flat = Array3::<u8>::zeros((8, 8, 4));
+        let small = Array3::<u8>::zeros((4, 4, 4));
+        let error = Octree::<u8, U3>::from_array3(&flat.view(), fill()).err();
+        assert_eq!(error.unwrap().kind(), ErrorKind::IncompatibleShape);
+        assert!(Octree::<u8, U3>::from_array3(&small.view(), fill()).is_err());
+        assert!(Octree::<u8, U2>::from_array3(&small.view(), fill()).is_ok());
+    }
+
+    #[test]
+    fn leaf_view_test() {
+        let array = Array3::from_shape_fn((4, 4, 4), |(x, y, z)| (x + y * 4 + z * 16) as u8);
+        let test = Octree::<u8, U2>::from_array3(&array.view(), FillMode::Value(0)).unwrap();
+        let view = test.leaf_view();
+        assert_eq!(view.len(), 64);
+        for (index, value) in view.iter().enumerate() {
+            let (x, y, z) = crate::util::leaf_index_to_coords(index);
+            assert_eq!(*value, array[[x, y, z]]);
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use std::marker::PhantomData;
+
+use typenum::Unsigned;
+
+use crate::{
+    layout::{BreathFirst, OctreeLayout},
+    util::{leaf_index_from_coords, subtree_length},
+};
+
+use super::Octree;
+
+/// Octree of packed boolean values.
+///
+/// Node order matches that of an [`Octree`] with the same `Depth` and layout,
+/// but each node takes up a single bit.
+#[derive(Debug, Clone)]
+pub struct BitOctree<Depth: Unsigned, L: OctreeLayout = BreathFirst> {
+    bits: Vec<u64>,
+    _phantom: PhantomData<fn() -> (Depth, L)>,
+}
+
+impl<Depth: Unsigned, L: OctreeLayout> BitOctree<Depth, L> {
+    /// Creates a bit octree with all nodes set to `value`.
+    pub fn new(value: bool) -> Self {
+        let len = subtree_length(Depth::USIZE);
+        let word = if value { u64::MAX } else { 0 };
+        let mut bits = vec![word; len.div_ceil(64)];
+        // Keeps bits past the last node cleared, so that they don't affect
+        // comparisons.
+        if !len.is_multiple_of(64) {
+            *bits.last_mut().unwrap() &= (1 << (len % 64)) - 1;
+        }
+        BitOctree {
+            bits,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the packed bits, with node `i` stored in bit `i % 64` of word
+    /// `i / 64`.
+    ///
+    /// Bits past the last node are zero.
+    pub fn as_words(&self) -> &[u64] {
+        &self.bits
+    }
+
+    /// Returns the bit of the node stored at `data` index `i`.
+    #[inline]
+    fn bit(&self, i: usize) -> bool {
+        self.bits[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    #[inline]
+    fn set_bit(&mut self, i: usize, value: bool) {
+        if value {
+            self.bits[i / 64] |= 1 << (i % 64);
+        } else {
+            self.bits[i / 64] &= !(1 << (i % 64));
+        }
+    }
+
+    /// Returns the bit of a node at the given `layer` and layer `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `layer` is greater than `Depth` or `index` is outside the
+    /// layer.
+    pub fn node(&self, layer: usize, index: usize) -> bool {
+        assert!(layer <= Depth::USIZE, "layer {} out of range", layer);
+        assert!(
+            index < crate::util::layer_length(layer),
+            "index out of range"
+        );
+        self.bit(Octree::<(), Depth, L>::node_data_index(layer, index))
+    }
+
+    /// Returns the leaf bit at the given coordinates, or `None` if they're
+    /// outside of the `2^Depth` grid.
+    pub fn get(&self, x: usize, y: usize, z: usize) -> Option<bool> {
+        let side = 1 << Depth::USIZE;
+        if x >= side || y >= side || z >= side {
+            return None;
+        }
+        let index = leaf_index_from_coords(x, y, z);
+        Some(self.bit(Octree::<(), Depth, L>::leaf_data_index(index)))
+    }
+
+    /// Sets the leaf bit at the given coordinates.
+    ///
+    /// Changing a leaf bit doesn't update bits of its ancestors.
+    ///
+    /// # Panics
+    ///
+    /// Panics if coordinates are outside of the `2^Depth` grid.
+    pub fn set(&mut self, x: usize, y: usize, z: usize, value: bool) {
+        let side = 1 << Depth::USIZE;
+        assert!(x < side && y < side && z < side, "coordinates out of range");
+        let index = leaf_index_from_coords(x, y, z);
+        self.set_bit(Octree::<(), Depth, L>::leaf_data_index(index), value);
+    }
+}
+
+impl<Depth: Unsigned, L: OctreeLayout> PartialEq for BitOctree<Depth, L> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bits == other.bits
+    }
+}
+
+impl<Depth: Unsigned, L: OctreeLayout> Eq for BitOctree<Depth, L> {}
+
+impl<T: Clone, Depth: Unsigned, L: OctreeLayout> Octree<T, Depth, L> {
+    /// Returns a packed octree with bits of every node set to the result of
+    /// `solid` for its value.
+    pub fn occupancy<F: Fn(&T) -> bool>(&self, solid: F) -> BitOctree<Depth, L> {
+        let mut result = BitOctree::new(false);
+        for (i, value) in self.data.iter().enumerate() {
+            if solid(value) {
+                result.set_bit(i, true);
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typenum::U3;
+
+    use super::*;
+    use crate::{layout::DepthFirst, test_support::xorshift};
+
+    fn check_occupancy<L: OctreeLayout>() {
+        let mut test = Octree::<u32, U3, L>::new(0);
+        let mut state = 0x9e37_79b9_u32;
+        for z in 0..8 {
+            for y in 0..8 {
+                for x in 0..8 {
+                    *test.get_mut(x, y, z).unwrap() = xorshift(&mut state) % 3;
+                }
+            }
+        }
+        test.data[0] = 1;
+
+        let bits = test.occupancy(|v| *v == 1);
+        for z in 0..8 {
+            for y in 0..8 {
+                for x in 0..8 {
+                    assert_eq!(bits.get(x, y, z), Some(*test.get(x, y, z).unwrap() == 1));
+                }
+            }
+        }
+        assert!(bits.node(0, 0));
+        assert!(!bits.node(1, 3));
+        assert_eq!(bits.get(8, 0, 0), None);
+        assert_eq!(bits.as_words().len(), 10);
+    }
+
+    #[test]
+    fn occupancy_test() {
+        check_occupancy::<BreathFirst>();
+        check_occupancy::<DepthFirst>();
+    }
+
+    #[test]
+    fn bit_octree_set_test() {
+        let mut test = BitOctree::<U3, DepthFirst>::new(true);
+        test.set(3, 4, 5, false);
+        assert_eq!(test.get(3, 4, 5), Some(false));
+        assert_eq!(test.get(4, 4, 5), Some(true));
+        assert!(test.node(0, 0));
+    }
+
+    #[test]
+    fn bit_octree_eq_test() {
+        let full = Octree::<u8, U3>::new(1).occupancy(|v| *v == 1);
+        assert!(BitOctree::<U3>::new(true) == full);
+        assert_eq!(full.as_words()[9], (1 << (585 % 64)) - 1);
+        assert!(
+            BitOctree::<U3, DepthFirst>::new(false)
+                == Octree::<u8, U3, DepthFirst>::new(1).occupancy(|v| *v == 0)
+        );
+        let mut test = BitOctree::<U3>::new(true);
+        test.set(1, 2, 3, false);
+        assert!(test != full);
+    }
+}
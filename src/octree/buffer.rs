@@ -0,0 +1,164 @@
+use std::{alloc::Layout, fmt, mem::ManuallyDrop, ops::Deref, ops::DerefMut, ptr::NonNull};
+
+/// Function used to release memory of an adopted octree allocation, see
+/// [`Octree::from_raw_parts`](crate::Octree::from_raw_parts).
+pub type Dealloc<T> = unsafe fn(*mut T, Layout);
+
+/// Fixed-length buffer of octree values.
+///
+/// Values are either stored in an allocation of the global allocator (as if
+/// it was a `Vec<T>` with capacity equal to its length), or in a foreign
+/// allocation which is released with a provided deallocation function.
+pub(crate) struct Buffer<T> {
+    ptr: NonNull<T>,
+    len: usize,
+    dealloc: Option<Dealloc<T>>,
+}
+
+// SAFETY: Buffer owns its values like Vec<T> does.
+unsafe impl<T: Send> Send for Buffer<T> {}
+// SAFETY: Buffer only hands out shared references to values through &self.
+unsafe impl<T: Sync> Sync for Buffer<T> {}
+
+impl<T> Buffer<T> {
+    /// Adopts `len` values at `ptr`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to `len` initialized values. If `dealloc` is `None`,
+    /// the memory must have been allocated by the global allocator with the
+    /// layout of a `[T; len]` array, otherwise `dealloc` must accept `ptr`
+    /// along with that layout.
+    pub(crate) unsafe fn from_raw_parts(
+        ptr: *mut T,
+        len: usize,
+        dealloc: Option<Dealloc<T>>,
+    ) -> Self {
+        Buffer {
+            ptr: NonNull::new(ptr).expect("null buffer pointer"),
+            len,
+            dealloc,
+        }
+    }
+
+    /// Returns the pointer to the first value and the deallocation function,
+    /// leaving the caller responsible for dropping values and releasing the
+    /// memory.
+    pub(crate) fn into_raw_parts(self) -> (*mut T, Option<Dealloc<T>>) {
+        let this = ManuallyDrop::new(self);
+        (this.ptr.as_ptr(), this.dealloc)
+    }
+
+    /// Converts the buffer into a `Vec`, copying values out of foreign
+    /// allocations.
+    pub(crate) fn into_vec(self) -> Vec<T> {
+        let len = self.len;
+        let (ptr, dealloc) = self.into_raw_parts();
+        unsafe {
+            match dealloc {
+                // SAFETY: ptr was allocated by the global allocator with
+                // capacity len.
+                None => Vec::from_raw_parts(ptr, len, len),
+                Some(dealloc) => {
+                    let mut result = Vec::with_capacity(len);
+                    // SAFETY: values are moved into the new allocation and not
+                    // dropped in the old one.
+                    std::ptr::copy_nonoverlapping(ptr, result.as_mut_ptr(), len);
+                    result.set_len(len);
+                    dealloc(ptr, array_layout::<T>(len));
+                    result
+                }
+            }
+        }
+    }
+}
+
+fn array_layout<T>(len: usize) -> Layout {
+    Layout::array::<T>(len).expect("buffer too large")
+}
+
+impl<T> Drop for Buffer<T> {
+    fn drop(&mut self) {
+        unsafe {
+            match self.dealloc {
+                // SAFETY: ptr was allocated by the global allocator with
+                // capacity len.
+                None => drop(Vec::from_raw_parts(self.ptr.as_ptr(), self.len, self.len)),
+                Some(dealloc) => {
+                    // SAFETY: all values are initialized and dropped once.
+                    std::ptr::drop_in_place(std::ptr::slice_from_raw_parts_mut(
+                        self.ptr.as_ptr(),
+                        self.len,
+                    ));
+                    dealloc(self.ptr.as_ptr(), array_layout::<T>(self.len));
+                }
+            }
+        }
+    }
+}
+
+impl<T> From<Vec<T>> for Buffer<T> {
+    fn from(value: Vec<T>) -> Self {
+        let mut value = ManuallyDrop::new(value.into_boxed_slice());
+        Buffer {
+            ptr: NonNull::new(value.as_mut_ptr()).unwrap(),
+            len: value.len(),
+            dealloc: None,
+        }
+    }
+}
+
+impl<T> FromIterator<T> for Buffer<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        iter.into_iter().collect::<Vec<_>>().into()
+    }
+}
+
+impl<T> Deref for Buffer<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        // SAFETY: ptr points to len initialized values.
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T> DerefMut for Buffer<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        // SAFETY: ptr points to len initialized values.
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T: Clone> Clone for Buffer<T> {
+    fn clone(&self) -> Self {
+        self.to_vec().into()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Buffer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: PartialEq> PartialEq for Buffer<T> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: PartialEq> PartialEq<Vec<T>> for Buffer<T> {
+    fn eq(&self, other: &Vec<T>) -> bool {
+        **self == **other
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Buffer<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
@@ -0,0 +1,231 @@
+use std::{
+    marker::PhantomData,
+    ops::{Index, IndexMut},
+};
+
+use typenum::Unsigned;
+
+use crate::{layout::OctreeLayout, octant::Axis, util::leaf_index_from_coords};
+
+use super::Octree;
+
+/// Returns the morton index bits of the given `axis`.
+pub(super) const fn axis_mask(axis: Axis) -> usize {
+    match axis {
+        Axis::X => leaf_index_from_coords(usize::MAX, 0, 0),
+        Axis::Y => leaf_index_from_coords(0, usize::MAX, 0),
+        Axis::Z => leaf_index_from_coords(0, 0, usize::MAX),
+    }
+}
+
+/// Returns the morton index of the leaf following the one at `index` along
+/// the axis with the given `mask`, incrementing only the masked bits.
+#[inline]
+pub(super) const fn next_along(index: usize, mask: usize) -> usize {
+    ((index | !mask).wrapping_add(1) & mask) | (index & !mask)
+}
+
+/// Returns the morton index of the leaf at position `k` along the `axis`,
+/// with the other two coordinates set to `u` and `v` (in `x`, `y`, `z`
+/// order).
+fn column_leaf_index(axis: Axis, u: usize, v: usize, k: usize) -> usize {
+    match axis {
+        Axis::X => leaf_index_from_coords(k, u, v),
+        Axis::Y => leaf_index_from_coords(u, k, v),
+        Axis::Z => leaf_index_from_coords(u, v, k),
+    }
+}
+
+/// Mutable view of a column of leaves, passed to the callback of
+/// [`Octree::for_each_column`].
+///
+/// Leaves are indexed by their coordinate along the column axis.
+pub struct ColumnMut<'a, T> {
+    data: *mut T,
+    indices: &'a [usize],
+    _phantom: PhantomData<&'a mut [T]>,
+}
+
+impl<'a, T> ColumnMut<'a, T> {
+    /// Returns the number of leaves in the column.
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Returns `true` if the column has no leaves.
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Returns an iterator over leaves of the column.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + '_ {
+        self.indices.iter().map(|i| unsafe {
+            // SAFETY: indices are in bounds of data.
+            &*self.data.add(*i)
+        })
+    }
+
+    /// Returns an iterator over mutable leaves of the column.
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut T> + '_ {
+        let data = self.data;
+        self.indices.iter().map(move |i| unsafe {
+            // SAFETY: indices are distinct and in bounds of data.
+            &mut *data.add(*i)
+        })
+    }
+}
+
+impl<'a, T> Index<usize> for ColumnMut<'a, T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        unsafe {
+            // SAFETY: indices are in bounds of data.
+            &*self.data.add(self.indices[index])
+        }
+    }
+}
+
+impl<'a, T> IndexMut<usize> for ColumnMut<'a, T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        unsafe {
+            // SAFETY: indices are in bounds of data.
+            &mut *self.data.add(self.indices[index])
+        }
+    }
+}
+
+impl<T: Clone, Depth: Unsigned, L: OctreeLayout> Octree<T, Depth, L> {
+    /// Returns an iterator over the `2^Depth` leaves along the `axis`, with
+    /// the other two coordinates set to `u` and `v` (in `x`, `y`, `z` order).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `u` or `v` is outside of the `2^Depth` grid.
+    pub fn column_iter(
+        &self,
+        axis: Axis,
+        u: usize,
+        v: usize,
+    ) -> impl DoubleEndedIterator<Item = &T> {
+        assert!(u < Self::SIDE && v < Self::SIDE, "coordinates out of range");
+        (0..Self::SIDE)
+            .map(move |k| &self.data[Self::leaf_data_index(column_leaf_index(axis, u, v, k))])
+    }
+
+    /// Returns an iterator over the `2^Depth` mutable leaves along the
+    /// `axis`, with the other two coordinates set to `u` and `v` (in `x`, `y`,
+    /// `z` order).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `u` or `v` is outside of the `2^Depth` grid.
+    pub fn column_iter_mut(
+        &mut self,
+        axis: Axis,
+        u: usize,
+        v: usize,
+    ) -> impl DoubleEndedIterator<Item = &mut T> {
+        assert!(u < Self::SIDE && v < Self::SIDE, "coordinates out of range");
+        let data = self.data.as_mut_ptr();
+        (0..Self::SIDE).map(move |k| unsafe {
+            // SAFETY: every leaf of the column has a distinct data index.
+            &mut *data.add(Self::leaf_data_index(column_leaf_index(axis, u, v, k)))
+        })
+    }
+
+    /// Calls `f` with the `u` and `v` coordinates (in `x`, `y`, `z` order) of
+    /// every column of leaves along the `axis`, and a mutable view of it.
+    ///
+    /// Columns are visited with `u` changing the fastest. Leaf indices are
+    /// stepped along the morton bits of the axis instead of being computed
+    /// from coordinates, which makes this cheaper than calling
+    /// [`Octree::column_iter_mut`] for every column.
+    pub fn for_each_column(&mut self, axis: Axis, mut f: impl FnMut(usize, usize, ColumnMut<T>)) {
+        let mask = axis_mask(axis);
+        let data = self.data.as_mut_ptr();
+        let mut indices = vec![0; Self::SIDE];
+        for v in 0..Self::SIDE {
+            for u in 0..Self::SIDE {
+                let mut index = column_leaf_index(axis, u, v, 0);
+                for it in indices.iter_mut() {
+                    *it = Self::leaf_data_index(index);
+                    index = next_along(index, mask);
+                }
+                f(
+                    u,
+                    v,
+                    ColumnMut {
+                        data,
+                        indices: &indices,
+                        _phantom: PhantomData,
+                    },
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typenum::U3;
+
+    use super::*;
+    use crate::layout::{BreathFirst, DepthFirst};
+
+    fn check_columns<L: OctreeLayout>() {
+        let mut test = Octree::<usize, U3, L>::new(0);
+        for (k, leaf) in test.column_iter_mut(Axis::Y, 5, 2).enumerate() {
+            *leaf = k + 1;
+        }
+        for y in 0..8 {
+            assert_eq!(test.get(5, y, 2), Some(&(y + 1)));
+        }
+        assert_eq!(test.get(5, 0, 3), Some(&0));
+        let reversed: Vec<usize> = test.column_iter(Axis::Y, 5, 2).rev().copied().collect();
+        assert_eq!(reversed, (1..=8).rev().collect::<Vec<_>>());
+
+        for (x, y, z) in [(0, 0, 0), (3, 6, 1), (7, 7, 7)] {
+            *test.get_mut(x, y, z).unwrap() = 100 + x + y * 8 + z * 64;
+        }
+        assert_eq!(
+            test.column_iter(Axis::Z, 3, 6).nth(1),
+            Some(&(100 + 3 + 6 * 8 + 64))
+        );
+        assert_eq!(
+            test.column_iter(Axis::X, 7, 7).next_back(),
+            Some(&(100 + 7 * 73))
+        );
+
+        let snapshot = test.map(|it| *it);
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            let mut visited = Vec::new();
+            test.for_each_column(axis, |u, v, column| {
+                assert_eq!(column.len(), 8);
+                assert!(column.iter().eq(snapshot.column_iter(axis, u, v)));
+                assert!(column
+                    .iter()
+                    .rev()
+                    .eq(snapshot.column_iter(axis, u, v).rev()));
+                visited.push((u, v));
+            });
+            assert_eq!(visited.len(), 64);
+            assert_eq!(visited[1], (1, 0));
+        }
+
+        test.for_each_column(Axis::X, |u, v, mut column| {
+            for leaf in column.iter_mut().rev() {
+                *leaf = u * 8 + v;
+            }
+            column[0] += 1000;
+        });
+        assert_eq!(test.get(0, 2, 5), Some(&(1000 + 2 * 8 + 5)));
+        assert_eq!(test.get(4, 2, 5), Some(&(2 * 8 + 5)));
+    }
+
+    #[test]
+    fn columns_test() {
+        check_columns::<BreathFirst>();
+        check_columns::<DepthFirst>();
+    }
+}
@@ -0,0 +1,105 @@
+use std::fmt::{self, Debug, Write};
+
+use typenum::Unsigned;
+
+use crate::{layout::OctreeLayout, octant::Octant};
+
+use super::{DynNode, Octree};
+
+impl<T: Clone, Depth: Unsigned, L: OctreeLayout> Octree<T, Depth, L> {
+    /// Prints the whole octree to stderr as an indented tree, showing the
+    /// octant, depth, bounding box and value of every node.
+    ///
+    /// The root node spans a cube between the origin and `world_size` on all
+    /// axes. This is intended for human inspection during development; output
+    /// grows with the number of nodes, so it's only practical for shallow
+    /// octrees.
+    pub fn debug_print_tree(&self, world_size: f32)
+    where
+        T: Debug,
+    {
+        let mut result = String::new();
+        self.write_debug_tree(&mut result, world_size)
+            .expect("writing to a String can't fail");
+        eprint!("{}", result);
+    }
+
+    fn write_debug_tree(&self, w: &mut impl Write, world_size: f32) -> fmt::Result
+    where
+        T: Debug,
+    {
+        fn write_node<T: Debug, L: OctreeLayout>(
+            w: &mut impl Write,
+            node: DynNode<T, L>,
+            min: [f32; 3],
+            size: f32,
+        ) -> fmt::Result {
+            let name = match node.octant() {
+                Some(octant) => format!("{:?}", octant),
+                None => "root".to_string(),
+            };
+            writeln!(
+                w,
+                "{:indent$}{} depth={} [{:?}..{:?}] = {:?}",
+                "",
+                name,
+                node.layer(),
+                min,
+                min.map(|it| it + size),
+                node.value(),
+                indent = node.layer() * 2,
+            )?;
+
+            let half = size / 2.0;
+            for octant in Octant::ALL {
+                let Some(child) = node.child(octant) else {
+                    break;
+                };
+                let i = octant.as_usize();
+                let child_min = [
+                    min[0] + (i & 1) as f32 * half,
+                    min[1] + ((i >> 1) & 1) as f32 * half,
+                    min[2] + ((i >> 2) & 1) as f32 * half,
+                ];
+                write_node(w, child, child_min, half)?;
+            }
+            Ok(())
+        }
+
+        write_node(w, self.dyn_root(), [0.0; 3], world_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typenum::U1;
+
+    use super::*;
+    use crate::octant::OctantRUB;
+
+    #[test]
+    fn debug_tree_test() {
+        let mut test = Octree::<u32, U1>::new(42);
+        test.child_mut::<OctantRUB>().set_value(7);
+
+        let mut result = String::new();
+        test.write_debug_tree(&mut result, 2.0).unwrap();
+        let lines: Vec<_> = result.lines().collect();
+
+        assert_eq!(lines.len(), 9);
+        assert_eq!(
+            lines[0],
+            "root depth=0 [[0.0, 0.0, 0.0]..[2.0, 2.0, 2.0]] = 42"
+        );
+        assert_eq!(
+            lines[1],
+            "  LDF depth=1 [[0.0, 0.0, 0.0]..[1.0, 1.0, 1.0]] = 42"
+        );
+        assert_eq!(
+            lines[8],
+            "  RUB depth=1 [[1.0, 1.0, 1.0]..[2.0, 2.0, 2.0]] = 7"
+        );
+
+        test.debug_print_tree(2.0);
+    }
+}
@@ -0,0 +1,268 @@
+use std::marker::PhantomData;
+
+use typenum::Unsigned;
+
+use crate::{layout::OctreeLayout, octant::Octant, util::node_offset_bf};
+
+use super::Octree;
+
+/// Runtime view of an octree node.
+///
+/// Unlike [`OctreeNode`](super::OctreeNode), position of this node within the
+/// octree isn't encoded in its type, which allows navigating the octree using
+/// octants only known at runtime, at the cost of some bookkeeping.
+#[derive(Debug)]
+pub struct DynNode<'a, T, L: OctreeLayout> {
+    data: &'a [T],
+    /// Bits of collapsed nodes, in breadth-first node order.
+    collapsed: &'a [u64],
+    size: usize,
+    offset: usize,
+    depth: usize,
+    index: usize,
+    _phantom: PhantomData<fn() -> L>,
+}
+
+impl<'a, T, L: OctreeLayout> Clone for DynNode<'a, T, L> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<'a, T, L: OctreeLayout> Copy for DynNode<'a, T, L> {}
+
+impl<'a, T, L: OctreeLayout> DynNode<'a, T, L> {
+    /// Returns the root node of octree `data` with the given `size`, and
+    /// `collapsed` node bits.
+    pub(super) fn root(data: &'a [T], size: usize, collapsed: &'a [u64]) -> Self {
+        DynNode {
+            data,
+            collapsed,
+            size,
+            offset: 0,
+            depth: size,
+            index: 0,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the node value.
+    pub fn value(&self) -> &'a T {
+        &self.data[self.offset]
+    }
+
+    /// Returns the (remaining) depth of the subtree rooted at this node.
+    pub const fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the layer this node is in, with `0` being the root layer.
+    pub const fn layer(&self) -> usize {
+        self.size - self.depth
+    }
+
+    /// Returns the index of this node within its layer.
+    pub const fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns `true` if this node has no children.
+    ///
+    /// Besides nodes in the last layer, this holds for nodes of uniform
+    /// subtrees collapsed by [`Octree::prune_uniform_depth`], which keep
+    /// their [`depth`](DynNode::depth).
+    pub const fn is_leaf(&self) -> bool {
+        self.depth == 0 || self.is_collapsed()
+    }
+
+    const fn is_collapsed(&self) -> bool {
+        let i = node_offset_bf(self.size - self.depth, self.index, self.size);
+        i / 64 < self.collapsed.len() && self.collapsed[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    /// Returns the current node octant relative to parent, or `None` for the
+    /// root node.
+    pub const fn octant(&self) -> Option<Octant> {
+        if self.depth == self.size {
+            None
+        } else {
+            Some(Octant::ALL[self.index % 8])
+        }
+    }
+
+    /// Returns the child node at the given `octant`, or `None` if this node
+    /// is a leaf.
+    pub fn child(&self, octant: Octant) -> Option<Self> {
+        if self.is_leaf() {
+            return None;
+        }
+        Some(DynNode {
+            offset: self.offset + L::child_offset::<T>(octant, self.size, self.depth, self.index),
+            depth: self.depth - 1,
+            index: self.index * 8 + octant.as_usize(),
+            ..*self
+        })
+    }
+
+    /// Returns the child node at raw octant index `i` (`0..8`), or `None` if
+    /// the index is out of range or this node is a leaf.
+    ///
+    /// Equivalent to `self.child(Octant::ALL[i])`.
+    pub fn child_by_index(&self, i: u8) -> Option<Self> {
+        Octant::ALL
+            .get(i as usize)
+            .and_then(|octant| self.child(*octant))
+    }
+
+    /// Returns the parent node, or `None` if this is the root node.
+    ///
+    /// As nodes don't store a reference to their parent, this descends from
+    /// the root node and takes `O(layer)` steps.
+    pub fn parent(&self) -> Option<Self> {
+        let layer = self.layer();
+        if layer == 0 {
+            return None;
+        }
+        let mut result = DynNode {
+            offset: 0,
+            depth: self.size,
+            index: 0,
+            ..*self
+        };
+        for i in (1..layer).rev() {
+            let octant = Octant::ALL[(self.index >> (i * 3)) & 0b111];
+            result = unsafe {
+                // SAFETY: result is above self, so it's not a leaf.
+                result.child(octant).unwrap_unchecked()
+            };
+        }
+        Some(result)
+    }
+
+    /// Returns `true` if all the nodes in this subtree have the same value.
+    pub fn is_uniform(&self) -> bool
+    where
+        T: PartialEq,
+    {
+        fn all_eq<T: PartialEq, L: OctreeLayout>(node: &DynNode<T, L>, value: &T) -> bool {
+            node.value() == value
+                && Octant::ALL
+                    .into_iter()
+                    .filter_map(|octant| node.child(octant))
+                    .all(|child| all_eq(&child, value))
+        }
+        all_eq(self, self.value())
+    }
+
+    /// Returns the shallowest ancestor-or-self whose subtree is uniform.
+    ///
+    /// Renderers can use this to draw a single large cube instead of
+    /// descending. If this node isn't uniform, it's returned as is.
+    pub fn coarsest_uniform(&self) -> Self
+    where
+        T: PartialEq,
+    {
+        let mut result = *self;
+        if !result.is_uniform() {
+            return result;
+        }
+        while let Some(parent) = result.parent() {
+            if !parent.is_uniform() {
+                break;
+            }
+            result = parent;
+        }
+        result
+    }
+}
+
+impl<T: Clone, Depth: Unsigned, L: OctreeLayout> Octree<T, Depth, L> {
+    /// Returns a runtime view of the root node.
+    pub fn dyn_root(&self) -> DynNode<'_, T, L> {
+        DynNode::root(&self.data, Depth::USIZE, &[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr::addr_of;
+
+    use typenum::{U2, U3};
+
+    use super::*;
+    use crate::{layout::DepthFirst, octant::*};
+
+    #[test]
+    fn dyn_node_child_test() {
+        let test = Octree::<u8, U3, DepthFirst>::new(0);
+        let node = test
+            .dyn_root()
+            .child(Octant::RDF)
+            .and_then(|it| it.child(Octant::LUB))
+            .unwrap();
+        let typed = test.child::<OctantRDF>().child::<OctantLUB>();
+
+        assert_eq!(addr_of!(*node.value()), addr_of!(**typed));
+        assert_eq!(node.layer(), 2);
+        assert_eq!(node.octant(), Some(Octant::LUB));
+        assert_eq!(test.dyn_root().octant(), None);
+
+        let parent = node.parent().unwrap();
+        assert_eq!(
+            addr_of!(*parent.value()),
+            addr_of!(**test.child::<OctantRDF>())
+        );
+        assert_eq!(parent.parent().unwrap().offset, 0);
+        assert!(parent.parent().unwrap().parent().is_none());
+    }
+
+    #[test]
+    fn dyn_node_child_by_index_test() {
+        let test = Octree::<u8, U2>::new(0);
+        let root = test.dyn_root();
+        for (i, octant) in Octant::ALL.into_iter().enumerate() {
+            let child = root.child_by_index(i as u8).unwrap();
+            assert_eq!(child.offset, root.child(octant).unwrap().offset);
+            assert_eq!(child.octant(), Some(octant));
+        }
+        assert!(root.child_by_index(8).is_none());
+        assert!(root.child_by_index(u8::MAX).is_none());
+
+        let leaf = root.child_by_index(3).unwrap().child_by_index(5).unwrap();
+        assert!(leaf.child_by_index(0).is_none());
+    }
+
+    #[test]
+    fn coarsest_uniform_test() {
+        let mut test = Octree::<u8, U3>::new(0);
+        test.child_mut::<OctantRUB>()
+            .child_mut::<OctantLDF>()
+            .set_value(1);
+
+        let region = test
+            .dyn_root()
+            .child(Octant::RUB)
+            .unwrap()
+            .child(Octant::LDF)
+            .unwrap();
+        let leaf = region.child(Octant::RUF).unwrap();
+        assert_eq!(leaf.coarsest_uniform().offset, region.offset);
+        assert_eq!(region.coarsest_uniform().offset, region.offset);
+
+        let uniform = test.dyn_root().child(Octant::LDF).unwrap();
+        let leaf = uniform
+            .child(Octant::RDB)
+            .unwrap()
+            .child(Octant::LUF)
+            .unwrap();
+        assert_eq!(leaf.coarsest_uniform().offset, uniform.offset);
+
+        let uniform = Octree::<u8, U2>::new(0);
+        let leaf = uniform
+            .dyn_root()
+            .child(Octant::LUB)
+            .unwrap()
+            .child(Octant::RDF)
+            .unwrap();
+        assert_eq!(leaf.coarsest_uniform().offset, 0);
+    }
+}
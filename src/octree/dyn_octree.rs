@@ -0,0 +1,364 @@
+use std::marker::PhantomData;
+
+use typenum::Unsigned;
+
+use crate::{
+    layout::{BreathFirst, OctreeLayout},
+    octant::Octant,
+    propagate::Propagator,
+    util::{layer_length, node_offset_bf, subtree_length},
+};
+
+use super::{DynNode, Octree, UniformMask};
+
+/// Octree structure with depth only known at runtime.
+///
+/// This is mostly useful for storing octrees of different depths together,
+/// such as levels of a mip chain.
+///
+/// Uniform subtrees can be collapsed (see [`Octree::prune_uniform_depth`]),
+/// in which case their roots are treated as leaves by [`DynNode`]. Values
+/// below them are still stored, but they're equal to the root value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DynOctree<T, L: OctreeLayout = BreathFirst> {
+    data: Vec<T>,
+    depth: usize,
+    /// Bits of collapsed nodes, in breadth-first node order.
+    collapsed: Vec<u64>,
+    _phantom: PhantomData<fn() -> L>,
+}
+
+impl<T: Clone, L: OctreeLayout> DynOctree<T, L> {
+    /// Creates an octree of the given `depth` with all nodes having the
+    /// initial `value`.
+    pub fn new(depth: usize, value: T) -> Self {
+        DynOctree {
+            data: vec![value; subtree_length(depth)],
+            depth,
+            collapsed: Vec::new(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the depth of the octree.
+    pub const fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns a runtime view of the root node.
+    pub fn dyn_root(&self) -> DynNode<'_, T, L> {
+        DynNode::root(&self.data, self.depth, &self.collapsed)
+    }
+
+    /// Returns an octree that's one layer shallower, with leaves computed from
+    /// the current leaves using the provided propagator `p`.
+    ///
+    /// Values of interior nodes are kept as is. Returns `None` if the octree
+    /// consists of a single node.
+    pub fn shrink_depth(&self, p: &impl Propagator<T>) -> Option<Self> {
+        if self.depth == 0 {
+            return None;
+        }
+        let mut result = DynOctree::new(self.depth - 1, self.data[0].clone());
+        // Breadth-first node offsets don't depend on the octree depth.
+        result.collapsed = self.collapsed.clone();
+        shrink_into::<T, L>(
+            &self.data,
+            self.depth,
+            &mut result.data,
+            p,
+            0,
+            0,
+            self.depth,
+            0,
+        );
+        Some(result)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn shrink_into<T: Clone, L: OctreeLayout>(
+    source: &[T],
+    size: usize,
+    target: &mut [T],
+    p: &impl Propagator<T>,
+    source_offset: usize,
+    target_offset: usize,
+    depth: usize,
+    index: usize,
+) {
+    let children =
+        Octant::ALL.map(|octant| source_offset + L::child_offset::<T>(octant, size, depth, index));
+    if depth == 1 {
+        target[target_offset] = p.propagate(children.map(|child| &source[child]));
+        return;
+    }
+
+    target[target_offset] = source[source_offset].clone();
+    for (octant, child) in Octant::ALL.into_iter().zip(children) {
+        let target_child = target_offset + L::child_offset::<T>(octant, size - 1, depth - 1, index);
+        shrink_into::<T, L>(
+            source,
+            size,
+            target,
+            p,
+            child,
+            target_child,
+            depth - 1,
+            index * 8 + octant.as_usize(),
+        );
+    }
+}
+
+impl<T, L: OctreeLayout> AsRef<[T]> for DynOctree<T, L> {
+    fn as_ref(&self) -> &[T] {
+        &self.data
+    }
+}
+
+impl<T: Clone, Depth: Unsigned, L: OctreeLayout> From<Octree<T, Depth, L>> for DynOctree<T, L> {
+    fn from(value: Octree<T, Depth, L>) -> Self {
+        DynOctree {
+            data: value.data.into_vec(),
+            depth: Depth::USIZE,
+            collapsed: Vec::new(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: Clone, Depth: Unsigned, L: OctreeLayout> Octree<T, Depth, L> {
+    /// Returns a copy of this octree with its depth only known at runtime.
+    pub fn to_dyn(&self) -> DynOctree<T, L> {
+        DynOctree {
+            data: self.data.to_vec(),
+            depth: Depth::USIZE,
+            collapsed: Vec::new(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the full mip chain of this octree, starting with a copy of
+    /// this octree and followed by progressively shallower octrees down to a
+    /// single node.
+    ///
+    /// Each level is computed from the previous one with
+    /// [`DynOctree::shrink_depth`].
+    pub fn mip_chain(&self, p: &impl Propagator<T>) -> Vec<DynOctree<T, L>> {
+        let mut result = Vec::with_capacity(Depth::USIZE + 1);
+        result.push(self.to_dyn());
+        while let Some(next) = result.last().and_then(|it| it.shrink_depth(p)) {
+            result.push(next);
+        }
+        result
+    }
+
+    /// Returns a copy of this octree with uniform subtrees (see
+    /// [`DynNode::is_uniform`]) collapsed into single leaves.
+    ///
+    /// Layers below the shallowest depth at which every node has a uniform
+    /// subtree are dropped. Above it, each uniform subtree is collapsed on
+    /// its own, so [`DynNode::is_leaf`] holds for its root while detailed
+    /// sibling subtrees keep their full depth.
+    ///
+    /// Leaves of the returned octree hold values their subtrees are uniform
+    /// with, so no information is lost. Interior nodes above them are copied
+    /// as is.
+    pub fn prune_uniform_depth(&self) -> DynOctree<T, L>
+    where
+        T: PartialEq,
+    {
+        struct Target<'a, T> {
+            data: &'a mut [T],
+            size: usize,
+            collapsed: &'a mut [u64],
+        }
+
+        fn copy_into<T: Clone, L: OctreeLayout>(
+            source: &[T],
+            size: usize,
+            mask: &UniformMask<impl Unsigned>,
+            target: &mut Target<T>,
+            offsets: (usize, usize),
+            layer: usize,
+            index: usize,
+        ) {
+            let (source_offset, target_offset) = offsets;
+            target.data[target_offset] = source[source_offset].clone();
+            if layer == target.size {
+                return;
+            }
+            if mask.is_uniform(layer, index) {
+                let i = node_offset_bf(layer, index, target.size);
+                target.collapsed[i / 64] |= 1 << (i % 64);
+            }
+            for octant in Octant::ALL {
+                let offsets = (
+                    source_offset + L::child_offset::<T>(octant, size, size - layer, index),
+                    target_offset
+                        + L::child_offset::<T>(octant, target.size, target.size - layer, index),
+                );
+                let child_index = index * 8 + octant.as_usize();
+                copy_into::<T, L>(source, size, mask, target, offsets, layer + 1, child_index);
+            }
+        }
+
+        let mask = self.build_uniform_mask();
+        let depth = (0..=Depth::USIZE)
+            .find(|layer| (0..layer_length(*layer)).all(|index| mask.is_uniform(*layer, index)))
+            .unwrap_or(Depth::USIZE);
+        let mut result = DynOctree::new(depth, self.data[0].clone());
+        result.collapsed = vec![0; subtree_length(depth).div_ceil(64)];
+        copy_into::<T, L>(
+            &self.data,
+            Depth::USIZE,
+            &mask,
+            &mut Target {
+                data: &mut result.data,
+                size: depth,
+                collapsed: &mut result.collapsed,
+            },
+            (0, 0),
+            0,
+            0,
+        );
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typenum::U3;
+
+    use super::*;
+    use crate::{layout::DepthFirst, octant::*, propagate::MostCommon};
+
+    fn check_mip_chain<L: OctreeLayout>() {
+        let mut test = Octree::<u8, U3, L>::new(0);
+        test.child_mut::<OctantRDF>().set_value(1);
+        test.child_mut::<OctantLUB>()
+            .child_mut::<OctantLDF>()
+            .child_mut::<OctantRUB>()
+            .set_value(2);
+        *test.get_mut(0, 7, 7).unwrap() = 3;
+        test.child_mut::<OctantRUB>()
+            .child_mut::<OctantRUB>()
+            .set_value(4);
+        *test.get_mut(7, 7, 7).unwrap() = 5;
+
+        let chain = test.mip_chain(&MostCommon);
+        assert_eq!(chain.len(), 3 + 1);
+        for (i, level) in chain.iter().enumerate() {
+            assert_eq!(level.depth(), 3 - i);
+            assert_eq!(level.as_ref().len(), subtree_length(3 - i));
+        }
+        assert_eq!(chain[0].as_ref(), test.as_ref() as &[u8]);
+
+        let level = chain[1].dyn_root();
+        let rdf = level.child(Octant::RDF).unwrap();
+        assert_eq!(*rdf.child(Octant::LUB).unwrap().value(), 1);
+        let lub = level.child(Octant::LUB).unwrap();
+        assert_eq!(*lub.child(Octant::LDF).unwrap().value(), 0);
+        let rub = level
+            .child(Octant::RUB)
+            .unwrap()
+            .child(Octant::RUB)
+            .unwrap();
+        assert_eq!(*rub.value(), 4);
+
+        let level = chain[2].dyn_root();
+        assert_eq!(*level.child(Octant::RDF).unwrap().value(), 1);
+        assert_eq!(*level.child(Octant::RUB).unwrap().value(), 0);
+        assert_eq!(*chain[3].dyn_root().value(), 0);
+    }
+
+    fn check_prune_uniform_depth<L: OctreeLayout>() {
+        let mut test = Octree::<u8, U3, L>::new(9);
+        test.child_mut::<OctantRDF>().set_value(1);
+        test.child_mut::<OctantLUB>().set_value(2);
+
+        let pruned = test.prune_uniform_depth();
+        assert_eq!(pruned.depth(), 1);
+        assert_eq!(*pruned.dyn_root().value(), 9);
+        let leaves = Octant::ALL.map(|octant| *pruned.dyn_root().child(octant).unwrap().value());
+        assert_eq!(leaves, [9, 1, 9, 9, 9, 9, 2, 9]);
+
+        // Interior values differing from the leaves below them are kept.
+        test.child_mut::<OctantRUB>()
+            .child_mut::<OctantLDB>()
+            .set_value(3);
+        let pruned = test.prune_uniform_depth();
+        assert_eq!(pruned.depth(), 2);
+        let rub = pruned.dyn_root().child(Octant::RUB).unwrap();
+        assert_eq!(*rub.child(Octant::LDB).unwrap().value(), 3);
+        assert_eq!(*rub.child(Octant::LDF).unwrap().value(), 9);
+
+        *test.get_mut(0, 0, 0).unwrap() = 4;
+        assert_eq!(test.prune_uniform_depth().as_ref(), test.as_ref() as &[u8]);
+        assert_eq!(Octree::<u8, U3, L>::new(5).prune_uniform_depth().depth(), 0);
+    }
+
+    fn check_prune_uniform_subtrees<L: OctreeLayout>() {
+        let mut test = Octree::<u8, U3, L>::new(0);
+        test.child_mut::<OctantLDF>().set_value(1);
+        test.child_mut::<OctantRDF>()
+            .child_mut::<OctantLUF>()
+            .set_value(2);
+        *test.get_mut(7, 1, 1).unwrap() = 3;
+
+        let pruned = test.prune_uniform_depth();
+        assert_eq!(pruned.depth(), 3);
+        let root = pruned.dyn_root();
+        assert!(!root.is_leaf());
+
+        // The uniform branch is collapsed into a single leaf.
+        let ldf = root.child(Octant::LDF).unwrap();
+        assert!(ldf.is_leaf());
+        assert_eq!(*ldf.value(), 1);
+        assert!(ldf.child(Octant::RUB).is_none());
+        assert!(ldf.is_uniform());
+
+        // Its detailed sibling keeps full depth, with its own uniform
+        // children collapsed.
+        let rdf = root.child(Octant::RDF).unwrap();
+        assert!(!rdf.is_leaf());
+        let luf = rdf.child(Octant::LUF).unwrap();
+        assert!(luf.is_leaf());
+        assert_eq!(*luf.value(), 2);
+        let detailed = rdf.child(Octant::RDF).unwrap();
+        assert!(!detailed.is_leaf());
+        let leaf = detailed.child(Octant::RUB).unwrap();
+        assert!(leaf.is_leaf());
+        assert_eq!(leaf.depth(), 0);
+        assert_eq!(*leaf.value(), 3);
+        assert!(rdf.child(Octant::LDF).unwrap().is_leaf());
+        assert!(root.child(Octant::RUB).unwrap().is_leaf());
+
+        // Collapsed nodes are kept by shrinking.
+        let shrunk = pruned.shrink_depth(&crate::propagate::MostCommon).unwrap();
+        assert!(shrunk.dyn_root().child(Octant::LDF).unwrap().is_leaf());
+        assert!(!shrunk.dyn_root().child(Octant::RDF).unwrap().is_leaf());
+        // Octrees without collapsed nodes are unaffected.
+        assert!(!test.dyn_root().child(Octant::LDF).unwrap().is_leaf());
+        assert!(!test
+            .to_dyn()
+            .dyn_root()
+            .child(Octant::LDF)
+            .unwrap()
+            .is_leaf());
+    }
+
+    #[test]
+    fn prune_uniform_depth_test() {
+        check_prune_uniform_subtrees::<BreathFirst>();
+        check_prune_uniform_subtrees::<DepthFirst>();
+        check_prune_uniform_depth::<BreathFirst>();
+        check_prune_uniform_depth::<DepthFirst>();
+    }
+
+    #[test]
+    fn mip_chain_test() {
+        check_mip_chain::<BreathFirst>();
+        check_mip_chain::<DepthFirst>();
+    }
+}
@@ -0,0 +1,734 @@
+use std::cmp::Ordering;
+
+use typenum::Unsigned;
+
+use crate::{
+    layout::OctreeLayout,
+    octant::Octant,
+    propagate::Propagator,
+    util::{leaf_index_from_coords, leaf_index_to_coords},
+};
+
+use super::{visit_leaves, Octree, OctreeNode};
+
+impl<T: Clone, S: Unsigned, L: OctreeLayout, D: Unsigned, I: Unsigned> OctreeNode<T, S, L, D, I> {
+    /// Replaces the `old` value of this node with `new`, and then does the
+    /// same for its descendants, stopping at nodes whose value isn't `old`.
+    ///
+    /// This refines a coarse edit without overwriting more detailed values
+    /// below it. Returns `false` (without changing anything) if the value of
+    /// this node isn't `old`.
+    pub fn refine_set(&mut self, old: &T, new: T) -> bool
+    where
+        T: PartialEq,
+    {
+        unsafe {
+            refine::<T, L>(
+                self.value_ptr_mut(),
+                S::USIZE,
+                D::USIZE,
+                I::USIZE,
+                old,
+                &new,
+            )
+        }
+    }
+
+    /// Sets the `value` of nodes of this subtree for which `predicate` holds.
+    ///
+    /// Leaves are written if `predicate` holds for them, while interior
+    /// nodes are only written if it holds for every node in their subtree,
+    /// so that interior values of partially written subtrees keep reflecting
+    /// their content.
+    pub fn set_value_if<F: Fn(&T) -> bool>(&mut self, value: T, predicate: F) {
+        unsafe {
+            set_if::<T, L>(
+                self.value_ptr_mut(),
+                S::USIZE,
+                D::USIZE,
+                I::USIZE,
+                &value,
+                &predicate,
+            );
+        }
+    }
+
+    /// Sets every leaf of this subtree to `f` evaluated at its (global)
+    /// coordinates, and then recomputes interior nodes of this subtree using
+    /// the propagator `p`.
+    ///
+    /// Ancestors of this node aren't updated.
+    pub fn regenerate<F: Fn([usize; 3]) -> T>(&mut self, f: F, p: &impl Propagator<T>) {
+        unsafe {
+            visit_leaves::<T, L>(
+                self.value_ptr_mut(),
+                S::USIZE,
+                D::USIZE,
+                I::USIZE,
+                &mut |leaf, index| {
+                    let (x, y, z) = leaf_index_to_coords(index);
+                    // SAFETY: leaf pointer is derived from a mutable
+                    // reference to this node.
+                    *(leaf as *mut T) = f([x, y, z]);
+                },
+            );
+        }
+        self.propagate_up(p);
+    }
+
+    /// Sets every node of this subtree to the value returned by `f`, called
+    /// once per node with its path relative to this node and its depth
+    /// relative to this node (the path length).
+    ///
+    /// Nodes are visited depth-first, which is the memory order of
+    /// [`DepthFirst`](crate::layout::DepthFirst) layout.
+    pub fn fill_with(&mut self, mut f: impl FnMut(&[Octant], usize) -> T) {
+        unsafe fn visit<T, L: OctreeLayout>(
+            base: *mut T,
+            size: usize,
+            depth: usize,
+            index: usize,
+            path: &mut Vec<Octant>,
+            f: &mut impl FnMut(&[Octant], usize) -> T,
+        ) {
+            *base = f(path, path.len());
+            if depth == 0 {
+                return;
+            }
+            for octant in Octant::ALL {
+                let child = base.add(L::child_offset::<T>(octant, size, depth, index));
+                path.push(octant);
+                visit::<T, L>(
+                    child,
+                    size,
+                    depth - 1,
+                    index * 8 + octant.as_usize(),
+                    path,
+                    f,
+                );
+                path.pop();
+            }
+        }
+
+        let mut path = Vec::with_capacity(D::USIZE);
+        unsafe {
+            visit::<T, L>(
+                self.value_ptr_mut(),
+                S::USIZE,
+                D::USIZE,
+                I::USIZE,
+                &mut path,
+                &mut f,
+            )
+        }
+    }
+
+    /// Sets every leaf of this subtree to the value returned by `f`, called
+    /// once per leaf with its (global) `x`, `y` and `z` coordinates.
+    ///
+    /// Values of interior nodes aren't updated.
+    pub fn fill_leaves_with(&mut self, mut f: impl FnMut(usize, usize, usize) -> T) {
+        unsafe {
+            visit_leaves::<T, L>(
+                self.value_ptr_mut(),
+                S::USIZE,
+                D::USIZE,
+                I::USIZE,
+                &mut |leaf, index| {
+                    let (x, y, z) = leaf_index_to_coords(index);
+                    // SAFETY: leaf pointer is derived from a mutable
+                    // reference to this node.
+                    *(leaf as *mut T) = f(x, y, z);
+                },
+            );
+        }
+    }
+
+    /// Sets every leaf of this subtree whose center lies within the sphere at
+    /// `center` (in leaf grid coordinates) with the given `radius` to `value`.
+    ///
+    /// Leaf `(x, y, z)` has its center at `(x + 0.5, y + 0.5, z + 0.5)`.
+    /// Subtrees with all leaf centers inside the sphere are set with a single
+    /// [`OctreeNode::set_value`], which updates their interior nodes as well,
+    /// while values of other interior nodes are left as is. Nothing is painted
+    /// if `center` or `radius` is NaN.
+    pub fn paint_sphere(&mut self, center: [f32; 3], radius: f32, value: T) {
+        let (x, y, z) = leaf_index_to_coords(I::USIZE << (D::USIZE * 3));
+        unsafe {
+            paint_sphere::<T, L>(
+                self.value_ptr_mut(),
+                S::USIZE,
+                D::USIZE,
+                I::USIZE,
+                [x, y, z],
+                (center, radius),
+                &value,
+            )
+        }
+    }
+}
+
+/// Paints the sphere into the subtree at `base`, whose first leaf is at
+/// `origin`.
+///
+/// # Safety
+///
+/// Same requirements as for [`OctreeLayout::fill`] apply.
+unsafe fn paint_sphere<T: Clone, L: OctreeLayout>(
+    base: *mut T,
+    size: usize,
+    depth: usize,
+    index: usize,
+    origin: [usize; 3],
+    (center, radius): ([f32; 3], f32),
+    value: &T,
+) {
+    // Bounds of leaf centers within this subtree.
+    let first = origin.map(|it| it as f32 + 0.5);
+    let last = first.map(|it| it + ((1 << depth) - 1) as f32);
+    let mut nearest = 0.0;
+    let mut farthest = 0.0;
+    for i in 0..3 {
+        let near = center[i].clamp(first[i], last[i]) - center[i];
+        let far = (center[i] - first[i])
+            .abs()
+            .max((center[i] - last[i]).abs());
+        nearest += near * near;
+        farthest += far * far;
+    }
+
+    let radius_squared = radius * radius;
+    // NaN center or radius is incomparable, and doesn't match any leaves.
+    if nearest
+        .partial_cmp(&radius_squared)
+        .is_none_or(Ordering::is_gt)
+    {
+        return;
+    }
+    if farthest <= radius_squared {
+        return L::fill(base, value.clone(), size, depth, index);
+    }
+    if depth == 0 {
+        return;
+    }
+
+    let half = 1 << (depth - 1);
+    for octant in Octant::ALL {
+        let i = octant.as_usize();
+        let child_origin = [
+            origin[0] + (i & 1) * half,
+            origin[1] + ((i >> 1) & 1) * half,
+            origin[2] + ((i >> 2) & 1) * half,
+        ];
+        let child = base.add(L::child_offset::<T>(octant, size, depth, index));
+        paint_sphere::<T, L>(
+            child,
+            size,
+            depth - 1,
+            index * 8 + i,
+            child_origin,
+            (center, radius),
+            value,
+        );
+    }
+}
+
+/// Replaces `old` with `new` in the subtree at `base`, descending only into
+/// replaced nodes.
+///
+/// # Safety
+///
+/// Same requirements as for [`OctreeLayout::fill`] apply.
+unsafe fn refine<T: Clone + PartialEq, L: OctreeLayout>(
+    base: *mut T,
+    size: usize,
+    depth: usize,
+    index: usize,
+    old: &T,
+    new: &T,
+) -> bool {
+    if *base != *old {
+        return false;
+    }
+    *base = new.clone();
+    if depth > 0 {
+        for octant in Octant::ALL {
+            let child = base.add(L::child_offset::<T>(octant, size, depth, index));
+            refine::<T, L>(
+                child,
+                size,
+                depth - 1,
+                index * 8 + octant.as_usize(),
+                old,
+                new,
+            );
+        }
+    }
+    true
+}
+
+/// Writes `value` to nodes of the subtree at `base` as described by
+/// [`OctreeNode::set_value_if`], and returns whether the whole subtree was
+/// written.
+unsafe fn set_if<T: Clone, L: OctreeLayout>(
+    base: *mut T,
+    size: usize,
+    depth: usize,
+    index: usize,
+    value: &T,
+    predicate: &impl Fn(&T) -> bool,
+) -> bool {
+    let mut matches = predicate(&*base);
+    if depth > 0 {
+        for octant in Octant::ALL {
+            let child = base.add(L::child_offset::<T>(octant, size, depth, index));
+            matches &= set_if::<T, L>(
+                child,
+                size,
+                depth - 1,
+                index * 8 + octant.as_usize(),
+                value,
+                predicate,
+            );
+        }
+    }
+    if matches {
+        *base = value.clone();
+    }
+    matches
+}
+
+impl<T: Clone, Depth: Unsigned, L: OctreeLayout> Octree<T, Depth, L> {
+    /// Sets all leaves on the boundary of the `2^Depth` grid to `value`.
+    ///
+    /// Only border leaves (those with any coordinate equal to `0` or
+    /// `2^Depth - 1`) are visited. Values of interior nodes aren't updated.
+    pub fn set_border(&mut self, value: T) {
+        let last = (1 << Depth::USIZE) - 1;
+        for z in 0..=last {
+            for y in 0..=last {
+                if z == 0 || z == last || y == 0 || y == last {
+                    for x in 0..=last {
+                        self.set_leaf_at(x, y, z, value.clone());
+                    }
+                } else {
+                    self.set_leaf_at(0, y, z, value.clone());
+                    self.set_leaf_at(last, y, z, value.clone());
+                }
+            }
+        }
+    }
+
+    /// Sets all leaves on the boundary of the `2^Depth` grid to `value`.
+    ///
+    /// This is the same as [`Octree::set_border`].
+    pub fn fill_border(&mut self, value: T) {
+        self.set_border(value);
+    }
+
+    /// Sets all leaves within the inclusive box between `min` and `max`
+    /// coordinates to `value`.
+    ///
+    /// Subtrees fully covered by the box are set with a single
+    /// [`OctreeNode::set_value`](super::OctreeNode::set_value) so their
+    /// interior nodes are updated as well, while values of partially covered
+    /// interior nodes are left as is. Coordinates outside of the grid are
+    /// clamped.
+    pub fn fill_box(&mut self, min: [usize; 3], max: [usize; 3], value: T) {
+        let last = (1 << Depth::USIZE) - 1;
+        let max = max.map(|it| it.min(last));
+        if (0..3).any(|i| min[i] > max[i]) {
+            return;
+        }
+        self.fill_box_in(0, Depth::USIZE, 0, [0; 3], min, max, &value);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn fill_box_in(
+        &mut self,
+        offset: usize,
+        depth: usize,
+        index: usize,
+        origin: [usize; 3],
+        min: [usize; 3],
+        max: [usize; 3],
+        value: &T,
+    ) {
+        let size = 1 << depth;
+        if (0..3).any(|i| origin[i] > max[i] || origin[i] + size - 1 < min[i]) {
+            return;
+        }
+        if (0..3).all(|i| min[i] <= origin[i] && origin[i] + size - 1 <= max[i]) {
+            unsafe {
+                // SAFETY: offset, depth and index describe a node of this
+                // octree.
+                L::fill(
+                    self.data.as_mut_ptr().add(offset),
+                    value.clone(),
+                    Depth::USIZE,
+                    depth,
+                    index,
+                );
+            }
+            return;
+        }
+
+        let half = size / 2;
+        for octant in Octant::ALL {
+            let i = octant.as_usize();
+            let child_origin = [
+                origin[0] + (i & 1) * half,
+                origin[1] + ((i >> 1) & 1) * half,
+                origin[2] + ((i >> 2) & 1) * half,
+            ];
+            let child = Self::child_data_index(offset, octant, depth, index);
+            self.fill_box_in(
+                child,
+                depth - 1,
+                index * 8 + i,
+                child_origin,
+                min,
+                max,
+                value,
+            );
+        }
+    }
+
+    /// Calls [`OctreeNode::refine_set`] on the node at the given `path`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `path` is longer than `Depth`.
+    pub fn refine_set_at(&mut self, path: &[Octant], old: &T, new: T) -> bool
+    where
+        T: PartialEq,
+    {
+        assert!(path.len() <= Depth::USIZE, "path is deeper than octree");
+        let layer = path.len();
+        let index = crate::util::path_to_index(path);
+        let offset = Self::node_data_index(layer, index);
+        unsafe {
+            // SAFETY: offset, depth and index describe a node of this octree.
+            refine::<T, L>(
+                self.data.as_mut_ptr().add(offset),
+                Depth::USIZE,
+                Depth::USIZE - layer,
+                index,
+                old,
+                &new,
+            )
+        }
+    }
+
+    #[inline]
+    fn set_leaf_at(&mut self, x: usize, y: usize, z: usize, value: T) {
+        let index = Self::leaf_data_index(leaf_index_from_coords(x, y, z));
+        self.data[index] = value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typenum::{U0, U3};
+
+    use super::*;
+    use crate::layout::DepthFirst;
+
+    #[test]
+    fn set_border_test() {
+        let mut bf = Octree::<u8, U3>::new(0);
+        let mut df = Octree::<u8, U3, DepthFirst>::new(0);
+        bf.set_border(1);
+        df.set_border(1);
+
+        for z in 0..8 {
+            for y in 0..8 {
+                for x in 0..8 {
+                    let border = [x, y, z].iter().any(|it| *it == 0 || *it == 7);
+                    let expected = if border { 1 } else { 0 };
+                    assert_eq!(bf.get(x, y, z), Some(&expected));
+                    assert_eq!(df.get(x, y, z), Some(&expected));
+                }
+            }
+        }
+        assert_eq!(
+            bf.data.iter().filter(|it| **it == 1).count(),
+            8 * 8 * 8 - 6 * 6 * 6
+        );
+        assert_eq!(**bf, 0);
+
+        let mut test = Octree::<u8, typenum::U2>::new(0);
+        test.set_border(1);
+        let leaves = test.layer_slice::<typenum::U2>();
+        assert_eq!(leaves.iter().filter(|it| **it == 0).count(), 2 * 2 * 2);
+        for (x, y, z) in [(1, 1, 1), (2, 1, 2), (2, 2, 2)] {
+            assert_eq!(test.get(x, y, z), Some(&0));
+        }
+    }
+
+    fn check_fill_box<L: OctreeLayout>() {
+        let mut test = Octree::<u8, U3, L>::new(0);
+        test.fill_box([4, 0, 4], [7, 3, 7], 1);
+        for z in 0..8 {
+            for y in 0..8 {
+                for x in 0..8 {
+                    let inside = x >= 4 && y < 4 && z >= 4;
+                    assert_eq!(test.get(x, y, z), Some(&(inside as u8)));
+                }
+            }
+        }
+        // Covered subtree was set as a whole, parent was left as is.
+        assert_eq!(**test.child::<crate::octant::OctantRDB>(), 1);
+        assert_eq!(**test, 0);
+
+        test.fill_box([1, 1, 1], [2, 2, 9], 2);
+        for z in 0..8 {
+            for y in 0..8 {
+                for x in 0..8 {
+                    let inside = (1..=2).contains(&x) && (1..=2).contains(&y) && z >= 1;
+                    let expected = if inside {
+                        2
+                    } else {
+                        (x >= 4 && y < 4 && z >= 4) as u8
+                    };
+                    assert_eq!(test.get(x, y, z), Some(&expected));
+                }
+            }
+        }
+        assert_eq!(**test.child::<crate::octant::OctantLDF>(), 0);
+
+        test.fill_box([3, 0, 0], [2, 7, 7], 3);
+        assert!(test.data.iter().all(|it| *it != 3));
+    }
+
+    #[test]
+    fn fill_box_test() {
+        check_fill_box::<crate::layout::BreathFirst>();
+        check_fill_box::<DepthFirst>();
+    }
+
+    fn check_refine_set<L: OctreeLayout>() {
+        use crate::octant::*;
+
+        let mut test = Octree::<u8, U3, L>::new(1);
+        // Detail edits within the RDB subtree.
+        test.child_mut::<OctantRDB>()
+            .child_mut::<OctantLUF>()
+            .set_value(5);
+        test.child_mut::<OctantRDB>()
+            .child_mut::<OctantRUB>()
+            .child_mut::<OctantLDB>()
+            .set_value(6);
+
+        assert!(!test.child_mut::<OctantRDB>().refine_set(&2, 3));
+        assert!(test.child_mut::<OctantRDB>().refine_set(&1, 3));
+
+        assert_eq!(**test, 1);
+        assert_eq!(**test.child::<OctantLDF>(), 1);
+        let rdb = test.child::<OctantRDB>();
+        assert_eq!(**rdb, 3);
+        assert_eq!(rdb.child::<OctantLUF>().count_in_subtree(|v| *v == 5), 8);
+        assert_eq!(**rdb.child::<OctantLUF>(), 5);
+        let rub = rdb.child::<OctantRUB>();
+        assert_eq!(**rub, 3);
+        assert_eq!(**rub.child::<OctantLDB>(), 6);
+        assert_eq!(rub.count_in_subtree(|v| *v == 3), 7);
+        assert_eq!(rdb.count_in_subtree(|v| *v == 3), 64 - 8 - 1);
+
+        let path = [Octant::RDB, Octant::RUB];
+        assert!(!test.refine_set_at(&path, &1, 4));
+        assert!(test.refine_set_at(&path, &3, 4));
+        assert_eq!(**test.child::<OctantRDB>().child::<OctantRUB>(), 4);
+        assert_eq!(**test.child::<OctantRDB>(), 3);
+    }
+
+    fn check_regenerate<L: OctreeLayout>() {
+        use crate::{octant::*, propagate::MostCommon};
+
+        let mut test = Octree::<u8, U3, L>::new(0);
+        let solid = |[x, y, z]: [usize; 3]| (x + y + z >= 19) as u8 * 2;
+        test.child_mut::<OctantRUB>().regenerate(solid, &MostCommon);
+
+        for z in 0..8 {
+            for y in 0..8 {
+                for x in 0..8 {
+                    let expected = if x >= 4 && y >= 4 && z >= 4 {
+                        solid([x, y, z])
+                    } else {
+                        0
+                    };
+                    assert_eq!(test.get(x, y, z), Some(&expected));
+                }
+            }
+        }
+        let rub = test.child::<OctantRUB>();
+        assert_eq!(**rub.child::<OctantRUB>(), 2);
+        assert_eq!(**rub.child::<OctantLDF>(), 0);
+        assert_eq!(**rub, 0);
+        assert_eq!(**test, 0);
+
+        test.child_mut::<OctantRUB>().regenerate(|_| 3, &MostCommon);
+        assert_eq!(**test.child::<OctantRUB>(), 3);
+        assert_eq!(test.child::<OctantRUB>().count_in_subtree(|v| *v == 3), 64);
+        assert_eq!(test.count_in_subtree(|v| *v == 0), 512 - 64);
+    }
+
+    fn check_paint_sphere<L: OctreeLayout>() {
+        use crate::octant::*;
+
+        let mut test = Octree::<u8, U3, L>::new(0);
+        test.paint_sphere([3.0, 4.0, 4.5], 1.2, 1);
+        for z in 0..8 {
+            for y in 0..8 {
+                for x in 0..8 {
+                    let d = [x as f32 + 0.5 - 3.0, y as f32 + 0.5 - 4.0, z as f32 - 4.0];
+                    let inside = d.iter().map(|it| it * it).sum::<f32>() <= 1.44;
+                    assert_eq!(test.get(x, y, z), Some(&(inside as u8)), "{x} {y} {z}");
+                }
+            }
+        }
+        assert_eq!(test.count_in_subtree(|v| *v == 1), 4);
+        assert_eq!(**test, 0);
+
+        // Fully covered subtrees are set as a whole.
+        test.child_mut::<OctantRUB>()
+            .paint_sphere([6.0, 6.0, 6.0], 3.0, 2);
+        assert_eq!(**test.child::<OctantRUB>(), 2);
+        assert_eq!(test.child::<OctantRUB>().count_in_subtree(|v| *v == 2), 64);
+        assert_eq!(**test, 0);
+        test.paint_sphere([4.0; 3], 7.0, 3);
+        assert_eq!(**test, 3);
+        assert_eq!(test.count_in_subtree(|v| *v == 3), 512);
+
+        // Spheres with NaN center or radius don't contain any leaves.
+        test.paint_sphere([4.0; 3], f32::NAN, 4);
+        test.paint_sphere([4.0, f32::NAN, 4.0], 2.0, 4);
+        test.paint_sphere([f32::NAN; 3], f32::INFINITY, 4);
+        assert_eq!(**test, 3);
+        assert_eq!(test.count_in_subtree(|v| *v == 3), 512);
+    }
+
+    fn check_fill_with<L: OctreeLayout>() {
+        use crate::octant::*;
+
+        let hash = |x: usize, y: usize, z: usize| ((x * 73 + y * 151 + z * 233) % 256) as u8;
+        let mut test = Octree::<u8, U3, L>::new(0);
+        test.fill_leaves_with(hash);
+        for z in 0..8 {
+            for y in 0..8 {
+                for x in 0..8 {
+                    assert_eq!(test.get(x, y, z), Some(&hash(x, y, z)));
+                }
+            }
+        }
+        assert_eq!(**test, 0);
+
+        test.child_mut::<OctantRUB>().fill_leaves_with(|_, _, _| 1);
+        assert_eq!(test.get(3, 3, 3), Some(&hash(3, 3, 3)));
+        assert_eq!(test.get(4, 5, 6), Some(&1));
+
+        let mut calls = 0;
+        test.child_mut::<OctantLUF>().fill_with(|path, depth| {
+            calls += 1;
+            assert_eq!(path.len(), depth);
+            path.iter()
+                .fold(depth as u8 * 100, |acc, it| acc + it.as_usize() as u8)
+        });
+        assert_eq!(calls, 1 + 8 + 64);
+        let luf = test.child::<OctantLUF>();
+        assert_eq!(**luf, 0);
+        assert_eq!(**luf.child::<OctantRUB>(), 107);
+        assert_eq!(**luf.child::<OctantRUB>().child::<OctantRDF>(), 208);
+        assert_eq!(test.get(0, 4, 0), Some(&200));
+        assert_eq!(test.get(4, 5, 6), Some(&1));
+    }
+
+    #[test]
+    fn fill_with_test() {
+        check_fill_with::<crate::layout::BreathFirst>();
+        check_fill_with::<DepthFirst>();
+    }
+
+    #[test]
+    fn paint_sphere_test() {
+        check_paint_sphere::<crate::layout::BreathFirst>();
+        check_paint_sphere::<DepthFirst>();
+    }
+
+    #[test]
+    fn regenerate_test() {
+        check_regenerate::<crate::layout::BreathFirst>();
+        check_regenerate::<DepthFirst>();
+    }
+
+    fn check_set_value_if<L: OctreeLayout>() {
+        use crate::octant::*;
+
+        let mut test = Octree::<u8, U3, L>::new(1);
+        test.child_mut::<OctantRDB>()
+            .child_mut::<OctantLUF>()
+            .set_value(5);
+        test.child_mut::<OctantRDB>()
+            .child_mut::<OctantRUB>()
+            .child_mut::<OctantLDB>()
+            .set_value(6);
+        let before = test.map(|it| *it);
+
+        test.child_mut::<OctantRDB>().set_value_if(3, |v| *v == 1);
+        for (old, new) in before.data.iter().zip(test.data.iter()) {
+            assert!(old == new || (*old == 1 && *new == 3));
+        }
+        assert_eq!(**test, 1);
+        assert_eq!(test.child::<OctantLDF>().count_in_subtree(|v| *v == 1), 64);
+        let rdb = test.child::<OctantRDB>();
+        // Partially written nodes keep their value.
+        assert_eq!(**rdb, 1);
+        assert_eq!(**rdb.child::<OctantRUB>(), 1);
+        assert_eq!(**rdb.child::<OctantLUF>(), 5);
+        assert_eq!(**rdb.child::<OctantLDF>(), 3);
+        assert_eq!(**rdb.child::<OctantRUB>().child::<OctantLDB>(), 6);
+        assert_eq!(rdb.count_in_subtree(|v| *v == 1), 0);
+        assert_eq!(rdb.count_in_subtree(|v| *v == 3), 64 - 8 - 1);
+        assert_eq!(
+            before.data.iter().filter(|it| **it == 1).count()
+                - test.data.iter().filter(|it| **it == 1).count(),
+            64 - 8 - 1 + 6
+        );
+
+        test.set_value_if(7, |v| *v != 0);
+        assert_eq!(
+            test.data.iter().filter(|it| **it == 7).count(),
+            test.data.len()
+        );
+    }
+
+    #[test]
+    fn set_value_if_test() {
+        check_set_value_if::<crate::layout::BreathFirst>();
+        check_set_value_if::<DepthFirst>();
+    }
+
+    #[test]
+    fn refine_set_test() {
+        check_refine_set::<crate::layout::BreathFirst>();
+        check_refine_set::<DepthFirst>();
+    }
+
+    #[test]
+    fn fill_border_test() {
+        let mut filled = Octree::<u8, U3, DepthFirst>::new(0);
+        let mut set = Octree::<u8, U3, DepthFirst>::new(0);
+        filled.fill_border(1);
+        set.set_border(1);
+        assert_eq!(filled.data, set.data);
+        assert_eq!(filled.get(0, 3, 4), Some(&1));
+        assert_eq!(filled.get(3, 3, 4), Some(&0));
+    }
+
+    #[test]
+    fn set_border_single_leaf_test() {
+        let mut test = Octree::<u8, U0>::new(0);
+        test.set_border(1);
+        assert_eq!(**test, 1);
+    }
+}
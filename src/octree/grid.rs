@@ -0,0 +1,873 @@
+use typenum::Unsigned;
+
+use crate::{
+    error::LenMismatch,
+    layout::OctreeLayout,
+    octant::{Axis, Octant},
+    util::{layer_length, leaf_index_from_coords, leaf_index_to_coords},
+};
+
+use super::{
+    column::{axis_mask, next_along},
+    visit_leaves, Octree, OctreeNode,
+};
+
+/// Distance metric used by leaf grid algorithms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// Distance is the largest difference along any axis, so all 26
+    /// surrounding leaves are at distance `1`.
+    Chebyshev,
+    /// Distance is the sum of differences along all axes, so only the 6
+    /// face-adjacent leaves are at distance `1`.
+    Manhattan,
+}
+
+/// Neighbourhood of a leaf considered connected to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Leaves are connected through faces only.
+    Six,
+    /// Leaves are connected through faces, edges and corners.
+    TwentySix,
+}
+
+impl Connectivity {
+    /// Returns offsets of all leaves connected to a leaf.
+    fn offsets(self) -> Vec<(isize, isize, isize)> {
+        let mut result = Vec::with_capacity(26);
+        for dz in -1isize..=1 {
+            for dy in -1isize..=1 {
+                for dx in -1isize..=1 {
+                    let distance = dx.abs() + dy.abs() + dz.abs();
+                    let connected = match self {
+                        Connectivity::Six => distance == 1,
+                        Connectivity::TwentySix => distance != 0,
+                    };
+                    if connected {
+                        result.push((dx, dy, dz));
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+impl<T: Clone, S: Unsigned, L: OctreeLayout, D: Unsigned, I: Unsigned> OctreeNode<T, S, L, D, I> {
+    /// Returns every pair of face-adjacent leaves in this subtree, along with
+    /// the direction from the first leaf to the second one.
+    ///
+    /// Each pair is yielded once, with the second leaf in the positive
+    /// direction of an axis: [`Octant::RDF`] for `x`, [`Octant::LUF`] for `y`
+    /// and [`Octant::LDB`] for `z`. Pairs are ordered by the morton index of
+    /// the first leaf.
+    pub fn face_pairs(&self) -> impl Iterator<Item = (&T, &T, Octant)> {
+        let mut leaves = Vec::with_capacity(layer_length(D::USIZE));
+        unsafe {
+            visit_leaves::<T, L>(
+                self.value_ptr(),
+                S::USIZE,
+                D::USIZE,
+                I::USIZE,
+                &mut |leaf, _| leaves.push(&*leaf),
+            );
+        }
+        let side = 1 << D::USIZE;
+
+        (0..leaves.len() * 3).filter_map(move |it| {
+            let index = it / 3;
+            let (x, y, z) = leaf_index_to_coords(index);
+            let (neighbor, direction) = match it % 3 {
+                0 if x + 1 < side => (leaf_index_from_coords(x + 1, y, z), Octant::RDF),
+                1 if y + 1 < side => (leaf_index_from_coords(x, y + 1, z), Octant::LUF),
+                2 if z + 1 < side => (leaf_index_from_coords(x, y, z + 1), Octant::LDB),
+                _ => return None,
+            };
+            Some((leaves[index], leaves[neighbor], direction))
+        })
+    }
+}
+
+impl<T: Clone, Depth: Unsigned, L: OctreeLayout> Octree<T, Depth, L> {
+    /// Returns an octree with leaves labeled by the connected component of
+    /// `solid` leaves they belong to, along with the number of components.
+    ///
+    /// Component labels start at `1` and are assigned in grid order (`x`
+    /// changing the fastest), while non-solid leaves and all interior nodes
+    /// are labeled `0`.
+    pub fn label_components(
+        &self,
+        solid: impl Fn(&T) -> bool,
+        connectivity: Connectivity,
+    ) -> (Octree<u32, Depth, L>, usize) {
+        let side = Self::SIDE as isize;
+        let solid = self.map_leaf_grid(solid);
+        let offsets = connectivity.offsets();
+
+        let mut labels = vec![0u32; solid.len()];
+        let mut visited = vec![false; solid.len()];
+        let mut queue = std::collections::VecDeque::new();
+        let mut count = 0;
+        for start in 0..solid.len() {
+            if !solid[start] || visited[start] {
+                continue;
+            }
+            count += 1;
+            visited[start] = true;
+            queue.push_back(start);
+            while let Some(current) = queue.pop_front() {
+                labels[current] = count as u32;
+                let x = (current % Self::SIDE) as isize;
+                let y = (current / Self::SIDE % Self::SIDE) as isize;
+                let z = (current / (Self::SIDE * Self::SIDE)) as isize;
+                for (dx, dy, dz) in &offsets {
+                    let (nx, ny, nz) = (x + dx, y + dy, z + dz);
+                    if nx < 0 || ny < 0 || nz < 0 || nx >= side || ny >= side || nz >= side {
+                        continue;
+                    }
+                    let next = Self::grid_index(nx as usize, ny as usize, nz as usize);
+                    if solid[next] && !visited[next] {
+                        visited[next] = true;
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+
+        let mut result = Octree::new(0);
+        result.write_leaf_grid(labels);
+        (result, count)
+    }
+
+    /// Sets the leaf at the `start` path, and all leaves connected to it
+    /// through faces of leaves equal to it, to `value`.
+    ///
+    /// Only leaves reachable in at most `max_distance` steps between
+    /// face-adjacent leaves are set, so `0` sets only the `start` leaf.
+    /// Values of interior nodes aren't updated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start` isn't a path to a leaf, i.e. if its length isn't
+    /// `Depth`.
+    pub fn flood_fill_3d_distance(&mut self, start: &[Octant], max_distance: u32, value: T)
+    where
+        T: PartialEq,
+    {
+        assert_eq!(start.len(), Depth::USIZE, "start isn't a path to a leaf");
+        let side = Self::SIDE;
+        let start = crate::util::path_to_index(start);
+        let target = self.data[Self::leaf_data_index(start)].clone();
+
+        let mut visited = vec![false; layer_length(Depth::USIZE)];
+        let mut queue = std::collections::VecDeque::new();
+        visited[start] = true;
+        queue.push_back((start, 0));
+        while let Some((current, distance)) = queue.pop_front() {
+            self.data[Self::leaf_data_index(current)] = value.clone();
+            if distance == max_distance {
+                continue;
+            }
+            let (x, y, z) = leaf_index_to_coords(current);
+            let neighbors = [
+                (x > 0).then(|| (x - 1, y, z)),
+                (x + 1 < side).then_some((x + 1, y, z)),
+                (y > 0).then(|| (x, y - 1, z)),
+                (y + 1 < side).then_some((x, y + 1, z)),
+                (z > 0).then(|| (x, y, z - 1)),
+                (z + 1 < side).then_some((x, y, z + 1)),
+            ];
+            for (nx, ny, nz) in neighbors.into_iter().flatten() {
+                let next = leaf_index_from_coords(nx, ny, nz);
+                if !visited[next] && self.data[Self::leaf_data_index(next)] == target {
+                    visited[next] = true;
+                    queue.push_back((next, distance + 1));
+                }
+            }
+        }
+    }
+
+    /// Returns a set of disjoint boxes exactly covering all leaves for which
+    /// `pred` holds, as inclusive `(min, max)` leaf coordinates.
+    ///
+    /// Boxes are built greedily in grid order: starting from the first
+    /// uncovered matching leaf, each box is grown along `x`, then `y` and
+    /// finally `z` for as long as all the newly covered leaves match. The
+    /// result is small, but not necessarily minimal.
+    #[allow(clippy::type_complexity)]
+    pub fn greedy_boxes(
+        &self,
+        pred: impl Fn(&T) -> bool,
+    ) -> Vec<((usize, usize, usize), (usize, usize, usize))> {
+        let side = Self::SIDE;
+        let matching = self.map_leaf_grid(pred);
+        let mut consumed = vec![false; matching.len()];
+        let free = |consumed: &[bool], x, y, z| {
+            let index = Self::grid_index(x, y, z);
+            matching[index] && !consumed[index]
+        };
+
+        let mut result = Vec::new();
+        for z in 0..side {
+            for y in 0..side {
+                for x in 0..side {
+                    if !free(&consumed, x, y, z) {
+                        continue;
+                    }
+                    let mut end_x = x;
+                    while end_x + 1 < side && free(&consumed, end_x + 1, y, z) {
+                        end_x += 1;
+                    }
+                    let mut end_y = y;
+                    while end_y + 1 < side
+                        && (x..=end_x).all(|ix| free(&consumed, ix, end_y + 1, z))
+                    {
+                        end_y += 1;
+                    }
+                    let mut end_z = z;
+                    while end_z + 1 < side
+                        && (y..=end_y)
+                            .all(|iy| (x..=end_x).all(|ix| free(&consumed, ix, iy, end_z + 1)))
+                    {
+                        end_z += 1;
+                    }
+
+                    for iz in z..=end_z {
+                        for iy in y..=end_y {
+                            for ix in x..=end_x {
+                                consumed[Self::grid_index(ix, iy, iz)] = true;
+                            }
+                        }
+                    }
+                    result.push(((x, y, z), (end_x, end_y, end_z)));
+                }
+            }
+        }
+        result
+    }
+
+    /// Number of leaves along each axis of the leaf grid.
+    pub(super) const SIDE: usize = 1 << Depth::USIZE;
+
+    /// Returns leaf values as a dense `2^Depth` cubed grid, with `x` changing
+    /// the fastest and `z` the slowest.
+    pub fn to_dense_grid(&self) -> Vec<T> {
+        self.map_leaf_grid(T::clone)
+    }
+
+    /// Constructs an octree with leaves below the terrain described by
+    /// `heights` set to `below`, and all other nodes set to `above`.
+    ///
+    /// `heights` is a `2^Depth` squared heightfield with `x` changing the
+    /// fastest, and a leaf at `(x, y, z)` is below the terrain if `y` is less
+    /// than the height at `(x, z)`. Heights are clamped to `2^Depth`. Interior
+    /// nodes are left as `above`, use a propagator to update them.
+    pub fn from_heightmap(heights: &[u16], below: T, above: T) -> Result<Self, LenMismatch> {
+        if heights.len() != Self::SIDE * Self::SIDE {
+            return Err(LenMismatch {
+                expected: Self::SIDE * Self::SIDE,
+                got: heights.len(),
+            });
+        }
+
+        let mut result = Self::new(above);
+        for z in 0..Self::SIDE {
+            for x in 0..Self::SIDE {
+                let height = (heights[x + z * Self::SIDE] as usize).min(Self::SIDE);
+                for y in 0..height {
+                    result.data[Self::leaf_data_index(leaf_index_from_coords(x, y, z))] =
+                        below.clone();
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Constructs an octree with leaves containing at least one of the
+    /// `points` set to `occupied`, and all other nodes set to `empty`.
+    ///
+    /// Points are in the unit cube `[0, 1]^3` covering the whole grid, and
+    /// ones outside of it are ignored. Interior nodes are left as `empty`.
+    pub fn from_point_cloud(points: &[[f32; 3]], occupied: T, empty: T) -> Self {
+        let mut result = Self::new(empty);
+        let side = Self::SIDE as f32;
+        for point in points {
+            if !point.iter().all(|it| (0.0..=1.0).contains(it)) {
+                continue;
+            }
+            let [x, y, z] = point.map(|it| ((it * side) as usize).min(Self::SIDE - 1));
+            result.data[Self::leaf_data_index(leaf_index_from_coords(x, y, z))] = occupied.clone();
+        }
+        result
+    }
+
+    /// Returns leaves along the `axis`, starting at `start` coordinates and
+    /// ending at the edge of the grid.
+    ///
+    /// With the `axis` component of `start` set to `0`, this yields all
+    /// `2^Depth` leaves of a line.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start` is outside of the `2^Depth` grid.
+    pub fn scan_line(&self, start: [usize; 3], axis: Axis) -> impl Iterator<Item = &T> {
+        assert!(
+            start.iter().all(|it| *it < Self::SIDE),
+            "start coordinates out of range"
+        );
+        let mask = axis_mask(axis);
+        let axis = axis as usize;
+        let first = leaf_index_from_coords(start[0], start[1], start[2]);
+
+        (start[axis]..Self::SIDE).scan(first, move |index, _| {
+            let current = *index;
+            *index = next_along(current, mask);
+            Some(&self.data[Self::leaf_data_index(current)])
+        })
+    }
+
+    /// Returns leaf values mapped with `f` as a dense grid, indexed with
+    /// [`Octree::grid_index`].
+    pub(super) fn map_leaf_grid<U>(&self, f: impl Fn(&T) -> U) -> Vec<U> {
+        let side = Self::SIDE;
+        let mut result = Vec::with_capacity(side * side * side);
+        for z in 0..side {
+            for y in 0..side {
+                for x in 0..side {
+                    let index = Self::leaf_data_index(leaf_index_from_coords(x, y, z));
+                    result.push(f(&self.data[index]));
+                }
+            }
+        }
+        result
+    }
+
+    /// Writes dense `grid` values into leaves of this octree.
+    pub(super) fn write_leaf_grid(&mut self, grid: impl IntoIterator<Item = T>) {
+        let side = Self::SIDE;
+        let mut grid = grid.into_iter();
+        for z in 0..side {
+            for y in 0..side {
+                for x in 0..side {
+                    let index = Self::leaf_data_index(leaf_index_from_coords(x, y, z));
+                    self.data[index] = grid.next().expect("grid too small");
+                }
+            }
+        }
+    }
+
+    /// Returns the index of the leaf at the given coordinates within a dense
+    /// leaf grid, where `x` changes the fastest and `z` the slowest.
+    #[inline]
+    pub(super) const fn grid_index(x: usize, y: usize, z: usize) -> usize {
+        (z * Self::SIDE + y) * Self::SIDE + x
+    }
+}
+
+impl<Depth: Unsigned, L: OctreeLayout> Octree<bool, Depth, L> {
+    /// Returns an octree where each leaf stores the distance (in leaves) to
+    /// the nearest `true` leaf, measured with the given `metric`.
+    ///
+    /// Distances are computed with a two-pass chamfer transform over the leaf
+    /// grid and saturate at `u16::MAX`, which is also the value of all leaves
+    /// if no leaf is set. Interior nodes store the smallest distance in their
+    /// subtree.
+    pub fn distance_field(&self, metric: Metric) -> Octree<u16, Depth, L> {
+        let side = Self::SIDE as isize;
+        let mut grid = self.map_leaf_grid(|it| if *it { 0 } else { u16::MAX });
+
+        // Neighbours preceding a leaf in grid order; following ones are
+        // obtained by negation.
+        let mut neighbours = Vec::with_capacity(13);
+        for dz in -1isize..=0 {
+            for dy in -1isize..=1 {
+                for dx in -1isize..=1 {
+                    let preceding = dz < 0 || (dz == 0 && (dy < 0 || (dy == 0 && dx < 0)));
+                    let allowed = match metric {
+                        Metric::Chebyshev => true,
+                        Metric::Manhattan => dx.abs() + dy.abs() + dz.abs() == 1,
+                    };
+                    if preceding && allowed {
+                        neighbours.push((dx, dy, dz));
+                    }
+                }
+            }
+        }
+
+        let mut relax = |x: isize, y: isize, z: isize, sign: isize| {
+            let current = Self::grid_index(x as usize, y as usize, z as usize);
+            for (dx, dy, dz) in &neighbours {
+                let (nx, ny, nz) = (x + dx * sign, y + dy * sign, z + dz * sign);
+                if nx < 0 || ny < 0 || nz < 0 || nx >= side || ny >= side || nz >= side {
+                    continue;
+                }
+                let other = grid[Self::grid_index(nx as usize, ny as usize, nz as usize)];
+                grid[current] = grid[current].min(other.saturating_add(1));
+            }
+        };
+        for z in 0..side {
+            for y in 0..side {
+                for x in 0..side {
+                    relax(x, y, z, 1);
+                }
+            }
+        }
+        for z in (0..side).rev() {
+            for y in (0..side).rev() {
+                for x in (0..side).rev() {
+                    relax(x, y, z, -1);
+                }
+            }
+        }
+
+        let mut result = Octree::new(u16::MAX);
+        result.write_leaf_grid(grid);
+        result.propagate_min();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typenum::{U2, U3};
+
+    use super::*;
+    use crate::{
+        layout::{BreathFirst, DepthFirst},
+        octant::{OctantLDF, OctantRUB},
+        test_support::xorshift,
+    };
+
+    fn brute_force<Depth: Unsigned, L: OctreeLayout>(
+        tree: &Octree<bool, Depth, L>,
+        metric: Metric,
+    ) -> Vec<u16> {
+        let side = Octree::<bool, Depth, L>::SIDE;
+        let mut set = Vec::new();
+        for z in 0..side {
+            for y in 0..side {
+                for x in 0..side {
+                    if *tree.get(x, y, z).unwrap() {
+                        set.push((x as isize, y as isize, z as isize));
+                    }
+                }
+            }
+        }
+        let mut result = Vec::new();
+        for z in 0..side as isize {
+            for y in 0..side as isize {
+                for x in 0..side as isize {
+                    let distance = set
+                        .iter()
+                        .map(|(sx, sy, sz)| {
+                            let (dx, dy, dz) = ((sx - x).abs(), (sy - y).abs(), (sz - z).abs());
+                            match metric {
+                                Metric::Chebyshev => dx.max(dy).max(dz),
+                                Metric::Manhattan => dx + dy + dz,
+                            }
+                        })
+                        .min()
+                        .map(|it| it as u16)
+                        .unwrap_or(u16::MAX);
+                    result.push(distance);
+                }
+            }
+        }
+        result
+    }
+
+    /// Labels components by repeatedly taking the smallest label among
+    /// neighbours until nothing changes.
+    fn brute_force_components<Depth: Unsigned, L: OctreeLayout>(
+        tree: &Octree<bool, Depth, L>,
+        connectivity: Connectivity,
+    ) -> usize {
+        let side = Octree::<bool, Depth, L>::SIDE as isize;
+        let solid = tree.map_leaf_grid(|it| *it);
+        let mut labels: Vec<usize> = (0..solid.len()).collect();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for i in 0..solid.len() {
+                if !solid[i] {
+                    continue;
+                }
+                let (x, y, z) = (
+                    i as isize % side,
+                    i as isize / side % side,
+                    i as isize / side / side,
+                );
+                for (dx, dy, dz) in connectivity.offsets() {
+                    let (nx, ny, nz) = (x + dx, y + dy, z + dz);
+                    if [nx, ny, nz].iter().any(|it| *it < 0 || *it >= side) {
+                        continue;
+                    }
+                    let n = ((nz * side + ny) * side + nx) as usize;
+                    if solid[n] && labels[n] < labels[i] {
+                        labels[i] = labels[n];
+                        changed = true;
+                    }
+                }
+            }
+        }
+        (0..solid.len())
+            .filter(|i| solid[*i] && labels[*i] == *i)
+            .count()
+    }
+
+    #[test]
+    fn label_components_test() {
+        let mut test = Octree::<bool, U3>::new(false);
+        test.child_mut::<crate::octant::OctantLDF>()
+            .child_mut::<crate::octant::OctantLDF>()
+            .set_value(true);
+        test.child_mut::<crate::octant::OctantRUB>()
+            .child_mut::<crate::octant::OctantRUB>()
+            .set_value(true);
+
+        let (labels, count) = test.label_components(|it| *it, Connectivity::Six);
+        assert_eq!(count, 2);
+        assert_eq!(labels.get(0, 0, 0), Some(&1));
+        assert_eq!(labels.get(1, 1, 1), Some(&1));
+        assert_eq!(labels.get(6, 6, 6), Some(&2));
+        assert_eq!(labels.get(7, 7, 7), Some(&2));
+        assert_eq!(labels.get(4, 4, 4), Some(&0));
+        assert_eq!(**labels, 0);
+
+        let mut test = Octree::<bool, U3, DepthFirst>::new(false);
+        for i in 0..6 {
+            *test.get_mut(i, 0, 3).unwrap() = true;
+            *test.get_mut(0, i, 3).unwrap() = true;
+        }
+        let (_, count) = test.label_components(|it| *it, Connectivity::Six);
+        assert_eq!(count, 1);
+
+        let mut test = Octree::<bool, U2>::new(false);
+        *test.get_mut(0, 0, 0).unwrap() = true;
+        *test.get_mut(1, 1, 1).unwrap() = true;
+        assert_eq!(test.label_components(|it| *it, Connectivity::Six).1, 2);
+        assert_eq!(
+            test.label_components(|it| *it, Connectivity::TwentySix).1,
+            1
+        );
+    }
+
+    #[test]
+    fn label_components_random_test() {
+        let mut state = 0x9e37_79b9u32;
+        for _ in 0..16 {
+            let mut test = Octree::<bool, U3>::new(false);
+            for _ in 0..96 {
+                let index = xorshift(&mut state) as usize % 512;
+                let (x, y, z) = crate::util::leaf_index_to_coords(index);
+                *test.get_mut(x, y, z).unwrap() = true;
+            }
+            for connectivity in [Connectivity::Six, Connectivity::TwentySix] {
+                let (labels, count) = test.label_components(|it| *it, connectivity);
+                assert_eq!(count, brute_force_components(&test, connectivity));
+                let max = labels.map_leaf_grid(|it| *it).into_iter().max().unwrap();
+                assert_eq!(max as usize, count);
+            }
+        }
+    }
+
+    fn check_from_heightmap<L: OctreeLayout>() {
+        let flat = Octree::<u8, U2, L>::from_heightmap(&[3; 16], 1, 0).unwrap();
+        assert_eq!(flat.count_leaves_satisfying(|v| *v == 1), 3 * 16);
+        assert_eq!(flat.heightmap(|v| *v == 1), vec![2; 16]);
+        assert_eq!(**flat, 0);
+
+        let ramp: Vec<u16> = (0..64).map(|i| (i % 8) as u16 * 2).collect();
+        let stairs = Octree::<u8, U3, L>::from_heightmap(&ramp, 1, 0).unwrap();
+        for z in [0, 3, 7] {
+            for x in 0..8 {
+                let column: Vec<_> = stairs.scan_line([x, 0, z], Axis::Y).copied().collect();
+                let solid = (x * 2).min(8);
+                assert_eq!(column.iter().filter(|it| **it == 1).count(), solid);
+                assert!(column[..solid].iter().all(|it| *it == 1));
+            }
+        }
+
+        assert_eq!(
+            Octree::<u8, U2, L>::from_heightmap(&[0; 15], 1, 0).err(),
+            Some(LenMismatch {
+                expected: 16,
+                got: 15
+            })
+        );
+    }
+
+    #[test]
+    fn from_heightmap_test() {
+        check_from_heightmap::<crate::layout::BreathFirst>();
+        check_from_heightmap::<DepthFirst>();
+    }
+
+    fn check_from_point_cloud<L: OctreeLayout>() {
+        let points = [
+            [0.55, 0.6, 0.9],
+            [0.56, 0.61, 0.91],
+            [0.99, 0.51, 0.75],
+            [1.0, 1.0, 1.0],
+            [0.7, 0.8, 0.5],
+            [1.01, 0.2, 0.2],
+            [-0.1, 0.2, 0.2],
+            [0.3, f32::NAN, 0.2],
+        ];
+        let test = Octree::<u8, U3, L>::from_point_cloud(&points, 1, 0);
+        let rub = test.child::<crate::octant::OctantRUB>();
+        assert_eq!(rub.count_in_subtree(|v| *v == 1), 4);
+        assert_eq!(test.count_leaves_satisfying(|v| *v == 1), 4);
+        assert_eq!(test.get(4, 4, 7), Some(&1));
+        assert_eq!(test.get(7, 4, 6), Some(&1));
+        assert_eq!(test.get(7, 7, 7), Some(&1));
+        assert_eq!(test.get(5, 6, 4), Some(&1));
+        assert_eq!(**test, 0);
+    }
+
+    #[test]
+    fn from_point_cloud_test() {
+        check_from_point_cloud::<crate::layout::BreathFirst>();
+        check_from_point_cloud::<DepthFirst>();
+    }
+
+    fn check_scan_line<L: OctreeLayout>() {
+        let mut test = Octree::<usize, U3, L>::new(0);
+        for z in 0..8 {
+            for y in 0..8 {
+                for x in 0..8 {
+                    *test.get_mut(x, y, z).unwrap() = x + y * 10 + z * 100;
+                }
+            }
+        }
+
+        let column: Vec<_> = test.scan_line([3, 0, 5], Axis::Y).copied().collect();
+        assert_eq!(column, (0..8).map(|y| 3 + y * 10 + 500).collect::<Vec<_>>());
+        let row: Vec<_> = test.scan_line([2, 7, 1], Axis::X).copied().collect();
+        assert_eq!(row, (2..8).map(|x| x + 70 + 100).collect::<Vec<_>>());
+        let depth: Vec<_> = test.scan_line([7, 7, 6], Axis::Z).copied().collect();
+        assert_eq!(depth, vec![677, 777]);
+    }
+
+    #[test]
+    fn scan_line_test() {
+        check_scan_line::<crate::layout::BreathFirst>();
+        check_scan_line::<DepthFirst>();
+    }
+
+    #[test]
+    fn to_dense_grid_test() {
+        let mut test = Octree::<u8, U2, DepthFirst>::new(0);
+        *test.get_mut(1, 0, 0).unwrap() = 1;
+        *test.get_mut(0, 1, 0).unwrap() = 2;
+        *test.get_mut(3, 3, 3).unwrap() = 3;
+
+        let grid = test.to_dense_grid();
+        assert_eq!(grid.len(), 64);
+        assert_eq!(&grid[..6], &[0, 1, 0, 0, 2, 0]);
+        assert_eq!(grid[63], 3);
+    }
+
+    #[test]
+    fn greedy_boxes_test() {
+        let mut test = Octree::<u8, U3>::new(0);
+        test.child_mut::<crate::octant::OctantLUB>().set_value(1);
+        assert_eq!(
+            test.greedy_boxes(|it| *it == 1),
+            vec![((0, 4, 4), (3, 7, 7))]
+        );
+
+        let mut test = Octree::<u8, U3, DepthFirst>::new(0);
+        for (x, y) in [(2, 1), (1, 2), (2, 2), (3, 2), (2, 3)] {
+            *test.get_mut(x, y, 0).unwrap() = 1;
+        }
+        assert_eq!(
+            test.greedy_boxes(|it| *it == 1),
+            vec![
+                ((2, 1, 0), (2, 3, 0)),
+                ((1, 2, 0), (1, 2, 0)),
+                ((3, 2, 0), (3, 2, 0)),
+            ]
+        );
+
+        assert!(Octree::<u8, U2>::new(0)
+            .greedy_boxes(|it| *it == 1)
+            .is_empty());
+    }
+
+    #[test]
+    fn greedy_boxes_random_test() {
+        let mut state = 0x1234_5678u32;
+        for _ in 0..16 {
+            let mut test = Octree::<bool, U3>::new(false);
+            for _ in 0..200 {
+                let index = xorshift(&mut state) as usize % 512;
+                let (x, y, z) = crate::util::leaf_index_to_coords(index);
+                *test.get_mut(x, y, z).unwrap() = true;
+            }
+
+            let mut covered = vec![false; 512];
+            for ((x0, y0, z0), (x1, y1, z1)) in test.greedy_boxes(|it| *it) {
+                for z in z0..=z1 {
+                    for y in y0..=y1 {
+                        for x in x0..=x1 {
+                            let index = Octree::<bool, U3>::grid_index(x, y, z);
+                            assert!(!covered[index], "boxes overlap");
+                            covered[index] = true;
+                        }
+                    }
+                }
+            }
+            assert_eq!(covered, test.map_leaf_grid(|it| *it));
+        }
+    }
+
+    #[test]
+    fn distance_field_ring_test() {
+        let mut test = Octree::<bool, U3>::new(false);
+        *test.get_mut(3, 4, 2).unwrap() = true;
+
+        let field = test.distance_field(Metric::Chebyshev);
+        assert_eq!(field.get(3, 4, 2), Some(&0));
+        assert_eq!(field.get(2, 5, 1), Some(&1));
+        assert_eq!(field.get(5, 2, 2), Some(&2));
+        assert_eq!(field.get(7, 4, 2), Some(&4));
+        assert_eq!(**field, 0);
+
+        let field = test.distance_field(Metric::Manhattan);
+        assert_eq!(field.get(2, 5, 1), Some(&3));
+        assert_eq!(field.get(3, 4, 3), Some(&1));
+        assert_eq!(field.get(5, 2, 2), Some(&4));
+    }
+
+    #[test]
+    fn distance_field_empty_test() {
+        let test = Octree::<bool, U2>::new(false);
+        let field = test.distance_field(Metric::Manhattan);
+        assert!(field.data.iter().all(|it| *it == u16::MAX));
+    }
+
+    #[test]
+    fn distance_field_brute_force_test() {
+        let mut test = Octree::<bool, U3, DepthFirst>::new(false);
+        for (x, y, z) in [(0, 0, 0), (7, 1, 5), (4, 4, 4), (1, 6, 7)] {
+            *test.get_mut(x, y, z).unwrap() = true;
+        }
+        for metric in [Metric::Chebyshev, Metric::Manhattan] {
+            let field = test.distance_field(metric);
+            assert_eq!(field.map_leaf_grid(|it| *it), brute_force(&test, metric));
+        }
+    }
+
+    fn check_face_pairs<L: OctreeLayout>() {
+        let mut test = Octree::<[usize; 3], U2, L>::new([usize::MAX; 3]);
+        for z in 0..4 {
+            for y in 0..4 {
+                for x in 0..4 {
+                    *test.get_mut(x, y, z).unwrap() = [x, y, z];
+                }
+            }
+        }
+        let check = |a: &[usize; 3], b: &[usize; 3], direction: Octant| {
+            let axis = match direction {
+                Octant::RDF => 0,
+                Octant::LUF => 1,
+                Octant::LDB => 2,
+                _ => panic!("unexpected direction {direction:?}"),
+            };
+            let mut expected = *a;
+            expected[axis] += 1;
+            assert_eq!(*b, expected);
+        };
+
+        assert_eq!(test.root().face_pairs().count(), 3 * 4 * 4 * 3);
+        for (a, b, direction) in test.root().face_pairs() {
+            check(a, b, direction);
+        }
+
+        let region = test.child::<OctantRUB>();
+        assert_eq!(region.face_pairs().count(), 12);
+        for (a, b, direction) in region.face_pairs() {
+            assert!(a.iter().chain(b).all(|it| *it >= 2));
+            check(a, b, direction);
+        }
+    }
+
+    fn check_flood_fill_3d_distance<L: OctreeLayout>() {
+        let start = [Octant::LDF, Octant::RUB, Octant::LDF];
+        let (x, y, z) = (2, 2, 2);
+        let changed = |tree: &Octree<u8, U3, L>| {
+            let mut result = Vec::new();
+            for i in 0..512 {
+                let (x, y, z) = leaf_index_to_coords(i);
+                if tree.get(x, y, z) == Some(&1) {
+                    result.push((x, y, z));
+                }
+            }
+            result
+        };
+
+        let mut test = Octree::<u8, U3, L>::new(0);
+        test.flood_fill_3d_distance(&start, 0, 1);
+        assert_eq!(changed(&test), vec![(x, y, z)]);
+        assert_eq!(**test, 0);
+
+        let mut test = Octree::<u8, U3, L>::new(0);
+        test.flood_fill_3d_distance(&start, 1, 1);
+        let mut expected = vec![
+            (x, y, z),
+            (x - 1, y, z),
+            (x + 1, y, z),
+            (x, y - 1, z),
+            (x, y + 1, z),
+            (x, y, z - 1),
+            (x, y, z + 1),
+        ];
+        let mut result = changed(&test);
+        expected.sort();
+        result.sort();
+        assert_eq!(result, expected);
+
+        // Leaves different from the start leaf block the fill, so reaching
+        // around a wall takes more steps.
+        let mut test = Octree::<u8, U3, L>::new(0);
+        for wy in 0..8 {
+            for wz in 0..8 {
+                *test.get_mut(3, wy, wz).unwrap() = 2;
+            }
+        }
+        *test.get_mut(3, 2, 2).unwrap() = 0;
+        test.flood_fill_3d_distance(&start, 3, 1);
+        for (x, y, z) in [(5, 2, 2), (4, 3, 2), (0, 2, 2), (2, 3, 3)] {
+            assert_eq!(test.get(x, y, z), Some(&1));
+        }
+        for (x, y, z) in [(6, 2, 2), (4, 3, 3), (4, 4, 2), (0, 0, 2)] {
+            assert_eq!(test.get(x, y, z), Some(&0));
+        }
+        assert_eq!(test.get(3, 3, 2), Some(&2));
+    }
+
+    #[test]
+    fn flood_fill_3d_distance_test() {
+        check_flood_fill_3d_distance::<BreathFirst>();
+        check_flood_fill_3d_distance::<DepthFirst>();
+    }
+
+    #[test]
+    fn face_pairs_test() {
+        check_face_pairs::<BreathFirst>();
+        check_face_pairs::<DepthFirst>();
+
+        let solid = Octree::<bool, U2>::new(true);
+        let region = solid.child::<OctantLDF>();
+        assert_eq!(
+            region.face_pairs().filter(|(a, b, _)| **a && **b).count(),
+            12
+        );
+        assert_eq!(
+            solid.root().face_pairs().filter(|(a, b, _)| a != b).count(),
+            0
+        );
+    }
+}
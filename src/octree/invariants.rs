@@ -0,0 +1,118 @@
+use typenum::Unsigned;
+
+use crate::{element::MaybeNan, layout::OctreeLayout};
+
+use super::Octree;
+
+impl<T: Clone, Depth: Unsigned, L: OctreeLayout> Octree<T, Depth, L> {
+    /// Panics if any structural invariant of the octree is violated.
+    ///
+    /// This checks that the data length matches `Depth` and that layer
+    /// offsets computed for the layout are within the data, in order. With
+    /// the `nan_check` feature enabled, values are also checked not to be NaN
+    /// (see [`MaybeNan`]).
+    ///
+    /// Unlike a full validation, this assumes memory wasn't corrupted. All
+    /// checks are compiled away in release builds.
+    #[inline]
+    pub fn assert_invariants(&self)
+    where
+        T: MaybeNan,
+    {
+        #[cfg(debug_assertions)]
+        {
+            self.assert_structure();
+            #[cfg(feature = "nan_check")]
+            if let Some(index) = self.data.iter().position(MaybeNan::is_nan) {
+                panic!("octree value at index {} is NaN", index);
+            }
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    fn assert_structure(&self) {
+        use crate::util::layer_length;
+
+        let len = self.data.len();
+        assert_eq!(
+            len,
+            Self::node_count(),
+            "octree of depth {} has {} values",
+            Depth::USIZE,
+            len
+        );
+
+        let mut previous = None;
+        for layer in 0..=Depth::USIZE {
+            let first = Self::node_data_index(layer, 0);
+            let last = Self::node_data_index(layer, layer_length(layer) - 1);
+            assert!(
+                first <= last && last < len,
+                "layer {} offsets out of bounds",
+                layer
+            );
+            assert!(
+                previous.map_or(first == 0, |previous| previous < first),
+                "layer {} offset out of order",
+                layer
+            );
+            previous = Some(first);
+        }
+        assert_eq!(
+            Self::leaf_data_index(layer_length(Depth::USIZE) - 1),
+            len - 1,
+            "last leaf isn't at the end of data"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typenum::{U0, U3};
+
+    use super::*;
+    use crate::layout::{BreathFirst, DepthFirst};
+
+    #[test]
+    fn assert_invariants_test() {
+        Octree::<u8, U0>::new(0).assert_invariants();
+        Octree::<u8, U3, BreathFirst>::new(0).assert_invariants();
+        Octree::<u8, U3, DepthFirst>::new(0).assert_invariants();
+        Octree::<f32, U3>::new(1.0).assert_invariants();
+        Octree::<f64, U3>::new(1.0).assert_invariants();
+    }
+
+    #[test]
+    #[cfg(all(debug_assertions, feature = "nan_check"))]
+    #[should_panic(expected = "is NaN")]
+    fn assert_invariants_nan_f32_test() {
+        let mut test = Octree::<f32, U3, DepthFirst>::new(0.0);
+        *test.get_mut(7, 0, 0).unwrap() = f32::NAN;
+        test.assert_invariants();
+    }
+
+    #[test]
+    #[cfg(all(debug_assertions, feature = "nan_check"))]
+    fn assert_invariants_nan_other_type_test() {
+        // Arrays of floats aren't checked.
+        Octree::<[f32; 2], U3>::new([f32::NAN; 2]).assert_invariants();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "octree of depth 3 has 584 values")]
+    fn assert_invariants_length_test() {
+        let mut test = Octree::<u8, U3, DepthFirst>::new(0);
+        test.data = test.data[1..].to_vec().into();
+        test.assert_invariants();
+    }
+
+    #[test]
+    #[cfg(all(debug_assertions, feature = "nan_check"))]
+    #[should_panic(expected = "is NaN")]
+    fn assert_invariants_nan_test() {
+        let mut test = Octree::<f64, U3>::new(0.0);
+        *test.get_mut(1, 2, 3).unwrap() = f64::NAN;
+        test.assert_invariants();
+    }
+}
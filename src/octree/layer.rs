@@ -0,0 +1,560 @@
+use std::marker::PhantomData;
+
+use typenum::{IsLessOrEqual, LeEq, Same, True, Unsigned};
+
+use crate::{
+    error::{LayerLenMismatch, LenMismatch},
+    layout::{BreathFirst, OctreeLayout},
+    octant::Octant,
+    util::{layer_length, node_offset_bf, subtree_length},
+};
+
+use super::{Octree, OctreeNode};
+
+impl<T: Clone, Depth: Unsigned, L: OctreeLayout> Octree<T, Depth, L> {
+    /// Sets all nodes at the given `depth` to `value`, without changing any
+    /// other layer.
+    ///
+    /// Layouts with [`OctreeLayout::CONTIGUOUS_LAYERS`], such as
+    /// [`BreathFirst`], fill the layer as a single slice, while other layouts
+    /// visit every layer node separately.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `depth` is larger than the octree depth.
+    pub fn fill_layer(&mut self, depth: usize, value: T) {
+        self.assert_layer(depth);
+        if L::CONTIGUOUS_LAYERS {
+            self.data[BreathFirst::layer_range(depth, Depth::USIZE)].fill(value);
+            return;
+        }
+        for index in 0..layer_length(depth) {
+            self.data[Self::node_data_index(depth, index)] = value.clone();
+        }
+    }
+
+    /// Sets all nodes at the `Layer` depth to `value`, without changing any
+    /// other layer.
+    ///
+    /// This is [`Octree::fill_layer`] with the depth checked at compile time.
+    pub fn fill_layer_typed<Layer>(&mut self, value: T)
+    where
+        Layer: Unsigned + IsLessOrEqual<Depth>,
+        LeEq<Layer, Depth>: Same<True>,
+    {
+        self.fill_layer(Layer::USIZE, value);
+    }
+
+    /// Returns `true` if all node values at the `Layer` depth are equal to
+    /// values of the same nodes in `other`.
+    ///
+    /// Other layers aren't compared.
+    pub fn compare_layers<Layer>(&self, other: &Self) -> bool
+    where
+        T: PartialEq,
+        Layer: Unsigned + IsLessOrEqual<Depth>,
+        LeEq<Layer, Depth>: Same<True>,
+    {
+        (0..layer_length(Layer::USIZE)).all(|index| {
+            let offset = Self::node_data_index(Layer::USIZE, index);
+            self.data[offset] == other.data[offset]
+        })
+    }
+
+    /// Returns an iterator over all interior nodes, along with their
+    /// children ordered as [`Octant::ALL`].
+    ///
+    /// Families are visited bottom-up by layer, starting with parents of
+    /// leaves, and by node index within each layer. This order is the same
+    /// for all layouts.
+    pub fn families(&self) -> impl Iterator<Item = (&T, [&T; 8])> {
+        (0..Depth::USIZE).rev().flat_map(move |layer| {
+            (0..layer_length(layer)).map(move |index| {
+                let offset = Self::node_data_index(layer, index);
+                let depth = Depth::USIZE - layer;
+                let children = Octant::ALL
+                    .map(|octant| &self.data[Self::child_data_index(offset, octant, depth, index)]);
+                (&self.data[offset], children)
+            })
+        })
+    }
+
+    /// Overwrites all nodes at the given `depth` with `values` ordered by
+    /// their index within the layer, without changing any other layer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `depth` is larger than the octree depth.
+    pub fn copy_layer_from_slice(&mut self, depth: usize, values: &[T]) -> Result<(), LenMismatch> {
+        self.assert_layer(depth);
+        let expected = layer_length(depth);
+        if values.len() != expected {
+            return Err(LenMismatch {
+                expected,
+                got: values.len(),
+            });
+        }
+        for (index, value) in values.iter().enumerate() {
+            self.data[Self::node_data_index(depth, index)] = value.clone();
+        }
+        Ok(())
+    }
+
+    /// Overwrites all leaves with `leaves` ordered by their morton index (see
+    /// [`leaf_index_from_coords`](crate::util::leaf_index_from_coords)),
+    /// without changing interior nodes.
+    pub fn fill_leaves_from_slice(&mut self, leaves: &[T]) -> Result<(), LenMismatch> {
+        self.copy_layer_from_slice(Depth::USIZE, leaves)
+    }
+
+    /// Constructs an octree from per-layer values, starting with the root
+    /// layer, with nodes in each layer ordered by their index.
+    ///
+    /// Values are moved, never cloned. Layouts with
+    /// [`OctreeLayout::CONTIGUOUS_LAYERS`], such as [`BreathFirst`], append
+    /// whole layers to the buffer, while other layouts scatter values node by
+    /// node.
+    pub fn from_layers(layers: Vec<Vec<T>>) -> Result<Self, LayerLenMismatch> {
+        if layers.len() != Depth::USIZE + 1 {
+            return Err(LayerLenMismatch::LayerCount {
+                expected: Depth::USIZE + 1,
+                got: layers.len(),
+            });
+        }
+        for (layer, values) in layers.iter().enumerate() {
+            if values.len() != layer_length(layer) {
+                return Err(LayerLenMismatch::LayerLength {
+                    layer,
+                    expected: layer_length(layer),
+                    got: values.len(),
+                });
+            }
+        }
+
+        if L::CONTIGUOUS_LAYERS {
+            let mut data = Vec::with_capacity(subtree_length(Depth::USIZE));
+            for mut values in layers {
+                data.append(&mut values);
+            }
+            return Ok(Octree {
+                data: data.into(),
+                _phantom: PhantomData,
+            });
+        }
+
+        let mut slots: Vec<Option<T>> = Vec::new();
+        slots.resize_with(subtree_length(Depth::USIZE), || None);
+        for (layer, values) in layers.into_iter().enumerate() {
+            for (index, value) in values.into_iter().enumerate() {
+                slots[Self::node_data_index(layer, index)] = Some(value);
+            }
+        }
+        Ok(Octree {
+            data: slots.into_iter().map(Option::unwrap).collect(),
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Constructs an octree with every node set to `f(depth, index)`, where
+    /// `depth` is the node layer (`0` for the root) and `index` is its index
+    /// within that layer.
+    ///
+    /// Nodes are initialized in depth-first order for all layouts.
+    pub fn from_depth_table<F: Fn(usize, usize) -> T>(f: F) -> Self {
+        let mut slots: Vec<Option<T>> = Vec::new();
+        slots.resize_with(subtree_length(Depth::USIZE), || None);
+        Self::for_each_node(&mut |offset, layer, index| slots[offset] = Some(f(layer, index)));
+        Octree {
+            data: slots.into_iter().map(Option::unwrap).collect(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Splits the octree into per-layer values, starting with the root layer,
+    /// with nodes in each layer ordered by their index.
+    ///
+    /// This is the inverse of [`Octree::from_layers`]. Values are moved, never
+    /// cloned. Layouts with [`OctreeLayout::CONTIGUOUS_LAYERS`] split whole
+    /// layers off of the buffer, while other layouts gather values node by
+    /// node.
+    pub fn into_layers(self) -> Vec<Vec<T>> {
+        if L::CONTIGUOUS_LAYERS {
+            let mut data = self.data.into_vec();
+            let mut layers: Vec<Vec<T>> = (0..=Depth::USIZE)
+                .rev()
+                .map(|layer| data.split_off(BreathFirst::layer_range(layer, Depth::USIZE).start))
+                .collect();
+            layers.reverse();
+            return layers;
+        }
+
+        let mut slots: Vec<Option<T>> = self.data.into_vec().into_iter().map(Some).collect();
+        (0..=Depth::USIZE)
+            .map(|layer| {
+                (0..layer_length(layer))
+                    .map(|index| slots[Self::node_data_index(layer, index)].take().unwrap())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns all leaves ordered by their morton index (see
+    /// [`leaf_index_from_coords`](crate::util::leaf_index_from_coords)) as
+    /// an array, without allocating.
+    ///
+    /// `N` must be the number of leaves (`8^Depth`), which is checked at
+    /// compile time:
+    ///
+    /// ```compile_fail
+    /// use flat_octree::{typenum::U2, Octree};
+    ///
+    /// let leaves: [u8; 8] = Octree::<u8, U2>::new(0).into_leaf_array();
+    /// ```
+    pub fn into_leaf_array<const N: usize>(self) -> [T; N]
+    where
+        T: Copy,
+    {
+        const_assert!(
+            N == layer_length(Depth::USIZE),
+            "array length must match the number of leaves"
+        );
+        std::array::from_fn(|index| self.data[Self::leaf_data_index(index)])
+    }
+
+    fn assert_layer(&self, depth: usize) {
+        assert!(
+            depth <= Depth::USIZE,
+            "depth {} is out of range for octree of depth {}",
+            depth,
+            Depth::USIZE
+        );
+    }
+}
+
+impl<T: Clone, S: Unsigned, D: Unsigned, I: Unsigned> OctreeNode<T, S, BreathFirst, D, I> {
+    /// Calls `f` with the depth of every layer of this subtree, relative to
+    /// this node, and the contiguous slice of that layer's nodes.
+    ///
+    /// Layers are visited from this node (depth `0`, a single value) down to
+    /// the leaves, with layer `k` containing `8^k` nodes.
+    pub fn each_layer<F: FnMut(usize, &[T])>(&self, mut f: F) {
+        let layer = S::USIZE - D::USIZE;
+        let offset = node_offset_bf(layer, I::USIZE, S::USIZE);
+        for depth in 0..=D::USIZE {
+            let len = layer_length(depth);
+            let start = node_offset_bf(layer + depth, I::USIZE * len, S::USIZE) - offset;
+            let values = unsafe {
+                // SAFETY: layers of the subtree are stored contiguously after
+                // its root within the octree data.
+                std::slice::from_raw_parts(self.value_ptr().add(start), len)
+            };
+            f(depth, values);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typenum::{U2, U3};
+
+    use super::*;
+    use crate::{
+        layout::{BreathFirst, DepthFirst},
+        octant::{OctantLUB, OctantRDF},
+    };
+
+    fn check_layer_writes<L: OctreeLayout>() {
+        let mut test = Octree::<usize, U3, L>::new(0).map_with_depth(|depth, _| depth * 1000);
+        let source: Vec<usize> = (0..8).map(|it| it + 10).collect();
+
+        test.copy_layer_from_slice(1, &source).unwrap();
+        let layers = test.map_with_depth(|depth, value| (depth, *value));
+        for (depth, value) in layers.data.iter() {
+            if *depth != 1 {
+                assert_eq!(*value, depth * 1000);
+            }
+        }
+        let dyn_root = test.dyn_root();
+        for octant in crate::octant::Octant::ALL {
+            let child = dyn_root.child(octant).unwrap();
+            assert_eq!(*child.value(), octant.as_usize() + 10);
+        }
+
+        let mut typed = Octree::<usize, U3, L> {
+            data: test.data.clone(),
+            _phantom: PhantomData,
+        };
+        test.fill_layer(2, 7);
+        typed.fill_layer_typed::<typenum::U2>(7);
+        assert_eq!(typed.data, test.data);
+        let layers = test.map_with_depth(|depth, value| (depth, *value));
+        for (depth, value) in layers.data.iter() {
+            match depth {
+                1 => assert!((10..18).contains(value)),
+                2 => assert_eq!(*value, 7),
+                _ => assert_eq!(*value, depth * 1000),
+            }
+        }
+
+        assert_eq!(
+            test.copy_layer_from_slice(2, &source),
+            Err(LenMismatch {
+                expected: 64,
+                got: 8
+            })
+        );
+    }
+
+    fn check_fill_leaves<L: OctreeLayout>() {
+        let mut test = Octree::<usize, U3, L>::new(0).map_with_depth(|depth, _| depth * 1000);
+        let leaves: Vec<usize> = (0..512).collect();
+        test.fill_leaves_from_slice(&leaves).unwrap();
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let (x, y, z) = crate::util::leaf_index_to_coords(index);
+            assert_eq!(test.get(x, y, z), Some(leaf));
+        }
+        let layers = test.map_with_depth(|depth, value| (depth, *value));
+        for (depth, value) in layers.data.iter() {
+            if *depth != 3 {
+                assert_eq!(*value, depth * 1000);
+            }
+        }
+        assert_eq!(
+            test.fill_leaves_from_slice(&leaves[1..]),
+            Err(LenMismatch {
+                expected: 512,
+                got: 511
+            })
+        );
+    }
+
+    fn check_compare_layers<L: OctreeLayout>() {
+        use typenum::{U0, U1, U2};
+
+        let mut a = Octree::<u8, U3, L>::new(0);
+        a.child_mut::<crate::octant::OctantLUB>().set_value(1);
+        let mut b = Octree::<u8, U3, L>::new(0);
+        b.child_mut::<crate::octant::OctantLUB>().set_value(1);
+        assert!(a.compare_layers::<U0>(&b));
+        assert!(a.compare_layers::<U1>(&b));
+        assert!(a.compare_layers::<U2>(&b));
+        assert!(a.compare_layers::<U3>(&b));
+
+        *b.get_mut(0, 0, 0).unwrap() = 1;
+        assert!(a.compare_layers::<U0>(&b));
+        assert!(a.compare_layers::<U1>(&b));
+        assert!(a.compare_layers::<U2>(&b));
+        assert!(!a.compare_layers::<U3>(&b));
+
+        *b.get_mut(7, 7, 7).unwrap() = 1;
+        b.propagate_up(&crate::propagate::MostCommon);
+        a.propagate_up(&crate::propagate::MostCommon);
+        assert!(!b.compare_layers::<U3>(&a));
+        assert!(b.compare_layers::<U1>(&a));
+    }
+
+    fn check_families<L: OctreeLayout>() {
+        use crate::octant::*;
+
+        let mut test = Octree::<usize, U3, L>::new(0);
+        Octree::<usize, U3, L>::for_each_node(&mut |offset, layer, index| {
+            test.data[offset] = layer * 1000 + index;
+        });
+
+        let families: Vec<_> = test.families().collect();
+        assert_eq!(families.len(), subtree_length(2));
+        for (parent, children) in &families {
+            let (layer, index) = (**parent / 1000, **parent % 1000);
+            for (octant, child) in children.iter().enumerate() {
+                assert_eq!(**child, (layer + 1) * 1000 + index * 8 + octant);
+            }
+        }
+        assert_eq!(*families[0].0, 2000);
+        assert_eq!(*families[64].0, 1000);
+
+        let (root, children) = families[72];
+        assert_eq!(root, &**test);
+        assert_eq!(children[0], &**test.child::<OctantLDF>());
+        assert_eq!(children[6], &**test.child::<OctantLUB>());
+        let (rdb, children) = families[64 + 5];
+        assert_eq!(rdb, &**test.child::<OctantRDB>());
+        assert_eq!(
+            children[3],
+            &**test.child::<OctantRDB>().child::<OctantRUF>()
+        );
+    }
+
+    #[test]
+    fn each_layer_test() {
+        let test = Octree::<usize, U3>::new_with(|i| i);
+        let mut layers = Vec::new();
+        test.each_layer(|depth, values| {
+            assert_eq!(values.len(), layer_length(depth));
+            layers.extend_from_slice(values);
+        });
+        assert_eq!(layers, *test.data);
+
+        let node = test.child::<OctantLUB>();
+        let mut layers = Vec::new();
+        let mut depths = Vec::new();
+        node.each_layer(|depth, values| {
+            assert_eq!(values.len(), layer_length(depth));
+            depths.push(depth);
+            layers.extend_from_slice(values);
+        });
+        assert_eq!(depths, [0, 1, 2]);
+        let expected: Vec<usize> = (0..=2)
+            .flat_map(|depth| {
+                let len = layer_length(depth);
+                (6 * len..7 * len).map(move |index| node_offset_bf(1 + depth, index, 3))
+            })
+            .collect();
+        assert_eq!(layers, expected);
+        assert_eq!(layers[0], **node);
+        assert_eq!(layers[2], **node.child::<OctantRDF>());
+    }
+
+    #[test]
+    fn families_test() {
+        check_families::<BreathFirst>();
+        check_families::<DepthFirst>();
+
+        let mut test = Octree::<u32, U3>::new(1);
+        for (parent, children) in test.families_mut::<typenum::U1>() {
+            *parent = children.iter().map(|it| **it).sum();
+        }
+        assert_eq!(**test.child::<crate::octant::OctantRUB>(), 8);
+        assert_eq!(**test, 1);
+    }
+
+    #[test]
+    fn compare_layers_test() {
+        check_compare_layers::<BreathFirst>();
+        check_compare_layers::<DepthFirst>();
+    }
+
+    #[test]
+    fn fill_leaves_test() {
+        check_fill_leaves::<BreathFirst>();
+        check_fill_leaves::<DepthFirst>();
+    }
+
+    fn check_into_leaf_array<L: OctreeLayout>() {
+        let mut test = Octree::<u32, typenum::U2, L>::new(u32::MAX);
+        let leaves: Vec<u32> = (0..64).collect();
+        test.fill_leaves_from_slice(&leaves).unwrap();
+        let array: [u32; 64] = test.into_leaf_array();
+        assert_eq!(array.as_slice(), leaves.as_slice());
+    }
+
+    #[test]
+    fn into_leaf_array_test() {
+        check_into_leaf_array::<BreathFirst>();
+        check_into_leaf_array::<DepthFirst>();
+
+        let mut test = Octree::<u8, typenum::U2>::new(0);
+        *test.get_mut(3, 1, 2).unwrap() = 1;
+        let array: [u8; 64] = test.into_leaf_array();
+        assert_eq!(array[crate::util::leaf_index_from_coords(3, 1, 2)], 1);
+        assert_eq!(array.iter().filter(|it| **it == 1).count(), 1);
+    }
+
+    #[test]
+    fn from_depth_table_test() {
+        let bf = Octree::<usize, typenum::U2, BreathFirst>::from_depth_table(|d, i| d * 100 + i);
+        let expected: Vec<usize> = [0].into_iter().chain(100..108).chain(200..264).collect();
+        assert_eq!(bf.data.to_vec(), expected);
+
+        let df = Octree::<usize, typenum::U2, DepthFirst>::from_depth_table(|d, i| d * 100 + i);
+        let expected: Vec<usize> = [0]
+            .into_iter()
+            .chain((0..8).flat_map(|i| [100 + i].into_iter().chain(200 + i * 8..200 + i * 8 + 8)))
+            .collect();
+        assert_eq!(df.data.to_vec(), expected);
+        assert_eq!(**df.child::<OctantRDF>().child::<OctantLUB>(), 200 + 0o16);
+
+        let layers =
+            Octree::<usize, U3, DepthFirst>::from_depth_table(|d, i| d * 1000 + i).into_layers();
+        for (depth, layer) in layers.into_iter().enumerate() {
+            assert!(layer
+                .into_iter()
+                .enumerate()
+                .all(|(i, it)| it == depth * 1000 + i));
+        }
+    }
+
+    #[test]
+    fn layer_writes_test() {
+        check_layer_writes::<BreathFirst>();
+        check_layer_writes::<DepthFirst>();
+    }
+
+    fn check_layers_round_trip<L: OctreeLayout>() {
+        let mut test = Octree::<usize, U3, L>::new(0).map_with_depth(|depth, _| depth);
+        *test.get_mut(5, 1, 6).unwrap() = 9;
+        let layers = Octree::<usize, U3, L> {
+            data: test.data.clone(),
+            _phantom: PhantomData,
+        }
+        .into_layers();
+        assert_eq!(layers.len(), 4);
+        assert_eq!(layers[2], vec![2; 64]);
+        assert_eq!(layers[3][crate::util::leaf_index_from_coords(5, 1, 6)], 9);
+
+        let rebuilt = Octree::<usize, U3, L>::from_layers(layers).ok().unwrap();
+        assert_eq!(rebuilt.data, test.data);
+    }
+
+    #[test]
+    fn layers_round_trip_test() {
+        check_layers_round_trip::<BreathFirst>();
+        check_layers_round_trip::<DepthFirst>();
+
+        let layers: Vec<Vec<String>> = (0..3)
+            .map(|layer| {
+                (0..layer_length(layer))
+                    .map(|i| format!("{layer}.{i}"))
+                    .collect()
+            })
+            .collect();
+        let bf = Octree::<String, U2>::from_layers(layers.clone())
+            .ok()
+            .unwrap();
+        assert_eq!(bf.data[9], "2.0");
+        let df = Octree::<String, U2, DepthFirst>::from_layers(layers.clone())
+            .ok()
+            .unwrap();
+        assert_eq!(df.data[2], "2.0");
+        assert_eq!(bf.into_layers(), layers);
+        assert_eq!(df.into_layers(), layers);
+    }
+
+    #[test]
+    fn from_layers_error_test() {
+        let layers = vec![vec![0u8], vec![0; 8]];
+        assert_eq!(
+            Octree::<u8, U3>::from_layers(layers).err(),
+            Some(LayerLenMismatch::LayerCount {
+                expected: 4,
+                got: 2
+            })
+        );
+
+        let layers = vec![vec![0u8], vec![0; 8], vec![0; 63], vec![0; 512]];
+        assert_eq!(
+            Octree::<u8, U3, DepthFirst>::from_layers(layers).err(),
+            Some(LayerLenMismatch::LayerLength {
+                layer: 2,
+                expected: 64,
+                got: 63
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "depth 4 is out of range for octree of depth 3")]
+    fn fill_layer_out_of_range_test() {
+        Octree::<u8, U3>::new(0).fill_layer(4, 1);
+    }
+}
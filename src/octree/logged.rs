@@ -0,0 +1,257 @@
+use std::ops::Deref;
+
+use typenum::Unsigned;
+
+use crate::{
+    layout::{BreathFirst, OctreeLayout},
+    octant::{Octant, OctantPath},
+    util::leaf_index_from_coords,
+};
+
+use super::Octree;
+
+/// Kind of a logged octree mutation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// A node and all of its descendants were set to the same value.
+    Flood,
+    /// A single leaf was written.
+    Leaf,
+    /// Leaves within the inclusive box between `min` and `max` coordinates
+    /// were written.
+    Region {
+        /// Minimum written coordinates.
+        min: [usize; 3],
+        /// Maximum written coordinates.
+        max: [usize; 3],
+    },
+}
+
+/// A record of a single [`LoggedOctree`] mutation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeEvent {
+    /// Path of the highest changed node.
+    pub path: OctantPath,
+    /// Depth of the highest changed node, with `0` being the root.
+    pub depth: usize,
+    /// Kind of the change.
+    pub kind: ChangeKind,
+}
+
+/// Octree wrapper that reports every mutation to a change listener.
+///
+/// Each mutating method emits exactly one [`ChangeEvent`] after the write
+/// completes. The wrapped octree can be read through [`Deref`], but not
+/// mutated directly.
+///
+/// The listener is required to be `Send` so that the octree can be moved to
+/// another thread, but as it's not required to be `Sync`, neither is this
+/// type.
+pub struct LoggedOctree<T: Clone, Depth: Unsigned, L: OctreeLayout = BreathFirst> {
+    inner: Octree<T, Depth, L>,
+    listener: Option<Box<dyn FnMut(ChangeEvent) + Send>>,
+}
+
+impl<T: Clone, Depth: Unsigned, L: OctreeLayout> LoggedOctree<T, Depth, L> {
+    /// Wraps the `inner` octree, without a change listener.
+    pub fn new(inner: Octree<T, Depth, L>) -> Self {
+        LoggedOctree {
+            inner,
+            listener: None,
+        }
+    }
+
+    /// Sets the `listener` called with every change, replacing the previous
+    /// one.
+    pub fn set_change_listener(&mut self, listener: impl FnMut(ChangeEvent) + Send + 'static) {
+        self.listener = Some(Box::new(listener));
+    }
+
+    /// Removes the change listener.
+    pub fn clear_change_listener(&mut self) {
+        self.listener = None;
+    }
+
+    /// Returns the wrapped octree.
+    pub fn into_inner(self) -> Octree<T, Depth, L> {
+        self.inner
+    }
+
+    fn emit(&mut self, path: OctantPath, kind: ChangeKind) {
+        if let Some(listener) = &mut self.listener {
+            let depth = path.len();
+            listener(ChangeEvent { path, depth, kind });
+        }
+    }
+
+    /// Sets the value of the node at `path` as well as its descendants.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `path` is longer than `Depth`.
+    pub fn set_value(&mut self, path: &[Octant], value: T) {
+        assert!(path.len() <= Depth::USIZE, "path is deeper than octree");
+        let layer = path.len();
+        let index = crate::util::path_to_index(path);
+        let offset = Octree::<T, Depth, L>::node_data_index(layer, index);
+        unsafe {
+            // SAFETY: offset, depth and index describe a node of this octree.
+            L::fill(
+                self.inner.data.as_mut_ptr().add(offset),
+                value,
+                Depth::USIZE,
+                Depth::USIZE - layer,
+                index,
+            );
+        }
+        self.emit(path.to_vec(), ChangeKind::Flood);
+    }
+
+    /// Sets the value of the leaf at the given coordinates.
+    ///
+    /// # Panics
+    ///
+    /// Panics if coordinates are outside of the `2^Depth` grid.
+    pub fn set_leaf(&mut self, x: usize, y: usize, z: usize, value: T) {
+        *self
+            .inner
+            .get_mut(x, y, z)
+            .expect("coordinates out of range") = value;
+        let index = leaf_index_from_coords(x, y, z);
+        let path = (0..Depth::USIZE)
+            .rev()
+            .map(|digit| Octant::ALL[(index >> (digit * 3)) & 0b111])
+            .collect();
+        self.emit(path, ChangeKind::Leaf);
+    }
+
+    /// Sets all leaves within the inclusive box between `min` and `max`
+    /// coordinates, as with [`Octree::fill_box`].
+    ///
+    /// The emitted event refers to the root node, with coordinates clamped to
+    /// the grid. No event is emitted if the box doesn't contain any leaves,
+    /// either because `min` is greater than `max` on some axis, or because
+    /// it's entirely outside of the grid.
+    pub fn set_region(&mut self, min: [usize; 3], max: [usize; 3], value: T) {
+        let last = (1 << Depth::USIZE) - 1;
+        let max = max.map(|it| it.min(last));
+        // Past this check `min` is at most `max`, so it's within the grid too.
+        if (0..3).any(|i| min[i] > max[i]) {
+            return;
+        }
+        self.inner.fill_box(min, max, value);
+        self.emit(OctantPath::new(), ChangeKind::Region { min, max });
+    }
+}
+
+impl<T: Clone, Depth: Unsigned, L: OctreeLayout> Deref for LoggedOctree<T, Depth, L> {
+    type Target = Octree<T, Depth, L>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<T: Clone, Depth: Unsigned, L: OctreeLayout> From<Octree<T, Depth, L>>
+    for LoggedOctree<T, Depth, L>
+{
+    fn from(value: Octree<T, Depth, L>) -> Self {
+        LoggedOctree::new(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use typenum::U2;
+
+    use super::*;
+    use crate::{layout::DepthFirst, octant::*};
+
+    #[test]
+    fn change_listener_test() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut test = LoggedOctree::new(Octree::<u8, U2, DepthFirst>::new(0));
+        test.set_leaf(0, 0, 0, 9);
+
+        let log = events.clone();
+        test.set_change_listener(move |event| log.lock().unwrap().push(event));
+        test.set_value(&[Octant::RUB], 1);
+        test.set_leaf(1, 2, 3, 2);
+        test.set_region([0, 0, 0], [1, 1, 9], 3);
+        test.set_value(&[], 4);
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                ChangeEvent {
+                    path: vec![Octant::RUB],
+                    depth: 1,
+                    kind: ChangeKind::Flood,
+                },
+                ChangeEvent {
+                    path: vec![Octant::LUB, Octant::RDB],
+                    depth: 2,
+                    kind: ChangeKind::Leaf,
+                },
+                ChangeEvent {
+                    path: vec![],
+                    depth: 0,
+                    kind: ChangeKind::Region {
+                        min: [0, 0, 0],
+                        max: [1, 1, 3],
+                    },
+                },
+                ChangeEvent {
+                    path: vec![],
+                    depth: 0,
+                    kind: ChangeKind::Flood,
+                },
+            ]
+        );
+        assert!(test.data.iter().all(|it| *it == 4));
+
+        // Empty boxes aren't written and don't emit events.
+        test.set_region([2, 0, 0], [1, 3, 3], 6);
+        test.set_region([4, 0, 0], [9, 9, 9], 6);
+        test.set_region([0, 9, 0], [3, 12, 3], 6);
+        assert_eq!(events.lock().unwrap().len(), 4);
+        assert!(test.data.iter().all(|it| *it == 4));
+
+        // Both corners are clamped.
+        test.set_region([3, 1, 2], [8, 1, 7], 6);
+        assert_eq!(
+            events.lock().unwrap()[4],
+            ChangeEvent {
+                path: vec![],
+                depth: 0,
+                kind: ChangeKind::Region {
+                    min: [3, 1, 2],
+                    max: [3, 1, 3],
+                },
+            }
+        );
+        assert_eq!(test.get(3, 1, 2), Some(&6));
+        assert_eq!(test.get(3, 1, 3), Some(&6));
+        assert_eq!(test.get(2, 1, 2), Some(&4));
+        test.set_value(&[], 4);
+
+        test.clear_change_listener();
+        test.set_leaf(3, 3, 3, 5);
+        assert_eq!(events.lock().unwrap().len(), 6);
+        assert_eq!(test.into_inner().get(3, 3, 3), Some(&5));
+    }
+
+    #[test]
+    fn logged_writes_test() {
+        let mut test = LoggedOctree::from(Octree::<u8, U2>::new(0));
+        test.set_value(&[Octant::RUB], 1);
+        assert_eq!(**test.child::<OctantRUB>().child::<OctantLDF>(), 1);
+        test.set_leaf(2, 2, 2, 7);
+        assert_eq!(**test.child::<OctantRUB>().child::<OctantLDF>(), 7);
+        assert_eq!(**test.child::<OctantRUB>(), 1);
+        test.set_region([0, 0, 0], [1, 1, 1], 3);
+        assert_eq!(**test.child::<OctantLDF>(), 3);
+    }
+}
@@ -0,0 +1,199 @@
+use typenum::Unsigned;
+
+use crate::{
+    layout::{BreathFirst, OctreeLayout},
+    octant::Octant,
+    util::leaf_index_from_coords,
+};
+
+use super::Octree;
+
+/// Octree storing the range of leaf values below every node.
+///
+/// Each node holds a `(min, max)` pair of values of leaves in its subtree,
+/// which allows answering range queries over regions without visiting every
+/// leaf and updating single leaves in `O(Depth)`.
+#[derive(Debug)]
+pub struct MinMaxOctree<T: Ord + Clone, Depth: Unsigned, L: OctreeLayout = BreathFirst> {
+    ranges: Octree<(T, T), Depth, L>,
+}
+
+impl<T: Ord + Clone, Depth: Unsigned, L: OctreeLayout> MinMaxOctree<T, Depth, L> {
+    /// Builds a min/max octree from leaf values of `leaves`.
+    ///
+    /// Values of interior nodes of `leaves` are ignored.
+    pub fn from_leaves(leaves: &Octree<T, Depth, L>) -> Self {
+        let mut result = MinMaxOctree {
+            ranges: leaves.map(|it| (it.clone(), it.clone())),
+        };
+        result.rebuild(0, Depth::USIZE, 0);
+        result
+    }
+
+    fn rebuild(&mut self, offset: usize, depth: usize, index: usize) {
+        if depth == 0 {
+            return;
+        }
+        for octant in Octant::ALL {
+            let child = Octree::<(T, T), Depth, L>::child_data_index(offset, octant, depth, index);
+            self.rebuild(child, depth - 1, index * 8 + octant.as_usize());
+        }
+        self.update_node(offset, depth, index);
+    }
+
+    /// Recomputes the range of a node from ranges of its children.
+    fn update_node(&mut self, offset: usize, depth: usize, index: usize) {
+        let data = &self.ranges.data;
+        let mut children = Octant::ALL.into_iter().map(|octant| {
+            &data[Octree::<(T, T), Depth, L>::child_data_index(offset, octant, depth, index)]
+        });
+        let (mut min, mut max) = children.next().unwrap().clone();
+        for (child_min, child_max) in children {
+            if *child_min < min {
+                min = child_min.clone();
+            }
+            if *child_max > max {
+                max = child_max.clone();
+            }
+        }
+        self.ranges.data[offset] = (min, max);
+    }
+
+    /// Returns the `(min, max)` range of all leaf values.
+    pub fn range(&self) -> &(T, T) {
+        &self.ranges.data[0]
+    }
+
+    /// Returns the `(min, max)` range of leaf values within the inclusive box
+    /// between `min` and `max` coordinates.
+    ///
+    /// Coordinates outside of the grid are clamped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the box contains no leaves.
+    pub fn range_of_region(&self, min: [usize; 3], max: [usize; 3]) -> (T, T) {
+        let last = (1 << Depth::USIZE) - 1;
+        let max = max.map(|it| it.min(last));
+        assert!((0..3).all(|i| min[i] <= max[i]), "empty region");
+        self.range_in(0, Depth::USIZE, 0, [0; 3], min, max)
+            .expect("non-empty region")
+    }
+
+    fn range_in(
+        &self,
+        offset: usize,
+        depth: usize,
+        index: usize,
+        origin: [usize; 3],
+        min: [usize; 3],
+        max: [usize; 3],
+    ) -> Option<(T, T)> {
+        let size = 1 << depth;
+        if (0..3).any(|i| origin[i] > max[i] || origin[i] + size - 1 < min[i]) {
+            return None;
+        }
+        if (0..3).all(|i| min[i] <= origin[i] && origin[i] + size - 1 <= max[i]) {
+            return Some(self.ranges.data[offset].clone());
+        }
+
+        let half = size / 2;
+        Octant::ALL
+            .into_iter()
+            .filter_map(|octant| {
+                let i = octant.as_usize();
+                let child_origin = [
+                    origin[0] + (i & 1) * half,
+                    origin[1] + ((i >> 1) & 1) * half,
+                    origin[2] + ((i >> 2) & 1) * half,
+                ];
+                let child =
+                    Octree::<(T, T), Depth, L>::child_data_index(offset, octant, depth, index);
+                self.range_in(child, depth - 1, index * 8 + i, child_origin, min, max)
+            })
+            .reduce(|(a_min, a_max), (b_min, b_max)| (a_min.min(b_min), a_max.max(b_max)))
+    }
+
+    /// Sets the value of the leaf at the given coordinates and updates ranges
+    /// of its ancestors.
+    ///
+    /// # Panics
+    ///
+    /// Panics if coordinates are outside of the `2^Depth` grid.
+    pub fn update_leaf(&mut self, x: usize, y: usize, z: usize, value: T) {
+        let side = 1 << Depth::USIZE;
+        assert!(x < side && y < side && z < side, "coordinates out of range");
+        let leaf = leaf_index_from_coords(x, y, z);
+        let offset = Octree::<(T, T), Depth, L>::leaf_data_index(leaf);
+        self.ranges.data[offset] = (value.clone(), value);
+
+        for layer in (0..Depth::USIZE).rev() {
+            let index = leaf >> ((Depth::USIZE - layer) * 3);
+            let offset = Octree::<(T, T), Depth, L>::node_data_index(layer, index);
+            self.update_node(offset, Depth::USIZE - layer, index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typenum::U3;
+
+    use super::*;
+    use crate::{layout::DepthFirst, test_support::xorshift};
+
+    fn brute_force<L: OctreeLayout>(
+        leaves: &Octree<u32, U3, L>,
+        min: [usize; 3],
+        max: [usize; 3],
+    ) -> (u32, u32) {
+        let mut result = (u32::MAX, u32::MIN);
+        for z in min[2]..=max[2] {
+            for y in min[1]..=max[1] {
+                for x in min[0]..=max[0] {
+                    let value = *leaves.get(x, y, z).unwrap();
+                    result = (result.0.min(value), result.1.max(value));
+                }
+            }
+        }
+        result
+    }
+
+    fn check_min_max<L: OctreeLayout>() {
+        let mut state = 0x1234_5678;
+        let mut leaves = Octree::<u32, U3, L>::new(0);
+        for z in 0..8 {
+            for y in 0..8 {
+                for x in 0..8 {
+                    *leaves.get_mut(x, y, z).unwrap() = xorshift(&mut state) % 1000;
+                }
+            }
+        }
+        let mut ranges = MinMaxOctree::from_leaves(&leaves);
+        assert_eq!(*ranges.range(), brute_force(&leaves, [0; 3], [7; 3]));
+
+        for round in 0..64 {
+            let a = [0; 3].map(|_| xorshift(&mut state) as usize % 8);
+            let b = [0; 3].map(|_| xorshift(&mut state) as usize % 8);
+            let min = [0, 1, 2].map(|i| a[i].min(b[i]));
+            let max = [0, 1, 2].map(|i| a[i].max(b[i]));
+            assert_eq!(
+                ranges.range_of_region(min, max),
+                brute_force(&leaves, min, max)
+            );
+
+            if round % 2 == 0 {
+                let value = xorshift(&mut state) % 1200;
+                ranges.update_leaf(a[0], a[1], a[2], value);
+                *leaves.get_mut(a[0], a[1], a[2]).unwrap() = value;
+            }
+        }
+        assert_eq!(*ranges.range(), brute_force(&leaves, [0; 3], [7; 3]));
+    }
+
+    #[test]
+    fn min_max_region_test() {
+        check_min_max::<BreathFirst>();
+        check_min_max::<DepthFirst>();
+    }
+}
@@ -0,0 +1,291 @@
+use std::marker::PhantomData;
+
+use typenum::Unsigned;
+
+use crate::{
+    layout::OctreeLayout,
+    octant::Octant,
+    util::{layer_length, node_offset_bf, subtree_length},
+};
+
+use super::Octree;
+
+/// Records which nodes of an octree have occupied leaves below them.
+///
+/// The pyramid stores a single bit for every node, set if any leaf in the
+/// subtree of the node satisfies the predicate it was built with. Bits are
+/// stored in [`BreathFirst`](crate::layout::BreathFirst) node order
+/// regardless of the layout of the source octree.
+///
+/// The pyramid isn't updated by octree mutations. It can be rebuilt with
+/// [`Octree::occupancy_pyramid`], or refreshed for a changed region with
+/// [`OccupancyPyramid::update_from`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OccupancyPyramid<Depth: Unsigned> {
+    bits: Vec<u8>,
+    _phantom: PhantomData<fn() -> Depth>,
+}
+
+impl<Depth: Unsigned> OccupancyPyramid<Depth> {
+    #[inline]
+    fn bit(&self, i: usize) -> bool {
+        self.bits[i / 8] & (1 << (i % 8)) != 0
+    }
+
+    #[inline]
+    fn set_bit(&mut self, i: usize, value: bool) {
+        if value {
+            self.bits[i / 8] |= 1 << (i % 8);
+        } else {
+            self.bits[i / 8] &= !(1 << (i % 8));
+        }
+    }
+
+    /// Returns whether any leaf below the node at the given `layer` and layer
+    /// `index` is occupied.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `layer` is greater than `Depth` or `index` is outside the
+    /// layer.
+    pub fn is_occupied(&self, layer: usize, index: usize) -> bool {
+        assert!(layer <= Depth::USIZE, "layer {} out of range", layer);
+        assert!(index < layer_length(layer), "index out of range");
+        self.bit(node_offset_bf(layer, index, Depth::USIZE))
+    }
+
+    /// Returns whether any leaf within the inclusive box between `min` and
+    /// `max` coordinates is occupied.
+    ///
+    /// Coordinates outside of the grid are clamped. Subtrees without occupied
+    /// leaves and subtrees fully inside the box are never descended into.
+    pub fn is_region_occupied(&self, min: [usize; 3], max: [usize; 3]) -> bool {
+        let last = (1 << Depth::USIZE) - 1;
+        let max = max.map(|it| it.min(last));
+        if (0..3).any(|i| min[i] > max[i]) {
+            return false;
+        }
+
+        let mut stack = vec![(0, 0, [0; 3])];
+        while let Some((layer, index, origin)) = stack.pop() {
+            if !self.bit(node_offset_bf(layer, index, Depth::USIZE)) {
+                continue;
+            }
+            let size = 1 << (Depth::USIZE - layer);
+            if (0..3).all(|axis| min[axis] <= origin[axis] && origin[axis] + size - 1 <= max[axis])
+            {
+                return true;
+            }
+            let half = size / 2;
+            for octant in Octant::ALL {
+                let i = octant.as_usize();
+                let child_origin = [0, 1, 2].map(|axis| origin[axis] + ((i >> axis) & 1) * half);
+                if (0..3).all(|axis| {
+                    child_origin[axis] <= max[axis] && child_origin[axis] + half > min[axis]
+                }) {
+                    stack.push((layer + 1, index * 8 + i, child_origin));
+                }
+            }
+        }
+        false
+    }
+
+    /// Returns the packed bits for uploading to the GPU.
+    ///
+    /// The bit of node `i` in breadth-first order (see
+    /// [`node_offset_bf`](crate::util::node_offset_bf)) is stored in bit
+    /// `i % 8` of byte `i / 8`. Bits past the last node are zero.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bits
+    }
+
+    /// Recomputes bits of leaves within the inclusive box between `min` and
+    /// `max` coordinates from `tree`, along with bits of all of their
+    /// ancestors.
+    ///
+    /// `occupied` should be the predicate the pyramid was built with. This
+    /// suffices if no leaves outside of the box changed since the pyramid was
+    /// last updated. Coordinates outside of the grid are clamped.
+    pub fn update_from<T: Clone, L: OctreeLayout>(
+        &mut self,
+        tree: &Octree<T, Depth, L>,
+        occupied: impl Fn(&T) -> bool,
+        min: [usize; 3],
+        max: [usize; 3],
+    ) {
+        let last = (1 << Depth::USIZE) - 1;
+        let max = max.map(|it| it.min(last));
+        if (0..3).any(|i| min[i] > max[i]) {
+            return;
+        }
+        self.update_in(tree, &occupied, 0, 0, 0, [0; 3], Some((min, max)));
+    }
+
+    /// Recomputes bits of the subtree at `offset` in the given `layer` which
+    /// intersect the `region` (or all of them, if it's `None`), and returns
+    /// whether the subtree is occupied.
+    #[allow(clippy::too_many_arguments)]
+    fn update_in<T: Clone, L: OctreeLayout>(
+        &mut self,
+        tree: &Octree<T, Depth, L>,
+        occupied: &impl Fn(&T) -> bool,
+        offset: usize,
+        layer: usize,
+        index: usize,
+        origin: [usize; 3],
+        region: Option<([usize; 3], [usize; 3])>,
+    ) -> bool {
+        let depth = Depth::USIZE - layer;
+        let result = if depth == 0 {
+            occupied(&tree.data[offset])
+        } else {
+            let half = 1 << (depth - 1);
+            let mut result = false;
+            for octant in Octant::ALL {
+                let i = octant.as_usize();
+                let child_index = index * 8 + i;
+                let child_origin = [0, 1, 2].map(|axis| origin[axis] + ((i >> axis) & 1) * half);
+                let intersects = region.is_none_or(|(min, max)| {
+                    (0..3).all(|axis| {
+                        child_origin[axis] <= max[axis] && child_origin[axis] + half > min[axis]
+                    })
+                });
+                result |= if intersects {
+                    let child =
+                        Octree::<T, Depth, L>::child_data_index(offset, octant, depth, index);
+                    self.update_in(
+                        tree,
+                        occupied,
+                        child,
+                        layer + 1,
+                        child_index,
+                        child_origin,
+                        region,
+                    )
+                } else {
+                    self.bit(node_offset_bf(layer + 1, child_index, Depth::USIZE))
+                };
+            }
+            result
+        };
+        self.set_bit(node_offset_bf(layer, index, Depth::USIZE), result);
+        result
+    }
+}
+
+impl<T: Clone, Depth: Unsigned, L: OctreeLayout> Octree<T, Depth, L> {
+    /// Returns an occupancy pyramid of this octree, with leaves for which
+    /// `occupied` holds marked as occupied.
+    ///
+    /// Values of interior nodes are ignored.
+    pub fn occupancy_pyramid(&self, occupied: impl Fn(&T) -> bool) -> OccupancyPyramid<Depth> {
+        let mut result = OccupancyPyramid {
+            bits: vec![0; subtree_length(Depth::USIZE).div_ceil(8)],
+            _phantom: PhantomData,
+        };
+        result.update_in(self, &occupied, 0, 0, 0, [0; 3], None);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typenum::U3;
+
+    use super::*;
+    use crate::{
+        layout::{BreathFirst, DepthFirst},
+        test_support::xorshift,
+        util::leaf_index_to_coords,
+    };
+
+    fn brute_force<L: OctreeLayout>(
+        tree: &Octree<u8, U3, L>,
+        min: [usize; 3],
+        max: [usize; 3],
+    ) -> bool {
+        (0..layer_length(3)).any(|i| {
+            let (x, y, z) = leaf_index_to_coords(i);
+            let at = [x, y, z];
+            (0..3).all(|axis| min[axis] <= at[axis] && at[axis] <= max[axis])
+                && *tree.get(x, y, z).unwrap() > 1
+        })
+    }
+
+    fn check_pyramid<L: OctreeLayout>(
+        tree: &Octree<u8, U3, L>,
+        pyramid: &OccupancyPyramid<U3>,
+        state: &mut u32,
+    ) {
+        for layer in 0..=3 {
+            let size = 1 << (3 - layer);
+            for index in 0..layer_length(layer) {
+                let (x, y, z) = leaf_index_to_coords(index);
+                let min = [x * size, y * size, z * size];
+                let max = min.map(|it| it + size - 1);
+                assert_eq!(
+                    pyramid.is_occupied(layer, index),
+                    brute_force(tree, min, max)
+                );
+            }
+        }
+        for _ in 0..200 {
+            let mut min = [0; 3];
+            let mut max = [0; 3];
+            for axis in 0..3 {
+                let a = xorshift(state) as usize % 10;
+                let b = xorshift(state) as usize % 10;
+                min[axis] = a.min(b);
+                max[axis] = a.max(b);
+            }
+            assert_eq!(
+                pyramid.is_region_occupied(min, max),
+                brute_force(tree, min, max)
+            );
+        }
+    }
+
+    fn check_occupancy<L: OctreeLayout>() {
+        let mut state = 0x2468_ace1u32;
+        let mut test = Octree::<u8, U3, L>::new(0);
+        let pyramid = test.occupancy_pyramid(|it| *it > 1);
+        assert!(pyramid.as_bytes().iter().all(|it| *it == 0));
+        assert!(!pyramid.is_region_occupied([0; 3], [7; 3]));
+
+        for _ in 0..20 {
+            let (x, y, z) = leaf_index_to_coords(xorshift(&mut state) as usize % layer_length(3));
+            *test.get_mut(x, y, z).unwrap() = xorshift(&mut state) as u8 % 4;
+        }
+        // Interior values don't affect occupancy.
+        test.set_value(9);
+        let mut pyramid = test.occupancy_pyramid(|it| *it > 1);
+        check_pyramid(&test, &pyramid, &mut state);
+        assert!(pyramid.is_occupied(0, 0));
+        assert_eq!(pyramid.as_bytes()[0] & 1, 1);
+
+        for _ in 0..20 {
+            let mut min = [0; 3];
+            let mut max = [0; 3];
+            for axis in 0..3 {
+                min[axis] = xorshift(&mut state) as usize % 8;
+                max[axis] = min[axis] + xorshift(&mut state) as usize % 3;
+            }
+            for i in 0..layer_length(3) {
+                let (x, y, z) = leaf_index_to_coords(i);
+                let at = [x, y, z];
+                if (0..3).all(|axis| min[axis] <= at[axis] && at[axis] <= max[axis]) {
+                    *test.get_mut(x, y, z).unwrap() = xorshift(&mut state) as u8 % 4;
+                }
+            }
+            pyramid.update_from(&test, |it| *it > 1, min, max);
+            assert_eq!(pyramid, test.occupancy_pyramid(|it| *it > 1));
+            check_pyramid(&test, &pyramid, &mut state);
+        }
+    }
+
+    #[test]
+    fn occupancy_test() {
+        check_occupancy::<BreathFirst>();
+        check_occupancy::<DepthFirst>();
+    }
+}
@@ -0,0 +1,167 @@
+use rayon::prelude::*;
+use typenum::Unsigned;
+
+use crate::{layout::OctreeLayout, octant::Octant, util::leaf_index_from_coords};
+
+use super::Octree;
+
+impl<T: Clone + Send + Sync, Depth: Unsigned, L: OctreeLayout> Octree<T, Depth, L> {
+    /// Parallel version of [`Octree::to_dense_grid`], filling each `z` slice
+    /// of the grid on a separate task.
+    pub fn par_to_dense_grid(&self) -> Vec<T> {
+        let side = Self::SIDE;
+        let data = &self.data;
+        let mut result = vec![self.data[0].clone(); side * side * side];
+        result
+            .par_chunks_mut(side * side)
+            .enumerate()
+            .for_each(|(z, slice)| {
+                for y in 0..side {
+                    for x in 0..side {
+                        let index = Self::leaf_data_index(leaf_index_from_coords(x, y, z));
+                        slice[y * side + x] = data[index].clone();
+                    }
+                }
+            });
+        result
+    }
+
+    /// Parallel version of [`Octree::diff`], comparing each of the top-level
+    /// subtrees on a separate task.
+    ///
+    /// The result is the same as the one returned by [`Octree::diff`].
+    pub fn par_diff(&self, other: &Self) -> Vec<(usize, usize)>
+    where
+        T: PartialEq,
+    {
+        let mut result = Vec::new();
+        if self.data[0] != other.data[0] {
+            result.push((0, 0));
+        }
+        if Depth::USIZE == 0 {
+            return result;
+        }
+        let subtrees: Vec<_> = Octant::ALL
+            .par_iter()
+            .map(|octant| {
+                let mut changes = Vec::new();
+                let child = Self::child_data_index(0, *octant, Depth::USIZE, 0);
+                self.diff_in(
+                    other,
+                    child,
+                    Depth::USIZE - 1,
+                    octant.as_usize(),
+                    &mut changes,
+                );
+                changes
+            })
+            .collect();
+        result.extend(subtrees.into_iter().flatten());
+        result
+    }
+
+    /// Parallel version of [`Octree::map_in_place`].
+    ///
+    /// Values aren't visited in any particular order.
+    pub fn par_map_in_place<F: Fn(&mut T) + Sync>(&mut self, f: F) {
+        self.data.par_iter_mut().for_each(&f);
+    }
+
+    /// Folds all node values in parallel.
+    ///
+    /// Every task starts with an accumulator created by `init_fn` and folds
+    /// values into it with `f`, after which partial results are merged with
+    /// `combine`. Values aren't visited in any particular order.
+    pub fn par_fold<A: Send, F: Fn(A, &T) -> A + Sync, B: Fn(A, A) -> A + Sync>(
+        &self,
+        init_fn: impl Fn() -> A + Sync,
+        f: F,
+        combine: B,
+    ) -> A {
+        self.data
+            .par_iter()
+            .fold(&init_fn, &f)
+            .reduce(&init_fn, &combine)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typenum::U4;
+
+    use super::*;
+    use crate::{
+        layout::{BreathFirst, DepthFirst},
+        test_support::xorshift,
+    };
+
+    fn check_par_to_dense_grid<L: OctreeLayout>() {
+        let mut test = Octree::<u32, U4, L>::new(0);
+        for i in 0..16 * 16 * 16 {
+            let (x, y, z) = crate::util::leaf_index_to_coords(i);
+            *test.get_mut(x, y, z).unwrap() = (i as u32).wrapping_mul(2654435761);
+        }
+        assert_eq!(test.par_to_dense_grid(), test.to_dense_grid());
+    }
+
+    #[test]
+    fn par_fold_test() {
+        let test = Octree::<u32, U4>::new(3);
+        let count = test.par_fold(|| 0usize, |a, _| a + 1, |a, b| a + b);
+        assert_eq!(count, Octree::<u32, U4>::node_count());
+
+        let mut test = Octree::<u32, U4, DepthFirst>::new(1);
+        test.child_mut::<crate::octant::OctantRUB>().set_value(2);
+        let sum = test.par_fold(|| 0u64, |a, v| a + *v as u64, |a, b| a + b);
+        assert_eq!(sum, 4681 + 585);
+    }
+
+    fn check_par_diff<L: OctreeLayout>() {
+        let mut state = 0x9e37_79b9u32;
+        let mut next = || xorshift(&mut state);
+
+        let a = Octree::<u32, U4, L>::new(0);
+        assert!(a.par_diff(&a).is_empty());
+        for _ in 0..8 {
+            let mut b = Octree::<u32, U4, L>::new(0);
+            for _ in 0..next() % 64 {
+                let index = next() as usize % b.data.len();
+                b.data[index] = next() % 3;
+            }
+            assert_eq!(a.par_diff(&b), a.diff(&b));
+            assert_eq!(b.par_diff(&a), b.diff(&a));
+        }
+    }
+
+    #[test]
+    fn par_diff_test() {
+        check_par_diff::<BreathFirst>();
+        check_par_diff::<DepthFirst>();
+        let root = Octree::<u8, typenum::U0>::new(1);
+        assert_eq!(root.par_diff(&Octree::new(2)), vec![(0, 0)]);
+    }
+
+    fn check_par_map_in_place<L: OctreeLayout>() {
+        let mut parallel = Octree::<f32, U4, L>::new(0.0);
+        for (i, value) in parallel.data.iter_mut().enumerate() {
+            *value = (i as u32).wrapping_mul(2654435761) as f32 / u32::MAX as f32 * 4.0 - 2.0;
+        }
+        let mut serial = parallel.map(|it| *it);
+        let clamp = |it: &mut f32| *it = it.clamp(-1.0, 1.0) * 0.5 + 0.5;
+        serial.map_in_place(clamp);
+        parallel.par_map_in_place(clamp);
+        assert_eq!(parallel.data, serial.data);
+    }
+
+    #[test]
+    fn par_map_in_place_test() {
+        check_par_map_in_place::<BreathFirst>();
+        check_par_map_in_place::<DepthFirst>();
+    }
+
+    #[test]
+    fn par_to_dense_grid_test() {
+        check_par_to_dense_grid::<BreathFirst>();
+        check_par_to_dense_grid::<DepthFirst>();
+    }
+}
@@ -0,0 +1,162 @@
+use typenum::Unsigned;
+
+use crate::{
+    layout::OctreeLayout,
+    octant::{visit_order, Octant},
+};
+
+use super::{OctreeNode, UniformMask};
+
+/// Returns the ray parameter at which the ray enters the cube at `min` with
+/// the given `extent`, or `None` if the ray misses it.
+///
+/// The parameter is clamped to `0` if `origin` is inside of the cube.
+fn intersect_cube(origin: [f32; 3], dir: [f32; 3], min: [f32; 3], extent: f32) -> Option<f32> {
+    let mut enter = f32::NEG_INFINITY;
+    let mut exit = f32::INFINITY;
+    for axis in 0..3 {
+        let max = min[axis] + extent;
+        if dir[axis] == 0.0 {
+            if origin[axis] < min[axis] || origin[axis] > max {
+                return None;
+            }
+            continue;
+        }
+        let a = (min[axis] - origin[axis]) / dir[axis];
+        let b = (max - origin[axis]) / dir[axis];
+        enter = enter.max(a.min(b));
+        exit = exit.min(a.max(b));
+    }
+    (enter <= exit && exit >= 0.0).then_some(enter.max(0.0))
+}
+
+impl<T: Clone, S: Unsigned, L: OctreeLayout, D: Unsigned, I: Unsigned> OctreeNode<T, S, L, D, I> {
+    /// Casts a ray through this subtree and returns the first leaf for which
+    /// `solid` holds, along with its coordinates and the point where the ray
+    /// enters it.
+    ///
+    /// The subtree is treated as a unit cube, so `origin` and the returned
+    /// entry point are expressed in its space, while leaf coordinates are
+    /// relative to this node. Children are visited front-to-back and
+    /// subtrees the ray misses are skipped, so the search stops at the first
+    /// hit. A ray starting inside a solid leaf returns `origin` as the entry
+    /// point.
+    ///
+    /// With a `mask` built for the octree, subtrees it marks as uniform are
+    /// skipped if `solid` doesn't hold for their root value.
+    pub fn raycast_first<F: Fn(&T) -> bool>(
+        &self,
+        origin: [f32; 3],
+        dir: [f32; 3],
+        solid: F,
+        mask: Option<&UniformMask<S>>,
+    ) -> Option<(&T, [usize; 3], [f32; 3])> {
+        #[allow(clippy::too_many_arguments)]
+        unsafe fn cast<'a, T, S: Unsigned, L: OctreeLayout>(
+            base: *const T,
+            size: usize,
+            depth: usize,
+            index: usize,
+            min: [f32; 3],
+            extent: f32,
+            coords: [usize; 3],
+            ray: ([f32; 3], [f32; 3], [bool; 3]),
+            solid: &impl Fn(&T) -> bool,
+            mask: Option<&UniformMask<S>>,
+        ) -> Option<(&'a T, [usize; 3], [f32; 3])> {
+            let (origin, dir, signs) = ray;
+            let t = intersect_cube(origin, dir, min, extent)?;
+            if depth == 0 {
+                let value = &*base;
+                let entry = [0, 1, 2].map(|axis| origin[axis] + dir[axis] * t);
+                return solid(value).then_some((value, coords, entry));
+            }
+            // Leaves of a solid uniform subtree are all solid, so the mask
+            // isn't needed below it.
+            let mask = match mask {
+                Some(it) if it.is_uniform(size - depth, index) => {
+                    if !solid(&*base) {
+                        return None;
+                    }
+                    None
+                }
+                it => it,
+            };
+
+            let half = extent / 2.0;
+            visit_order(signs).into_iter().find_map(|octant: Octant| {
+                let i = octant.as_usize();
+                let bits = [i & 1, (i >> 1) & 1, (i >> 2) & 1];
+                let child = base.add(L::child_offset::<T>(octant, size, depth, index));
+                cast::<T, S, L>(
+                    child,
+                    size,
+                    depth - 1,
+                    index * 8 + i,
+                    [0, 1, 2].map(|axis| min[axis] + bits[axis] as f32 * half),
+                    half,
+                    [0, 1, 2].map(|axis| coords[axis] * 2 + bits[axis]),
+                    ray,
+                    solid,
+                    mask,
+                )
+            })
+        }
+
+        let signs = dir.map(|it| it < 0.0);
+        unsafe {
+            cast::<T, S, L>(
+                self.value_ptr(),
+                S::USIZE,
+                D::USIZE,
+                I::USIZE,
+                [0.0; 3],
+                1.0,
+                [0; 3],
+                (origin, dir, signs),
+                &solid,
+                mask,
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typenum::U2;
+
+    use crate::{layout::DepthFirst, Octree};
+
+    #[test]
+    fn raycast_first_test() {
+        let mut test = Octree::<u8, U2>::new(0);
+        *test.get_mut(2, 1, 3).unwrap() = 1;
+
+        let hit = test.raycast_first([-1.0, 0.375, 0.875], [1.0, 0.0, 0.0], |it| *it == 1, None);
+        assert_eq!(hit, Some((&1, [2, 1, 3], [0.5, 0.375, 0.875])));
+
+        let hit = test.raycast_first([0.625, 2.0, 0.875], [0.0, -1.0, 0.0], |it| *it == 1, None);
+        assert_eq!(hit, Some((&1, [2, 1, 3], [0.625, 0.5, 0.875])));
+
+        let hit = test.raycast_first([0.6, 0.3, 0.8], [0.0, 0.0, 1.0], |it| *it == 1, None);
+        assert_eq!(hit, Some((&1, [2, 1, 3], [0.6, 0.3, 0.8])));
+
+        let miss = test.raycast_first([-1.0, 0.375, 0.875], [-1.0, 0.0, 0.0], |it| *it == 1, None);
+        assert_eq!(miss, None);
+        let miss = test.raycast_first([-1.0, 0.125, 0.875], [1.0, 0.0, 0.0], |it| *it == 1, None);
+        assert_eq!(miss, None);
+    }
+
+    #[test]
+    fn raycast_first_front_to_back_test() {
+        let mut test = Octree::<u8, U2, DepthFirst>::new(0);
+        *test.get_mut(0, 0, 0).unwrap() = 1;
+        *test.get_mut(3, 3, 3).unwrap() = 2;
+
+        let hit = test.raycast_first([-0.5, -0.5, -0.5], [1.0, 1.0, 1.0], |it| *it != 0, None);
+        assert_eq!(hit.map(|it| (*it.0, it.1)), Some((1, [0, 0, 0])));
+        let hit = test.raycast_first([1.5, 1.5, 1.5], [-1.0, -1.0, -1.0], |it| *it != 0, None);
+        assert_eq!(hit.map(|it| (*it.0, it.1)), Some((2, [3, 3, 3])));
+        assert_eq!(hit.unwrap().2, [1.0, 1.0, 1.0]);
+    }
+}
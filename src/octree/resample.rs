@@ -0,0 +1,263 @@
+use typenum::{IsLessOrEqual, LeEq, Same, True, Unsigned};
+
+use crate::{error::OutOfBounds, layout::OctreeLayout, util::leaf_index_from_coords};
+
+use super::Octree;
+
+/// Filter used to combine source leaves when downsampling.
+pub enum Filter<'a, T> {
+    /// Uses the source leaf with the smallest coordinates in each block.
+    Nearest,
+    /// Reduces all source leaves of a block, ordered with `x` changing the
+    /// fastest and `z` the slowest, into a single value.
+    Reduce(&'a dyn Fn(&[T]) -> T),
+}
+
+impl<T: Clone, Depth: Unsigned, L: OctreeLayout> Octree<T, Depth, L> {
+    /// Sets leaves of this octree to values sampled from the region of `src`
+    /// starting at `src_origin`.
+    ///
+    /// A positive `scale_log2` upsamples the region, with each source leaf
+    /// replicated into a `2^scale_log2` cubed block of leaves. A negative one
+    /// downsamples it, with each `2^-scale_log2` cubed block of source leaves
+    /// combined using the `filter`, which is ignored otherwise. Values of
+    /// interior nodes aren't updated.
+    ///
+    /// Returns an error without changing any values if the sampled region
+    /// extends outside of `src`. Corner coordinates reported by the error
+    /// saturate at `usize::MAX` for scales too large to represent.
+    pub fn resample_from<D2: Unsigned, L2: OctreeLayout>(
+        &mut self,
+        src: &Octree<T, D2, L2>,
+        src_origin: (usize, usize, usize),
+        scale_log2: i8,
+        filter: Filter<T>,
+    ) -> Result<(), OutOfBounds> {
+        let side = 1usize << Depth::USIZE;
+        let src_side = 1usize << D2::USIZE;
+        let origin = [src_origin.0, src_origin.1, src_origin.2];
+        let up = scale_log2.max(0) as u32;
+        let down = scale_log2.min(0).unsigned_abs() as u32;
+        // Blocks that don't fit into `usize` can't fit into `src` either, so
+        // overflowing extents saturate and are reported as out of bounds.
+        let block = 1usize.checked_shl(down).unwrap_or(usize::MAX);
+        let extent = (side - 1)
+            .checked_shr(up)
+            .unwrap_or(0)
+            .checked_mul(block)
+            .and_then(|it| it.checked_add(block - 1))
+            .unwrap_or(usize::MAX);
+        let last = origin.map(|it| it.saturating_add(extent));
+        if last.iter().any(|it| *it >= src_side) {
+            return Err(OutOfBounds {
+                coords: last,
+                side: src_side,
+            });
+        }
+
+        let source = |[x, y, z]: [usize; 3]| {
+            &src.data[Octree::<T, D2, L2>::leaf_data_index(leaf_index_from_coords(x, y, z))]
+        };
+        let mut values = Vec::with_capacity(block * block * block);
+        for index in 0..crate::util::layer_length(Depth::USIZE) {
+            let (x, y, z) = crate::util::leaf_index_to_coords(index);
+            let base = [x, y, z].map(|it| it.checked_shr(up).unwrap_or(0) * block);
+            let base = [0, 1, 2].map(|i| origin[i] + base[i]);
+
+            let value = match filter {
+                Filter::Reduce(reduce) if down > 0 => {
+                    values.clear();
+                    for dz in 0..block {
+                        for dy in 0..block {
+                            for dx in 0..block {
+                                values.push(
+                                    source([base[0] + dx, base[1] + dy, base[2] + dz]).clone(),
+                                );
+                            }
+                        }
+                    }
+                    reduce(&values)
+                }
+                _ => source(base).clone(),
+            };
+            self.data[Self::leaf_data_index(index)] = value;
+        }
+        Ok(())
+    }
+
+    /// Returns an octree of `NewDepth`, with each leaf of this octree
+    /// replicated into the `8^(NewDepth - Depth)` leaves below it.
+    ///
+    /// Values of interior nodes are kept, while new interior nodes below the
+    /// original leaves take their value as well.
+    pub fn upsample<NewDepth>(&self) -> Octree<T, NewDepth, L>
+    where
+        NewDepth: Unsigned,
+        Depth: IsLessOrEqual<NewDepth>,
+        LeEq<Depth, NewDepth>: Same<True>,
+    {
+        let mut result = Octree::<T, NewDepth, L>::new(self.data[0].clone());
+        Octree::<T, NewDepth, L>::for_each_node(&mut |offset, layer, index| {
+            let source = if layer <= Depth::USIZE {
+                Self::node_data_index(layer, index)
+            } else {
+                Self::leaf_data_index(index >> (3 * (layer - Depth::USIZE)))
+            };
+            result.data[offset] = self.data[source].clone();
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typenum::{U1, U2, U3};
+
+    use super::*;
+    use crate::layout::{BreathFirst, DepthFirst};
+
+    fn majority(values: &[u8]) -> u8 {
+        (values.iter().filter(|it| **it == 1).count() * 2 > values.len()) as u8
+    }
+
+    fn check_downsample<L: OctreeLayout>() {
+        let mut src = Octree::<u8, U3, L>::new(0);
+        // Region starting at (4, 0, 4); its (1, 0, 0) block is mostly solid.
+        src.fill_box([6, 0, 4], [7, 1, 5], 1);
+        *src.get_mut(6, 0, 4).unwrap() = 0;
+        *src.get_mut(6, 1, 4).unwrap() = 0;
+        // Its (0, 1, 1) block is solid in less than a half.
+        src.fill_box([4, 2, 6], [5, 2, 7], 1);
+
+        let mut dst = Octree::<u8, U1, BreathFirst>::new(9);
+        dst.resample_from(&src, (4, 0, 4), -1, Filter::Reduce(&majority))
+            .unwrap();
+        assert_eq!(dst.get(1, 0, 0), Some(&1));
+        assert_eq!(dst.get(0, 1, 1), Some(&0));
+        assert_eq!(dst.data[1..].iter().filter(|it| **it == 1).count(), 1);
+        assert_eq!(**dst, 9);
+
+        dst.resample_from(&src, (4, 0, 4), -1, Filter::Nearest)
+            .unwrap();
+        assert_eq!(dst.get(1, 0, 0), Some(&0));
+        assert_eq!(dst.get(0, 1, 1), Some(&1));
+    }
+
+    fn check_upsample<L: OctreeLayout>() {
+        let mut src = Octree::<u8, U2, L>::new(0);
+        for (i, value) in src.data.iter_mut().enumerate() {
+            *value = i as u8;
+        }
+
+        let mut dst = Octree::<u8, U3, DepthFirst>::new(0);
+        dst.resample_from(&src, (0, 0, 0), 1, Filter::Nearest)
+            .unwrap();
+        for z in 0..8 {
+            for y in 0..8 {
+                for x in 0..8 {
+                    assert_eq!(dst.get(x, y, z), src.get(x / 2, y / 2, z / 2));
+                }
+            }
+        }
+
+        dst.resample_from(&src, (2, 1, 2), 2, Filter::Nearest)
+            .unwrap();
+        for z in 0..8 {
+            for y in 0..8 {
+                for x in 0..8 {
+                    assert_eq!(dst.get(x, y, z), src.get(2 + x / 4, 1 + y / 4, 2 + z / 4));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn resample_test() {
+        check_downsample::<BreathFirst>();
+        check_downsample::<DepthFirst>();
+        check_upsample::<BreathFirst>();
+        check_upsample::<DepthFirst>();
+    }
+
+    fn check_scale_up<L: OctreeLayout>() {
+        use crate::octant::{Octant, OctantRUB};
+
+        let mut src = Octree::<u8, U1, L>::new(100);
+        for octant in Octant::ALL {
+            src.data[Octree::<u8, U1, L>::leaf_data_index(octant.as_usize())] =
+                octant.as_usize() as u8;
+        }
+        let dst: Octree<u8, U2, L> = src.upsample();
+        assert_eq!(**dst, 100);
+        for z in 0..4 {
+            for y in 0..4 {
+                for x in 0..4 {
+                    assert_eq!(dst.get(x, y, z), src.get(x / 2, y / 2, z / 2));
+                }
+            }
+        }
+        let rub = dst.child::<OctantRUB>();
+        assert_eq!(rub.count_in_subtree(|v| *v == 7), 8);
+        assert_eq!(**rub, 7);
+
+        let same: Octree<u8, U1, L> = src.upsample();
+        assert_eq!(same.data, src.data);
+        let deep: Octree<u8, U3, L> = src.upsample();
+        assert_eq!(deep.get(7, 0, 5), Some(&5));
+        assert_eq!(**deep.child::<OctantRUB>().child::<OctantRUB>(), 7);
+    }
+
+    #[test]
+    fn upsample_test() {
+        check_scale_up::<BreathFirst>();
+        check_scale_up::<DepthFirst>();
+    }
+
+    #[test]
+    fn resample_bounds_test() {
+        let src = Octree::<u8, U2>::new(1);
+        let mut dst = Octree::<u8, U2>::new(0);
+
+        assert_eq!(
+            dst.resample_from(&src, (1, 0, 0), 0, Filter::Nearest),
+            Err(OutOfBounds {
+                coords: [4, 3, 3],
+                side: 4
+            })
+        );
+        assert_eq!(
+            dst.resample_from(&src, (0, 0, 0), -1, Filter::Nearest),
+            Err(OutOfBounds {
+                coords: [7, 7, 7],
+                side: 4
+            })
+        );
+        // Extents past `usize` are saturated.
+        for scale_log2 in [-63, -64, -100, i8::MIN] {
+            assert_eq!(
+                dst.resample_from(&src, (1, 0, 0), scale_log2, Filter::Nearest),
+                Err(OutOfBounds {
+                    coords: [usize::MAX; 3],
+                    side: 4
+                })
+            );
+        }
+        assert!(dst.data.iter().all(|it| *it == 0));
+
+        // Upsampling a single source leaf into the whole grid.
+        assert_eq!(
+            dst.resample_from(&src, (3, 3, 3), 2, Filter::Nearest),
+            Ok(())
+        );
+        assert!(dst.data[9..].iter().all(|it| *it == 1));
+
+        let mut dst = Octree::<u8, U1>::new(0);
+        assert_eq!(
+            dst.resample_from(&src, (3, 4, 3), 1, Filter::Nearest),
+            Err(OutOfBounds {
+                coords: [3, 4, 3],
+                side: 4
+            })
+        );
+    }
+}
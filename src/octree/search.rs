@@ -0,0 +1,407 @@
+use typenum::Unsigned;
+
+use crate::{
+    error::ZeroStep,
+    layout::OctreeLayout,
+    octant::Octant,
+    util::{leaf_index_from_coords, leaf_index_to_coords},
+};
+
+use super::{Octree, OctreeNode, UniformMask};
+
+impl<T: Clone, S: Unsigned, L: OctreeLayout, D: Unsigned, I: Unsigned> OctreeNode<T, S, L, D, I> {
+    /// Returns whether `gate` holds for this node and every node along the
+    /// `path` from it.
+    ///
+    /// Descent stops at the first node for which `gate` doesn't hold. Paths
+    /// longer than the depth of this subtree can't be traversed, so `false`
+    /// is returned for them.
+    pub fn can_descend<F: Fn(&T) -> bool>(&self, path: &[Octant], gate: F) -> bool {
+        if path.len() > D::USIZE {
+            return false;
+        }
+        let mut node = self.value_ptr();
+        let mut index = I::USIZE;
+        if !gate(unsafe { &*node }) {
+            return false;
+        }
+        for (layer, octant) in path.iter().enumerate() {
+            let value = unsafe {
+                // SAFETY: path is no deeper than this subtree.
+                node = node.add(L::child_offset::<T>(
+                    *octant,
+                    S::USIZE,
+                    D::USIZE - layer,
+                    index,
+                ));
+                &*node
+            };
+            index = index * 8 + octant.as_usize();
+            if !gate(value) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl<T: Clone, Depth: Unsigned, L: OctreeLayout> Octree<T, Depth, L> {
+    /// Returns coordinates and value of the first leaf for which `pred`
+    /// holds.
+    ///
+    /// Leaves are visited depth-first in octant order, so the returned leaf is
+    /// always the matching one with the lowest morton index. Interior nodes
+    /// for which `prune` returns `true` are skipped along with their whole
+    /// subtree.
+    pub fn find_leaf_where(
+        &self,
+        pred: impl Fn(&T) -> bool,
+        prune: Option<impl Fn(&T) -> bool>,
+    ) -> Option<((usize, usize, usize), &T)> {
+        self.find_map_leaf(|value| pred(value).then_some(value), prune)
+    }
+
+    /// Returns coordinates of the first leaf for which `f` returns `Some`,
+    /// along with the returned value.
+    ///
+    /// Leaves are visited in the same order as in
+    /// [`Octree::find_leaf_where`], and `prune` is consulted in the same way.
+    pub fn find_map_leaf<'a, R>(
+        &'a self,
+        f: impl Fn(&'a T) -> Option<R>,
+        prune: Option<impl Fn(&T) -> bool>,
+    ) -> Option<((usize, usize, usize), R)> {
+        self.find_map_in(0, Depth::USIZE, 0, &f, prune.as_ref())
+            .map(|(index, result)| (leaf_index_to_coords(index), result))
+    }
+
+    /// Returns values of nodes along the `path` from the root, including both
+    /// the root and the node at the end of the `path`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `path` is longer than `Depth`.
+    pub fn path_values(&self, path: &[Octant]) -> Vec<T> {
+        assert!(path.len() <= Depth::USIZE, "path is deeper than octree");
+        let mut result = Vec::with_capacity(path.len() + 1);
+        let mut offset = 0;
+        let mut index = 0;
+        result.push(self.data[offset].clone());
+        for (layer, octant) in path.iter().enumerate() {
+            offset = Self::child_data_index(offset, *octant, Depth::USIZE - layer, index);
+            index = index * 8 + octant.as_usize();
+            result.push(self.data[offset].clone());
+        }
+        result
+    }
+
+    /// Returns coordinates and values of all leaves within the inclusive box
+    /// between `min` and `max` coordinates.
+    ///
+    /// Leaves are yielded in morton order, and coordinates outside of the
+    /// grid are clamped. With a `mask` built for this octree, leaves of
+    /// subtrees it marks as uniform are yielded with a reference to the
+    /// subtree root value, without reading the leaves themselves.
+    pub fn leaves_in_region<'a>(
+        &'a self,
+        min: [usize; 3],
+        max: [usize; 3],
+        mask: Option<&'a UniformMask<Depth>>,
+    ) -> impl Iterator<Item = ((usize, usize, usize), &'a T)> {
+        let last = (1 << Depth::USIZE) - 1;
+        let max = max.map(|it| it.min(last));
+        let mut stack = Vec::with_capacity(Depth::USIZE * 7 + 1);
+        if (0..3).all(|i| min[i] <= max[i]) {
+            // Offset, layer, index and origin of a node, along with the value
+            // of its uniform ancestor, if any.
+            stack.push((0, 0, 0, [0; 3], None::<&T>));
+        }
+
+        std::iter::from_fn(move || loop {
+            let (offset, layer, index, origin, uniform) = stack.pop()?;
+            let depth = Depth::USIZE - layer;
+            if depth == 0 {
+                let value = uniform.unwrap_or(&self.data[offset]);
+                return Some(((origin[0], origin[1], origin[2]), value));
+            }
+            let uniform = uniform.or_else(|| {
+                mask.filter(|it| it.is_uniform(layer, index))
+                    .map(|_| &self.data[offset])
+            });
+
+            let half = 1 << (depth - 1);
+            for octant in Octant::ALL.into_iter().rev() {
+                let i = octant.as_usize();
+                let child_origin = [0, 1, 2].map(|axis| origin[axis] + ((i >> axis) & 1) * half);
+                if (0..3).any(|axis| {
+                    child_origin[axis] > max[axis] || child_origin[axis] + half - 1 < min[axis]
+                }) {
+                    continue;
+                }
+                let child = Self::child_data_index(offset, octant, depth, index);
+                stack.push((child, layer + 1, index * 8 + i, child_origin, uniform));
+            }
+        })
+    }
+
+    /// Returns coordinates and values of leaves within the inclusive box
+    /// between `min` and `max` coordinates, whose coordinates are offset from
+    /// `min` by a multiple of `step` along every axis.
+    ///
+    /// Leaves are yielded with `x` changing the fastest, and coordinates
+    /// outside of the grid are clamped. Only yielded leaves are visited, so a
+    /// large `step` is cheaper than filtering [`Octree::leaves_in_region`].
+    /// Steps larger than the region yield only the `min` corner.
+    ///
+    /// Returns an error if any of the steps is zero.
+    pub fn leaves_in_region_strided(
+        &self,
+        min: [usize; 3],
+        max: [usize; 3],
+        step: (usize, usize, usize),
+    ) -> Result<impl Iterator<Item = ((usize, usize, usize), &T)>, ZeroStep> {
+        let step = [step.0, step.1, step.2];
+        if let Some(axis) = step.iter().position(|it| *it == 0) {
+            return Err(ZeroStep { axis });
+        }
+        let last = (1 << Depth::USIZE) - 1;
+        let max = max.map(|it| it.min(last));
+        let empty = (0..3).any(|i| min[i] > max[i]);
+        let axis = move |i: usize| (min[i]..=max[i]).step_by(step[i]);
+
+        Ok((!empty)
+            .then(|| {
+                axis(2).flat_map(move |z| {
+                    axis(1).flat_map(move |y| {
+                        axis(0).map(move |x| {
+                            let value =
+                                &self.data[Self::leaf_data_index(leaf_index_from_coords(x, y, z))];
+                            ((x, y, z), value)
+                        })
+                    })
+                })
+            })
+            .into_iter()
+            .flatten())
+    }
+
+    fn find_map_in<'a, R>(
+        &'a self,
+        offset: usize,
+        depth: usize,
+        index: usize,
+        f: &impl Fn(&'a T) -> Option<R>,
+        prune: Option<&impl Fn(&T) -> bool>,
+    ) -> Option<(usize, R)> {
+        let value = &self.data[offset];
+        if depth == 0 {
+            return f(value).map(|result| (index, result));
+        }
+        if prune.is_some_and(|prune| prune(value)) {
+            return None;
+        }
+
+        Octant::ALL.into_iter().find_map(|octant| {
+            let child = Self::child_data_index(offset, octant, depth, index);
+            self.find_map_in(child, depth - 1, index * 8 + octant.as_usize(), f, prune)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typenum::{U2, U3};
+
+    use super::*;
+    use crate::{layout::DepthFirst, test_support::xorshift, util::layer_length};
+
+    const NO_PRUNE: Option<fn(&u32) -> bool> = None;
+
+    fn check_path_values<L: OctreeLayout>() {
+        let mut test = Octree::<usize, U3, L>::new(0);
+        Octree::<usize, U3, L>::for_each_node(&mut |offset, layer, index| {
+            test.data[offset] = layer * 1000 + index;
+        });
+
+        let path = [Octant::RDB, Octant::LUF, Octant::RUB];
+        let values = test.path_values(&path);
+        assert_eq!(values.len(), path.len() + 1);
+        assert_eq!(values, vec![0, 1005, 2000 + 0o52, 3000 + 0o527]);
+        assert_eq!(values.last(), test.get_path(&path));
+        assert_eq!(test.get_path(&path[..1]), Some(&1005));
+        assert_eq!(test.path_values(&[]), vec![0]);
+        assert_eq!(test.get_path(&[Octant::LDF; 4]), None);
+    }
+
+    fn check_strided<L: OctreeLayout>() {
+        let mut state = 0x1357_9bdf;
+        let mut test = Octree::<u32, U3, L>::new(0);
+        for i in 0..layer_length(3) {
+            test.data[Octree::<u32, U3, L>::leaf_data_index(i)] = xorshift(&mut state) % 100;
+        }
+
+        for _ in 0..64 {
+            let mut min = [0; 3];
+            let mut max = [0; 3];
+            for axis in 0..3 {
+                min[axis] = xorshift(&mut state) as usize % 9;
+                max[axis] = min[axis] + xorshift(&mut state) as usize % 6;
+            }
+            let step = [0; 3].map(|_| 1 + xorshift(&mut state) as usize % 5);
+
+            let mut strided: Vec<_> = test
+                .leaves_in_region_strided(min, max, (step[0], step[1], step[2]))
+                .unwrap()
+                .collect();
+            strided.sort();
+            let mut dense: Vec<_> = test
+                .leaves_in_region(min, max, None)
+                .filter(|((x, y, z), _)| {
+                    let at = [*x, *y, *z];
+                    (0..3).all(|axis| (at[axis] - min[axis]) % step[axis] == 0)
+                })
+                .collect();
+            dense.sort();
+            assert_eq!(strided, dense);
+        }
+
+        let corner: Vec<_> = test
+            .leaves_in_region_strided([2, 3, 4], [5, 5, 5], (8, 8, 8))
+            .unwrap()
+            .collect();
+        assert_eq!(corner, vec![((2, 3, 4), test.get(2, 3, 4).unwrap())]);
+        assert_eq!(
+            test.leaves_in_region_strided([0; 3], [7; 3], (1, 0, 2))
+                .err(),
+            Some(ZeroStep { axis: 1 })
+        );
+    }
+
+    #[test]
+    fn leaves_in_region_strided_test() {
+        check_strided::<crate::layout::BreathFirst>();
+        check_strided::<DepthFirst>();
+    }
+
+    #[test]
+    fn path_values_test() {
+        check_path_values::<crate::layout::BreathFirst>();
+        check_path_values::<DepthFirst>();
+    }
+
+    fn check_can_descend<L: OctreeLayout>() {
+        let mut test = Octree::<u8, U3, L>::new(0);
+        test.child_mut::<crate::octant::OctantRUB>()
+            .child_mut::<crate::octant::OctantLDF>()
+            .set_value(1);
+        let air = |it: &u8| *it == 0;
+
+        assert!(test.root().can_descend(&[], air));
+        assert!(test
+            .root()
+            .can_descend(&[Octant::RUB, Octant::RUB, Octant::LDF], air));
+        assert!(!test.root().can_descend(&[Octant::RUB, Octant::LDF], air));
+        assert!(!test
+            .root()
+            .can_descend(&[Octant::RUB, Octant::LDF, Octant::RUB], air));
+        assert!(!test.root().can_descend(&[Octant::LDF; 4], air));
+
+        let visited = std::cell::Cell::new(0);
+        let counted = |it: &u8| {
+            visited.set(visited.get() + 1);
+            *it == 0
+        };
+        assert!(!test
+            .root()
+            .can_descend(&[Octant::RUB, Octant::LDF, Octant::RUB], counted));
+        assert_eq!(visited.get(), 3);
+
+        let rub = test.child::<crate::octant::OctantRUB>();
+        assert!(rub.can_descend(&[Octant::RDF, Octant::LUB], air));
+        assert!(!rub.can_descend(&[Octant::LDF, Octant::LUB], air));
+        assert!(!rub.can_descend(&[Octant::RDF; 3], air));
+    }
+
+    #[test]
+    fn can_descend_test() {
+        check_can_descend::<crate::layout::BreathFirst>();
+        check_can_descend::<DepthFirst>();
+    }
+
+    fn brute_force<Depth: Unsigned, L: OctreeLayout>(
+        tree: &Octree<u32, Depth, L>,
+        pred: impl Fn(&u32) -> bool,
+    ) -> Option<((usize, usize, usize), &u32)> {
+        (0..layer_length(Depth::USIZE))
+            .map(|i| (i, &tree.data[Octree::<u32, Depth, L>::leaf_data_index(i)]))
+            .find(|(_, value)| pred(value))
+            .map(|(i, value)| (leaf_index_to_coords(i), value))
+    }
+
+    #[test]
+    fn find_last_leaf_test() {
+        let mut bf = Octree::<u32, U3>::new(0);
+        let last = Octree::<u32, U3>::leaf_data_index(layer_length(3) - 1);
+        bf.data[last] = 1;
+        assert_eq!(
+            bf.find_leaf_where(|v| *v == 1, NO_PRUNE),
+            Some(((7, 7, 7), &1))
+        );
+
+        let mut df = Octree::<u32, U3, DepthFirst>::new(0);
+        let last = Octree::<u32, U3, DepthFirst>::leaf_data_index(layer_length(3) - 1);
+        df.data[last] = 1;
+        assert_eq!(
+            df.find_leaf_where(|v| *v == 1, NO_PRUNE),
+            Some(((7, 7, 7), &1))
+        );
+        assert_eq!(
+            df.find_map_leaf(|v| (*v == 1).then_some("found"), NO_PRUNE),
+            Some(((7, 7, 7), "found"))
+        );
+    }
+
+    #[test]
+    fn find_pruned_test() {
+        let mut test = Octree::<u32, U2>::new(0);
+        test.child_mut::<crate::octant::OctantRUB>().set_value(1);
+
+        assert_eq!(
+            test.find_leaf_where(|v| *v == 1, Some(|_: &u32| true)),
+            None
+        );
+        assert_eq!(
+            test.find_leaf_where(|v| *v == 1, Some(|v: &u32| *v == 0)),
+            None,
+        );
+        assert_eq!(
+            test.find_leaf_where(|v| *v == 1, Some(|_: &u32| false)),
+            Some(((2, 2, 2), &1))
+        );
+    }
+
+    #[test]
+    fn find_random_test() {
+        let mut state = 0x2545_f491;
+        for _ in 0..32 {
+            let mut bf = Octree::<u32, U3>::new(0);
+            let mut df = Octree::<u32, U3, DepthFirst>::new(0);
+            for _ in 0..4 {
+                let leaf = xorshift(&mut state) as usize % layer_length(3);
+                let value = xorshift(&mut state) % 4;
+                bf.data[Octree::<u32, U3>::leaf_data_index(leaf)] = value;
+                df.data[Octree::<u32, U3, DepthFirst>::leaf_data_index(leaf)] = value;
+            }
+
+            for target in 1..4 {
+                let pred = |v: &u32| *v == target;
+                assert_eq!(bf.find_leaf_where(pred, NO_PRUNE), brute_force(&bf, pred));
+                assert_eq!(df.find_leaf_where(pred, NO_PRUNE), brute_force(&df, pred));
+                assert_eq!(
+                    bf.find_leaf_where(pred, NO_PRUNE),
+                    df.find_leaf_where(pred, NO_PRUNE)
+                );
+            }
+        }
+    }
+}
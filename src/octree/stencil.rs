@@ -0,0 +1,290 @@
+use std::marker::PhantomData;
+
+use typenum::Unsigned;
+
+use crate::{layout::OctreeLayout, util::leaf_index_from_coords};
+
+use super::Octree;
+
+/// Handling of neighbours outside of the grid for stencil operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Boundary {
+    /// Coordinates outside of the grid are clamped to the nearest edge node.
+    Clamp,
+    /// Coordinates outside of the grid wrap around to the opposite side.
+    Wrap,
+    /// Nodes with neighbours outside of the grid are left unchanged.
+    Skip,
+}
+
+impl Boundary {
+    /// Returns the coordinate used for `coord` on a grid with `side` nodes,
+    /// or `None` if it's outside of the grid and should be skipped.
+    #[inline]
+    fn resolve(self, coord: isize, side: usize) -> Option<usize> {
+        let side = side as isize;
+        if (0..side).contains(&coord) {
+            return Some(coord as usize);
+        }
+        match self {
+            Boundary::Clamp => Some(coord.clamp(0, side - 1) as usize),
+            Boundary::Wrap => Some(coord.rem_euclid(side) as usize),
+            Boundary::Skip => None,
+        }
+    }
+}
+
+impl<T: Clone, Depth: Unsigned, L: OctreeLayout> Octree<T, Depth, L> {
+    /// Returns values of the nodes at `layer` as a dense `2^layer` cubed
+    /// grid, with `x` changing the fastest and `z` the slowest.
+    fn layer_grid(&self, layer: usize) -> Vec<T> {
+        let side = 1 << layer;
+        let mut result = Vec::with_capacity(side * side * side);
+        for z in 0..side {
+            for y in 0..side {
+                for x in 0..side {
+                    let index = leaf_index_from_coords(x, y, z);
+                    result.push(self.data[Self::node_data_index(layer, index)].clone());
+                }
+            }
+        }
+        result
+    }
+
+    /// Replaces every node at `layer` with the result of `f` called with
+    /// values of its 3×3×3 neighbourhood within the layer.
+    ///
+    /// Neighbours are passed in grid order, with offset `(dx, dy, dz)` at
+    /// index `(dx + 1) + 3 * (dy + 1) + 9 * (dz + 1)`, so the node itself is
+    /// at index `13`. All nodes read values from before the update. Values of
+    /// other layers aren't updated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `layer` is greater than `Depth`.
+    pub fn apply_kernel<F: Fn([&T; 27]) -> T>(&mut self, layer: usize, boundary: Boundary, f: F) {
+        assert!(layer <= Depth::USIZE, "layer {} out of range", layer);
+        let side = 1 << layer;
+        let grid = self.layer_grid(layer);
+        let grid_index = |x: usize, y: usize, z: usize| (z * side + y) * side + x;
+
+        for z in 0..side {
+            for y in 0..side {
+                'cell: for x in 0..side {
+                    let mut neighbours = [&grid[grid_index(x, y, z)]; 27];
+                    for (i, neighbour) in neighbours.iter_mut().enumerate() {
+                        let resolve = |coord: usize, offset: usize| {
+                            boundary.resolve(coord as isize + offset as isize - 1, side)
+                        };
+                        match (resolve(x, i % 3), resolve(y, i / 3 % 3), resolve(z, i / 9)) {
+                            (Some(nx), Some(ny), Some(nz)) => {
+                                *neighbour = &grid[grid_index(nx, ny, nz)];
+                            }
+                            _ => continue 'cell,
+                        }
+                    }
+                    let index = Self::node_data_index(layer, leaf_index_from_coords(x, y, z));
+                    self.data[index] = f(neighbours);
+                }
+            }
+        }
+    }
+
+    /// Returns the next generation of a cellular automaton over leaves, with
+    /// each leaf computed by `rule` from its value and values of its 6
+    /// face-adjacent neighbours.
+    ///
+    /// Neighbours are ordered `-x`, `+x`, `-y`, `+y`, `-z`, `+z`. All leaves
+    /// are computed from the current generation, and interior nodes are
+    /// copied as is.
+    pub fn cellular_step<F: Fn(&T, [&T; 6]) -> T>(
+        &self,
+        boundary: Boundary,
+        rule: F,
+    ) -> Octree<T, Depth, L> {
+        const OFFSETS: [(isize, isize, isize); 6] = [
+            (-1, 0, 0),
+            (1, 0, 0),
+            (0, -1, 0),
+            (0, 1, 0),
+            (0, 0, -1),
+            (0, 0, 1),
+        ];
+        let side = Self::SIDE;
+        let grid = self.to_dense_grid();
+
+        let mut result = Octree {
+            data: self.data.clone(),
+            _phantom: PhantomData,
+        };
+        for z in 0..side {
+            for y in 0..side {
+                'cell: for x in 0..side {
+                    let current = &grid[Self::grid_index(x, y, z)];
+                    let mut neighbours = [current; 6];
+                    for (neighbour, (dx, dy, dz)) in neighbours.iter_mut().zip(OFFSETS) {
+                        let resolve = |coord: usize, offset: isize| {
+                            boundary.resolve(coord as isize + offset, side)
+                        };
+                        match (resolve(x, dx), resolve(y, dy), resolve(z, dz)) {
+                            (Some(nx), Some(ny), Some(nz)) => {
+                                *neighbour = &grid[Self::grid_index(nx, ny, nz)];
+                            }
+                            _ => continue 'cell,
+                        }
+                    }
+                    let index = Self::leaf_data_index(leaf_index_from_coords(x, y, z));
+                    result.data[index] = rule(current, neighbours);
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typenum::U3;
+
+    use super::*;
+    use crate::{
+        layout::{BreathFirst, DepthFirst},
+        test_support::xorshift,
+    };
+
+    /// Game of Life rule over the `z` plane of the neighbourhood.
+    fn life(neighbours: [&bool; 27]) -> bool {
+        let alive = (9..18).filter(|i| *i != 13 && *neighbours[*i]).count();
+        matches!((*neighbours[13], alive), (true, 2) | (_, 3))
+    }
+
+    fn alive_cells<L: OctreeLayout>(test: &Octree<bool, U3, L>) -> Vec<(usize, usize, usize)> {
+        let mut result = Vec::new();
+        for z in 0..8 {
+            for y in 0..8 {
+                for x in 0..8 {
+                    if *test.get(x, y, z).unwrap() {
+                        result.push((x, y, z));
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    fn check_life<L: OctreeLayout>() {
+        let mut test = Octree::<bool, U3, L>::new(false);
+        for (x, y) in [(2, 3), (3, 3), (4, 3)] {
+            *test.get_mut(x, y, 5).unwrap() = true;
+        }
+        test.apply_kernel(3, Boundary::Skip, life);
+        assert_eq!(alive_cells(&test), vec![(3, 2, 5), (3, 3, 5), (3, 4, 5)]);
+        test.apply_kernel(3, Boundary::Skip, life);
+        assert_eq!(alive_cells(&test), vec![(2, 3, 5), (3, 3, 5), (4, 3, 5)]);
+
+        // A glider moves by one cell diagonally every 4 generations, wrapping
+        // around the edges.
+        let mut test = Octree::<bool, U3, L>::new(false);
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        for (x, y) in glider {
+            *test.get_mut(x, y, 1).unwrap() = true;
+        }
+        for _ in 0..32 {
+            test.apply_kernel(3, Boundary::Wrap, life);
+        }
+        let mut expected: Vec<_> = glider.iter().map(|(x, y)| (x % 8, y % 8, 1)).collect();
+        expected.sort_by_key(|(x, y, z)| (*z, *y, *x));
+        assert_eq!(alive_cells(&test), expected);
+        assert!(!**test);
+    }
+
+    #[test]
+    fn apply_kernel_life_test() {
+        check_life::<BreathFirst>();
+        check_life::<DepthFirst>();
+    }
+
+    /// Fills empty cells surrounded by mostly solid neighbours.
+    fn fill_pits(current: &bool, neighbours: [&bool; 6]) -> bool {
+        *current || neighbours.iter().filter(|it| ***it).count() >= 4
+    }
+
+    /// Removes solid cells without solid neighbours.
+    fn erode_isolated(current: &bool, neighbours: [&bool; 6]) -> bool {
+        *current && neighbours.iter().any(|it| **it)
+    }
+
+    fn check_cellular_step<L: OctreeLayout>() {
+        let mut state = 0x7f4a_7c15_u32;
+        let mut test = Octree::<bool, U3, L>::new(false);
+        for z in 0..8 {
+            for y in 0..8 {
+                for x in 0..8 {
+                    *test.get_mut(x, y, z).unwrap() = xorshift(&mut state) & 1 == 0;
+                }
+            }
+        }
+
+        for boundary in [Boundary::Clamp, Boundary::Wrap, Boundary::Skip] {
+            let mut current = test.cellular_step(boundary, fill_pits);
+            let mut steps = 1;
+            loop {
+                let next = current.cellular_step(boundary, fill_pits);
+                if next.data == current.data {
+                    break;
+                }
+                current = next;
+                steps += 1;
+                assert!(steps < 64, "smoothing didn't converge");
+            }
+            assert_ne!(current.data, test.data);
+            assert_eq!(**current, **test);
+        }
+    }
+
+    #[test]
+    fn cellular_step_test() {
+        check_cellular_step::<BreathFirst>();
+        check_cellular_step::<DepthFirst>();
+
+        // A single solid leaf gets removed, except on the edges when skipped.
+        let mut test = Octree::<bool, U3>::new(false);
+        *test.get_mut(0, 0, 0).unwrap() = true;
+        *test.get_mut(3, 4, 5).unwrap() = true;
+        let next = test.cellular_step(Boundary::Skip, erode_isolated);
+        assert_eq!(next.get(0, 0, 0), Some(&true));
+        assert_eq!(next.get(3, 4, 5), Some(&false));
+        let next = test.cellular_step(Boundary::Wrap, erode_isolated);
+        assert_eq!(next.get(0, 0, 0), Some(&false));
+    }
+
+    #[test]
+    fn apply_kernel_boundary_test() {
+        let mut test = Octree::<u32, U3>::new(0);
+        for (i, value) in test.layer_slice_mut::<typenum::U1>().iter_mut().enumerate() {
+            *value = i as u32;
+        }
+        let sum = |n: [&u32; 27]| n.into_iter().sum::<u32>();
+
+        let mut clamped = Octree::<u32, U3>::new(0);
+        clamped.copy_from(&test);
+        clamped.apply_kernel(1, Boundary::Clamp, sum);
+        // In a 2×2×2 layer, clamping repeats the node's own coordinate along
+        // each axis, while wrapping repeats the other one.
+        let clamped = clamped.layer_slice::<typenum::U1>();
+        assert_eq!((clamped[0], clamped[7]), (63, 126));
+
+        let mut wrapped = Octree::<u32, U3>::new(0);
+        wrapped.copy_from(&test);
+        wrapped.apply_kernel(1, Boundary::Wrap, sum);
+        let wrapped = wrapped.layer_slice::<typenum::U1>();
+        assert_eq!((wrapped[0], wrapped[7]), (126, 63));
+
+        let mut skipped = Octree::<u32, U3>::new(0);
+        skipped.copy_from(&test);
+        skipped.apply_kernel(1, Boundary::Skip, sum);
+        assert_eq!(skipped.data, test.data);
+        skipped.apply_kernel(0, Boundary::Clamp, sum);
+        assert_eq!(**skipped, 0);
+    }
+}
@@ -0,0 +1,279 @@
+use std::{
+    io::{self, Read, Write},
+    marker::PhantomData,
+    mem::size_of,
+};
+
+use typenum::{IsLessOrEqual, LeEq, Same, True, Unsigned};
+
+use crate::{
+    element::{read_value, slice_bytes, PlainElement},
+    layout::BreathFirst,
+    util::layer_length,
+};
+
+use super::Octree;
+
+/// Size of the stream header: depth (`u8`) followed by element size (`u32`
+/// little endian).
+const HEADER_LEN: usize = 5;
+
+fn header<T, Depth: Unsigned>() -> [u8; HEADER_LEN] {
+    let mut result = [0; HEADER_LEN];
+    result[0] = Depth::U8;
+    result[1..].copy_from_slice(&(size_of::<T>() as u32).to_le_bytes());
+    result
+}
+
+/// Size of the single layer header: depth (`u8`), layer (`u8`) and element
+/// size (`u32` little endian).
+const LAYER_HEADER_LEN: usize = 6;
+
+fn layer_header<T, Depth: Unsigned, Layer: Unsigned>() -> [u8; LAYER_HEADER_LEN] {
+    let mut result = [0; LAYER_HEADER_LEN];
+    result[0] = Depth::U8;
+    result[1] = Layer::U8;
+    result[2..].copy_from_slice(&(size_of::<T>() as u32).to_le_bytes());
+    result
+}
+
+/// Incremental serializer that writes octree layers to a sink as they're
+/// produced, starting with the root layer.
+///
+/// The written stream consists of a small header followed by values in
+/// [`BreathFirst`] order, so it can be loaded with
+/// [`Octree::read_progressive`] without ever holding the whole octree in
+/// memory while writing.
+#[derive(Debug)]
+pub struct StreamingWriter<W: Write, T: PlainElement, Depth: Unsigned> {
+    sink: W,
+    layer: usize,
+    _phantom: PhantomData<fn(&[T]) -> Depth>,
+}
+
+impl<W: Write, T: PlainElement, Depth: Unsigned> StreamingWriter<W, T, Depth> {
+    /// Creates a writer and writes the stream header to `sink`.
+    pub fn new(mut sink: W) -> io::Result<Self> {
+        sink.write_all(&header::<T, Depth>())?;
+        Ok(StreamingWriter {
+            sink,
+            layer: 0,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Returns the depth of the next layer to be written, or `None` if all
+    /// layers were written.
+    pub fn next_layer(&self) -> Option<usize> {
+        (self.layer <= Depth::USIZE).then_some(self.layer)
+    }
+
+    /// Writes values of the next layer, ordered by their index.
+    ///
+    /// Returns an [`InvalidInput`](io::ErrorKind::InvalidInput) error if
+    /// all layers were already written or `values` length doesn't match the
+    /// layer length.
+    pub fn write_layer(&mut self, values: &[T]) -> io::Result<()> {
+        let layer = self.next_layer().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "all layers already written")
+        })?;
+        if values.len() != layer_length(layer) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "layer {} expects {} values, got {}",
+                    layer,
+                    layer_length(layer),
+                    values.len()
+                ),
+            ));
+        }
+        self.sink.write_all(slice_bytes(values))?;
+        self.layer += 1;
+        Ok(())
+    }
+
+    /// Finishes writing and returns the sink.
+    ///
+    /// Returns an [`InvalidInput`](io::ErrorKind::InvalidInput) error if
+    /// not all layers were written.
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(layer) = self.next_layer() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("layer {} wasn't written", layer),
+            ));
+        }
+        self.sink.flush()?;
+        Ok(self.sink)
+    }
+}
+
+impl<T: PlainElement, Depth: Unsigned> Octree<T, Depth, BreathFirst> {
+    /// Returns a [`StreamingWriter`] for octrees of this type, writing to
+    /// `sink`.
+    pub fn streaming_writer<W: Write>(sink: W) -> io::Result<StreamingWriter<W, T, Depth>> {
+        StreamingWriter::new(sink)
+    }
+
+    /// Loads an octree written by a [`StreamingWriter`] from `reader`, calling
+    /// `on_layer` with the depth and values of every layer as soon as it's
+    /// read.
+    ///
+    /// Returns an [`InvalidData`](io::ErrorKind::InvalidData) error if the
+    /// stream header doesn't match the octree depth or value size.
+    pub fn read_progressive<R: Read>(
+        mut reader: R,
+        mut on_layer: impl FnMut(usize, &[T]),
+    ) -> io::Result<Self> {
+        let mut stream_header = [0; HEADER_LEN];
+        reader.read_exact(&mut stream_header)?;
+        if stream_header != header::<T, Depth>() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "stream header doesn't match octree type",
+            ));
+        }
+
+        let mut data = Vec::with_capacity(Self::node_count());
+        let mut bytes = Vec::new();
+        for layer in 0..=Depth::USIZE {
+            bytes.resize(layer_length(layer) * size_of::<T>(), 0);
+            reader.read_exact(&mut bytes)?;
+            let start = data.len();
+            data.extend((0..layer_length(layer)).map(|i| read_value::<T>(&bytes, i)));
+            on_layer(layer, &data[start..]);
+        }
+        Ok(Octree {
+            data: data.into(),
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Writes values of the `Layer` to `w`, preceded by a header describing
+    /// the octree depth, the layer and the value size.
+    ///
+    /// The layer can be loaded back with [`Octree::read_layer`].
+    pub fn write_layer<Layer, W: Write>(&self, w: &mut W) -> io::Result<()>
+    where
+        Layer: Unsigned + IsLessOrEqual<Depth>,
+        LeEq<Layer, Depth>: Same<True>,
+    {
+        w.write_all(&layer_header::<T, Depth, Layer>())?;
+        w.write_all(slice_bytes(self.layer_slice::<Layer>()))
+    }
+
+    /// Overwrites values of the `Layer` with ones written by
+    /// [`Octree::write_layer`] to `r`, without changing any other layer.
+    ///
+    /// Returns an [`InvalidData`](io::ErrorKind::InvalidData) error if the
+    /// header doesn't match the octree depth, layer or value size. Values are
+    /// only changed if the whole layer was read successfully.
+    pub fn read_layer<Layer, R: Read>(&mut self, r: &mut R) -> io::Result<()>
+    where
+        Layer: Unsigned + IsLessOrEqual<Depth>,
+        LeEq<Layer, Depth>: Same<True>,
+    {
+        let mut stream_header = [0; LAYER_HEADER_LEN];
+        r.read_exact(&mut stream_header)?;
+        if stream_header != layer_header::<T, Depth, Layer>() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "layer header doesn't match octree layer",
+            ));
+        }
+
+        let mut bytes = vec![0; layer_length(Layer::USIZE) * size_of::<T>()];
+        r.read_exact(&mut bytes)?;
+        for (i, value) in self.layer_slice_mut::<Layer>().iter_mut().enumerate() {
+            *value = read_value(&bytes, i);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typenum::{U1, U2, U3};
+
+    use super::*;
+
+    #[test]
+    fn streaming_round_trip_test() {
+        let mut writer = Octree::<u32, U3>::streaming_writer(Vec::new()).unwrap();
+        while let Some(layer) = writer.next_layer() {
+            let values: Vec<u32> = (0..layer_length(layer))
+                .map(|i| (layer as u32) << 16 | i as u32)
+                .collect();
+            writer.write_layer(&values).unwrap();
+        }
+        let bytes = writer.finish().unwrap();
+
+        let mut layers = Vec::new();
+        let tree = Octree::<u32, U3>::read_progressive(&bytes[..], |layer, values| {
+            layers.push((layer, values.len()))
+        })
+        .unwrap();
+        assert_eq!(layers, vec![(0, 1), (1, 8), (2, 64), (3, 512)]);
+        assert_eq!(&bytes[HEADER_LEN..], tree.as_bytes());
+        assert_eq!(*tree.get(7, 7, 7).unwrap(), 3 << 16 | 511);
+        assert_eq!(tree.layer_slice::<U2>()[5], 2 << 16 | 5);
+    }
+
+    #[test]
+    fn layer_round_trip_test() {
+        let mut source = Octree::<u16, U3>::new(0);
+        for (i, value) in source.data.iter_mut().enumerate() {
+            *value = i as u16;
+        }
+        let mut bytes = Vec::new();
+        source.write_layer::<U2, _>(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), LAYER_HEADER_LEN + 64 * 2);
+
+        let mut target = Octree::<u16, U3>::new(7);
+        target.read_layer::<U2, _>(&mut &bytes[..]).unwrap();
+        assert_eq!(target.layer_slice::<U2>(), source.layer_slice::<U2>());
+        assert_eq!(**target, 7);
+        assert!(target.layer_slice::<U1>().iter().all(|it| *it == 7));
+        assert!(target.layer_slice::<U3>().iter().all(|it| *it == 7));
+
+        let wrong_layer = target.read_layer::<U1, _>(&mut &bytes[..]);
+        assert_eq!(wrong_layer.unwrap_err().kind(), io::ErrorKind::InvalidData);
+        let mut shallow = Octree::<u16, U2>::new(0);
+        let wrong_depth = shallow.read_layer::<U2, _>(&mut &bytes[..]);
+        assert_eq!(wrong_depth.unwrap_err().kind(), io::ErrorKind::InvalidData);
+        let mut target = Octree::<u16, U3>::new(7);
+        let truncated = target.read_layer::<U2, _>(&mut &bytes[..bytes.len() - 1]);
+        assert_eq!(truncated.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        assert!(target.data.iter().all(|it| *it == 7));
+    }
+
+    #[test]
+    fn streaming_errors_test() {
+        let mut writer = StreamingWriter::<_, u16, U2>::new(Vec::new()).unwrap();
+        assert!(writer.write_layer(&[0, 1]).is_err());
+        writer.write_layer(&[0]).unwrap();
+        let bytes = writer.finish().unwrap_err();
+        assert_eq!(bytes.kind(), io::ErrorKind::InvalidInput);
+
+        let mut writer = Octree::<u16, U2>::streaming_writer(Vec::new()).unwrap();
+        writer.write_layer(&[0]).unwrap();
+        writer.write_layer(&[0; 8]).unwrap();
+        writer.write_layer(&[0; 64]).unwrap();
+        assert!(writer.write_layer(&[0]).is_err());
+        let bytes = writer.finish().unwrap();
+
+        let wrong_type = Octree::<u32, U2>::read_progressive(&bytes[..], |_, _| {});
+        assert_eq!(wrong_type.err().unwrap().kind(), io::ErrorKind::InvalidData);
+        let wrong_depth = Octree::<u16, U3>::read_progressive(&bytes[..], |_, _| {});
+        assert_eq!(
+            wrong_depth.err().unwrap().kind(),
+            io::ErrorKind::InvalidData
+        );
+        let truncated = Octree::<u16, U2>::read_progressive(&bytes[..bytes.len() - 1], |_, _| {});
+        assert_eq!(
+            truncated.err().unwrap().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::VecDeque;
+
+use typenum::Unsigned;
+
+use crate::{layout::OctreeLayout, octant::Octant};
+
+use super::Octree;
+
+/// Occupancy of a subtree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Occupancy {
+    Empty,
+    Full,
+    Mixed,
+}
+
+/// Largest relative child pointer that fits into an SVO node.
+const MAX_POINTER: usize = (1 << 24) - 1;
+
+impl<T: Clone, Depth: Unsigned, L: OctreeLayout> Octree<T, Depth, L> {
+    /// Encodes leaf occupancy as a sparse voxel octree node array, as
+    /// expected by GPU raymarching shaders.
+    ///
+    /// A leaf is occupied if `solid` holds for it. Each node is a single
+    /// `u32`, with the lowest 8 bits being a mask of non-empty children (bit
+    /// `i` for octant `i`), and the upper 24 bits the offset from the node to
+    /// its first child in the returned array. Non-empty children of a node
+    /// are stored contiguously in octant order, and fully occupied subtrees
+    /// are pruned to a single node with an empty child mask. The root is
+    /// stored first, unless no leaf is occupied, in which case the returned
+    /// array is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a child offset doesn't fit into 24 bits.
+    pub fn to_svo(&self, solid: impl Fn(&T) -> bool) -> Vec<u32> {
+        let mut occupancy = vec![Occupancy::Empty; self.data.len()];
+        self.occupancy_in(0, Depth::USIZE, 0, &solid, &mut occupancy);
+        match occupancy[0] {
+            Occupancy::Empty => return Vec::new(),
+            Occupancy::Full => return vec![0],
+            Occupancy::Mixed => {}
+        }
+
+        let mut result = vec![0];
+        let mut queue = VecDeque::new();
+        queue.push_back((0, Depth::USIZE, 0, 0));
+        while let Some((offset, depth, index, node)) = queue.pop_front() {
+            let first_child = result.len();
+            let mut mask = 0;
+            for octant in Octant::ALL {
+                let child = Self::child_data_index(offset, octant, depth, index);
+                match occupancy[child] {
+                    Occupancy::Empty => continue,
+                    Occupancy::Full => {}
+                    Occupancy::Mixed => {
+                        let child_index = index * 8 + octant.as_usize();
+                        queue.push_back((child, depth - 1, child_index, result.len()));
+                    }
+                }
+                mask |= 1 << octant.as_usize();
+                result.push(0);
+            }
+            let pointer = first_child - node;
+            assert!(pointer <= MAX_POINTER, "SVO child offset out of range");
+            result[node] = mask | (pointer as u32) << 8;
+        }
+        result
+    }
+
+    fn occupancy_in(
+        &self,
+        offset: usize,
+        depth: usize,
+        index: usize,
+        solid: &impl Fn(&T) -> bool,
+        occupancy: &mut [Occupancy],
+    ) -> Occupancy {
+        let result = if depth == 0 {
+            if solid(&self.data[offset]) {
+                Occupancy::Full
+            } else {
+                Occupancy::Empty
+            }
+        } else {
+            let mut children = Octant::ALL.into_iter().map(|octant| {
+                let child = Self::child_data_index(offset, octant, depth, index);
+                let child_index = index * 8 + octant.as_usize();
+                self.occupancy_in(child, depth - 1, child_index, solid, occupancy)
+            });
+            let first = children.next().unwrap();
+            children.fold(
+                first,
+                |acc, it| if acc == it { acc } else { Occupancy::Mixed },
+            )
+        };
+        occupancy[offset] = result;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typenum::U3;
+
+    use super::*;
+    use crate::{
+        layout::{BreathFirst, DepthFirst},
+        test_support::xorshift,
+        util::{layer_length, leaf_index_to_coords},
+    };
+
+    /// Returns morton indices of occupied leaves in `svo` of the given
+    /// `depth`.
+    fn decode(svo: &[u32], depth: usize) -> Vec<usize> {
+        fn visit(svo: &[u32], node: usize, depth: usize, index: usize, result: &mut Vec<usize>) {
+            let mask = svo[node] & 0xFF;
+            if mask == 0 {
+                let count = layer_length(depth);
+                result.extend(index * count..(index + 1) * count);
+                return;
+            }
+            let mut child = node + (svo[node] >> 8) as usize;
+            for i in 0..8 {
+                if mask & (1 << i) != 0 {
+                    visit(svo, child, depth - 1, index * 8 + i, result);
+                    child += 1;
+                }
+            }
+        }
+
+        let mut result = Vec::new();
+        if !svo.is_empty() {
+            visit(svo, 0, depth, 0, &mut result);
+        }
+        result
+    }
+
+    fn occupied<L: OctreeLayout>(tree: &Octree<u8, U3, L>) -> Vec<usize> {
+        (0..layer_length(3))
+            .filter(|i| {
+                let (x, y, z) = leaf_index_to_coords(*i);
+                *tree.get(x, y, z).unwrap() != 0
+            })
+            .collect()
+    }
+
+    fn check_to_svo<L: OctreeLayout>() {
+        let mut test = Octree::<u8, U3, L>::new(0);
+        assert!(test.to_svo(|it| *it != 0).is_empty());
+
+        test.child_mut::<crate::octant::OctantRUB>().set_value(1);
+        *test.get_mut(1, 2, 3).unwrap() = 2;
+        *test.get_mut(1, 2, 2).unwrap() = 3;
+        *test.get_mut(6, 0, 1).unwrap() = 4;
+        let svo = test.to_svo(|it| *it != 0);
+        assert_eq!(decode(&svo, 3), occupied(&test));
+        // The fully occupied RUB subtree is a single node.
+        assert_eq!(svo[0], 0b1000_0011 | 1 << 8);
+        assert_eq!(svo[3], 0);
+        assert_eq!(svo.len(), 1 + 3 + 1 + 1 + 2 + 1);
+
+        let mut state = 0x1234_5678u32;
+        for _ in 0..64 {
+            let value = xorshift(&mut state);
+            let (x, y, z) = leaf_index_to_coords(value as usize % layer_length(3));
+            *test.get_mut(x, y, z).unwrap() = (value >> 16) as u8 % 2;
+        }
+        let svo = test.to_svo(|it| *it != 0);
+        assert_eq!(decode(&svo, 3), occupied(&test));
+
+        let full = Octree::<u8, U3, L>::new(1);
+        assert_eq!(full.to_svo(|it| *it != 0), vec![0]);
+    }
+
+    #[test]
+    fn to_svo_test() {
+        check_to_svo::<BreathFirst>();
+        check_to_svo::<DepthFirst>();
+    }
+}
@@ -0,0 +1,264 @@
+use std::{
+    marker::PhantomData,
+    ops::{BitAnd, BitOr, BitXor},
+};
+
+use typenum::Unsigned;
+
+use crate::{
+    layout::OctreeLayout,
+    octant::{Axis, Octant, OctantPath},
+};
+
+use super::Octree;
+
+impl<T: Clone, Depth: Unsigned, L: OctreeLayout> Octree<T, Depth, L> {
+    /// Returns an octree with all node values mapped with `f`.
+    pub fn map<U: Clone>(&self, f: impl Fn(&T) -> U) -> Octree<U, Depth, L> {
+        Octree {
+            data: self.data.iter().map(f).collect(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Applies `f` to all node values in place.
+    pub fn map_in_place<F: FnMut(&mut T)>(&mut self, f: F) {
+        self.data.iter_mut().for_each(f);
+    }
+
+    /// Returns an octree with node values computed by `f` from values of the
+    /// same nodes in this octree and `other`.
+    pub fn zip_with<U: Clone, V: Clone>(
+        &self,
+        other: &Octree<U, Depth, L>,
+        f: impl Fn(&T, &U) -> V,
+    ) -> Octree<V, Depth, L> {
+        Octree {
+            data: self
+                .data
+                .iter()
+                .zip(other.data.iter())
+                .map(|(a, b)| f(a, b))
+                .collect(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns an octree with all node values mapped with `f`, which also
+    /// receives the node depth (`0` for the root, `Depth` for leaves).
+    pub fn map_with_depth<U: Clone, F: Fn(usize, &T) -> U>(&self, f: F) -> Octree<U, Depth, L> {
+        let mut layers = vec![0; self.data.len()];
+        Self::for_each_node(&mut |offset, layer, _| layers[offset] = layer);
+        Octree {
+            data: self
+                .data
+                .iter()
+                .zip(layers)
+                .map(|(value, layer)| f(layer, value))
+                .collect(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns an octree rotated by 90° counter-clockwise around the `axis`
+    /// (looking from its positive end).
+    pub fn rotate90(&self, axis: Axis) -> Self {
+        let mut data = self.data.clone();
+        Self::for_each_node(&mut |offset, layer, index| {
+            let rotated = (0..layer).fold(0, |acc, digit| {
+                let octant = Octant::ALL[(index >> ((layer - 1 - digit) * 3)) & 0b111];
+                acc * 8 + octant.rotate90(axis).as_usize()
+            });
+            data[Self::node_data_index(layer, rotated)] = self.data[offset].clone();
+        });
+        Octree {
+            data,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns an octree with nodes moved to paths returned by `remap`.
+    ///
+    /// `remap` is called with the path of every node and returns its
+    /// destination path, or `None` if the node should be dropped. Nodes that
+    /// no source node is moved to are set to `T::default()`, and if multiple
+    /// source nodes are moved to the same node, the last visited one (in
+    /// depth-first order) is kept.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a returned path is longer than `Depth`.
+    pub fn apply_affine<F: Fn(&OctantPath) -> Option<OctantPath>>(&self, remap: F) -> Self
+    where
+        T: Default,
+    {
+        let mut data = vec![T::default(); self.data.len()];
+        let mut path = OctantPath::with_capacity(Depth::USIZE);
+        Self::for_each_node(&mut |offset, layer, index| {
+            path.clear();
+            path.extend(
+                (0..layer).map(|digit| Octant::ALL[(index >> ((layer - 1 - digit) * 3)) & 0b111]),
+            );
+            if let Some(target) = remap(&path) {
+                assert!(target.len() <= Depth::USIZE, "path is deeper than octree");
+                let target_index = target
+                    .iter()
+                    .fold(0, |acc, octant| acc * 8 + octant.as_usize());
+                data[Self::node_data_index(target.len(), target_index)] = self.data[offset].clone();
+            }
+        });
+        Octree {
+            data: data.into(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+macro_rules! impl_bool_op {
+    ($($trait: ident, $method: ident, $op: tt;)*) => {$(
+        impl<Depth: Unsigned, L: OctreeLayout> $trait for &Octree<bool, Depth, L> {
+            type Output = Octree<bool, Depth, L>;
+
+            fn $method(self, rhs: Self) -> Self::Output {
+                self.zip_with(rhs, |a, b| *a $op *b)
+            }
+        }
+
+        impl<Depth: Unsigned, L: OctreeLayout> $trait for Octree<bool, Depth, L> {
+            type Output = Octree<bool, Depth, L>;
+
+            fn $method(self, rhs: Self) -> Self::Output {
+                (&self).$method(&rhs)
+            }
+        }
+    )*};
+}
+impl_bool_op! {
+    BitOr, bitor, |;
+    BitAnd, bitand, &;
+    BitXor, bitxor, ^;
+}
+
+#[cfg(test)]
+mod tests {
+    use typenum::U3;
+
+    use super::*;
+    use crate::{
+        layout::{BreathFirst, DepthFirst},
+        octant::*,
+    };
+
+    fn check_map_with_depth<L: OctreeLayout>() {
+        let test = Octree::<u8, U3, L>::new(0);
+        let depths = test.map_with_depth(|depth, _| depth);
+
+        assert_eq!(**depths, 0);
+        assert_eq!(**depths.child::<OctantRDB>(), 1);
+        assert_eq!(**depths.child::<OctantRDB>().child::<OctantLUF>(), 2);
+        let leaf = depths
+            .child::<OctantRUB>()
+            .child::<OctantLDF>()
+            .child::<OctantRDB>();
+        assert_eq!(**leaf, 3);
+        assert_eq!(depths.data.iter().filter(|it| **it == 3).count(), 512);
+        assert_eq!(depths.data.iter().filter(|it| **it == 2).count(), 64);
+    }
+
+    fn check_rotate90<L: OctreeLayout>() {
+        let mut test = Octree::<u32, U3, L>::new(0);
+        for (i, value) in test.data.iter_mut().enumerate() {
+            *value = i as u32;
+        }
+
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            let rotated = test.rotate90(axis);
+            let remapped = test.apply_affine(|path| {
+                Some(path.iter().map(|octant| octant.rotate90(axis)).collect())
+            });
+            assert_eq!(rotated.data, remapped.data);
+            assert_eq!(**rotated, **test);
+
+            for (x, y, z) in [(0, 0, 0), (1, 2, 3), (7, 0, 5)] {
+                let (rx, ry, rz) = match axis {
+                    Axis::X => (x, 7 - z, y),
+                    Axis::Y => (z, y, 7 - x),
+                    Axis::Z => (7 - y, x, z),
+                };
+                assert_eq!(rotated.get(rx, ry, rz), test.get(x, y, z));
+            }
+
+            let full = (0..3).fold(rotated, |it, _| it.rotate90(axis));
+            assert_eq!(full.data, test.data);
+        }
+    }
+
+    #[test]
+    fn rotate90_test() {
+        check_rotate90::<BreathFirst>();
+        check_rotate90::<DepthFirst>();
+    }
+
+    #[test]
+    fn apply_affine_drop_test() {
+        let mut test = Octree::<u8, U3>::new(1);
+        test.child_mut::<OctantRUB>().set_value(2);
+        let moved = test.apply_affine(|path| match path.first() {
+            Some(Octant::RUB) => Some(path[1..].to_vec()),
+            _ => None,
+        });
+
+        assert_eq!(**moved, 2);
+        assert_eq!(**moved.child::<OctantLDF>(), 2);
+        assert!(moved.data[Octree::<u8, U3>::node_data_index(3, 0)..]
+            .iter()
+            .all(|it| *it == 0));
+    }
+
+    #[test]
+    fn bool_ops_test() {
+        let mut a = Octree::<bool, typenum::U1, DepthFirst>::new(false);
+        let mut b = Octree::<bool, typenum::U1, DepthFirst>::new(false);
+        // Leaves LDF..RUF cover all four input pairs.
+        a.child_mut::<OctantLUF>().set_value(true);
+        a.child_mut::<OctantRUF>().set_value(true);
+        b.child_mut::<OctantRDF>().set_value(true);
+        b.child_mut::<OctantRUF>().set_value(true);
+
+        let leaves = |tree: &Octree<bool, typenum::U1, DepthFirst>| {
+            Octant::ALL.map(|octant| *tree.dyn_root().child(octant).unwrap().value())
+        };
+        let or = &a | &b;
+        let and = &a & &b;
+        let xor = &a ^ &b;
+        assert_eq!(leaves(&or)[..4], [false, true, true, true]);
+        assert_eq!(leaves(&and)[..4], [false, false, false, true]);
+        assert_eq!(leaves(&xor)[..4], [false, true, true, false]);
+        assert!(!leaves(&or)[4..].contains(&true));
+
+        assert_eq!((a.map(|it| *it) | b.map(|it| *it)).data, or.data);
+        assert_eq!((a.map(|it| *it) & b.map(|it| *it)).data, and.data);
+        assert_eq!((a ^ b).data, xor.data);
+    }
+
+    #[test]
+    fn map_test() {
+        let mut test = Octree::<u8, U3>::new(1);
+        test.child_mut::<OctantLUF>().set_value(2);
+        let mapped = test.map(|it| *it as u32 * 10);
+        assert_eq!(**mapped, 10);
+        assert_eq!(**mapped.child::<OctantLUF>().child::<OctantRDB>(), 20);
+
+        check_map_with_depth::<BreathFirst>();
+        check_map_with_depth::<DepthFirst>();
+    }
+
+    #[test]
+    fn map_in_place_test() {
+        let mut test = Octree::<u8, U3, DepthFirst>::new(1);
+        test.child_mut::<OctantRUB>().set_value(5);
+        let expected = test.map(|it| it.min(&3) * 2);
+        test.map_in_place(|it| *it = (*it).min(3) * 2);
+        assert_eq!(test.data, expected.data);
+    }
+}
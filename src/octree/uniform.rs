@@ -0,0 +1,367 @@
+use std::marker::PhantomData;
+
+use typenum::Unsigned;
+
+use crate::{
+    layout::OctreeLayout,
+    octant::Octant,
+    util::{layer_length, node_offset_bf},
+};
+
+use super::{ChangeEvent, Octree};
+
+/// Records which subtrees of an octree are uniform.
+///
+/// A subtree is uniform if all of its nodes, including the subtree root, are
+/// equal. The mask stores a single bit for every interior node, while leaves
+/// are always uniform. Queries that accept a mask read the value of a
+/// uniform subtree root once instead of descending into it.
+///
+/// The mask isn't updated by octree mutations. It can be rebuilt with
+/// [`Octree::build_uniform_mask`], or updated incrementally with
+/// [`UniformMask::update_path`] and [`UniformMask::apply`], the latter
+/// taking events reported by a [`LoggedOctree`](super::LoggedOctree).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniformMask<Depth: Unsigned> {
+    bits: Vec<u64>,
+    _phantom: PhantomData<fn() -> Depth>,
+}
+
+impl<Depth: Unsigned> UniformMask<Depth> {
+    /// Number of interior nodes.
+    const LEN: usize = (layer_length(Depth::USIZE) - 1) / 7;
+
+    /// Returns whether the subtree of the node at the given `layer` and layer
+    /// `index` is uniform.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `layer` is greater than `Depth` or `index` is outside the
+    /// layer.
+    pub fn is_uniform(&self, layer: usize, index: usize) -> bool {
+        assert!(layer <= Depth::USIZE, "layer {} out of range", layer);
+        assert!(index < layer_length(layer), "index out of range");
+        if layer == Depth::USIZE {
+            return true;
+        }
+        let i = node_offset_bf(layer, index, Depth::USIZE);
+        self.bits[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    /// Returns the number of interior nodes with uniform subtrees.
+    pub(super) fn uniform_count(&self) -> usize {
+        self.bits.iter().map(|it| it.count_ones() as usize).sum()
+    }
+
+    fn set(&mut self, layer: usize, index: usize, value: bool) {
+        if layer == Depth::USIZE {
+            return;
+        }
+        let i = node_offset_bf(layer, index, Depth::USIZE);
+        if value {
+            self.bits[i / 64] |= 1 << (i % 64);
+        } else {
+            self.bits[i / 64] &= !(1 << (i % 64));
+        }
+    }
+
+    /// Recomputes bits of the node at `path` and all of its descendants and
+    /// ancestors from `tree`.
+    ///
+    /// This needs to be called after the subtree at `path` is written, and
+    /// suffices if no nodes outside of it and its ancestors changed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `path` is longer than `Depth`.
+    pub fn update_path<T: Clone + PartialEq, L: OctreeLayout>(
+        &mut self,
+        tree: &Octree<T, Depth, L>,
+        path: &[Octant],
+    ) {
+        assert!(path.len() <= Depth::USIZE, "path is deeper than octree");
+        let mut offsets = Vec::with_capacity(path.len() + 1);
+        let mut offset = 0;
+        let mut index = 0;
+        offsets.push(offset);
+        for (layer, octant) in path.iter().enumerate() {
+            offset = Octree::<T, Depth, L>::child_data_index(
+                offset,
+                *octant,
+                Depth::USIZE - layer,
+                index,
+            );
+            index = index * 8 + octant.as_usize();
+            offsets.push(offset);
+        }
+
+        let mut uniform = self.update_in(tree, offsets[path.len()], path.len(), index);
+        for layer in (0..path.len()).rev() {
+            let child = index;
+            index /= 8;
+            let offset = offsets[layer];
+            let depth = Depth::USIZE - layer;
+            // Siblings of the updated child are unchanged, so their bits are
+            // still valid.
+            uniform = uniform
+                && Octant::ALL.into_iter().all(|octant| {
+                    let child_index = index * 8 + octant.as_usize();
+                    let child_offset =
+                        Octree::<T, Depth, L>::child_data_index(offset, octant, depth, index);
+                    (child_index == child || self.is_uniform(layer + 1, child_index))
+                        && tree.data[child_offset] == tree.data[offset]
+                });
+            self.set(layer, index, uniform);
+        }
+    }
+
+    /// Updates the mask for a change reported by a
+    /// [`LoggedOctree`](super::LoggedOctree) wrapping `tree`.
+    pub fn apply<T: Clone + PartialEq, L: OctreeLayout>(
+        &mut self,
+        tree: &Octree<T, Depth, L>,
+        event: &ChangeEvent,
+    ) {
+        self.update_path(tree, &event.path);
+    }
+
+    /// Recomputes bits of the subtree at `offset` in the given `layer`, and
+    /// returns whether it's uniform.
+    fn update_in<T: Clone + PartialEq, L: OctreeLayout>(
+        &mut self,
+        tree: &Octree<T, Depth, L>,
+        offset: usize,
+        layer: usize,
+        index: usize,
+    ) -> bool {
+        if layer == Depth::USIZE {
+            return true;
+        }
+        let depth = Depth::USIZE - layer;
+        let mut uniform = true;
+        for octant in Octant::ALL {
+            let child_offset =
+                Octree::<T, Depth, L>::child_data_index(offset, octant, depth, index);
+            let child_index = index * 8 + octant.as_usize();
+            let child_uniform = self.update_in(tree, child_offset, layer + 1, child_index);
+            uniform &= child_uniform && tree.data[child_offset] == tree.data[offset];
+        }
+        self.set(layer, index, uniform);
+        uniform
+    }
+}
+
+impl<T: Clone + PartialEq, Depth: Unsigned, L: OctreeLayout> Octree<T, Depth, L> {
+    /// Returns a mask of uniform subtrees of this octree.
+    pub fn build_uniform_mask(&self) -> UniformMask<Depth> {
+        let mut mask = UniformMask {
+            bits: vec![0; UniformMask::<Depth>::LEN.div_ceil(64)],
+            _phantom: PhantomData,
+        };
+        mask.update_in(self, 0, 0, 0);
+        mask
+    }
+
+    /// Returns the depth of the deepest subtree of this octree whose nodes
+    /// are all equal.
+    ///
+    /// This is `Depth` for a uniform octree and `0` if no interior node has a
+    /// uniform subtree, as every leaf is trivially uniform.
+    pub fn max_contiguous_uniform_depth(&self) -> usize {
+        let mask = self.build_uniform_mask();
+        (0..=Depth::USIZE)
+            .find(|layer| (0..layer_length(*layer)).any(|index| mask.is_uniform(*layer, index)))
+            .map_or(0, |layer| Depth::USIZE - layer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typenum::U3;
+
+    use super::*;
+    use crate::{
+        layout::{BreathFirst, DepthFirst},
+        octant::*,
+        LoggedOctree,
+    };
+
+    fn check_build<L: OctreeLayout>() {
+        let mut test = Octree::<u8, U3, L>::new(0);
+        let mask = test.build_uniform_mask();
+        assert!((0..=3).all(|layer| (0..layer_length(layer)).all(|i| mask.is_uniform(layer, i))));
+
+        *test.get_mut(7, 7, 7).unwrap() = 1;
+        let mask = test.build_uniform_mask();
+        assert!(!mask.is_uniform(0, 0));
+        assert!(!mask.is_uniform(1, 7));
+        assert!(!mask.is_uniform(2, 0o77));
+        assert!(mask.is_uniform(2, 0o76));
+        assert!(mask.is_uniform(1, 6));
+        assert!(mask.is_uniform(3, 0o777));
+
+        // Interior values differing from their leaves aren't uniform.
+        test.child_mut::<OctantLDF>()
+            .child_mut::<OctantLDF>()
+            .set_value(2);
+        test.child_mut::<OctantLDF>()
+            .child_mut::<OctantLDF>()
+            .children_mut()
+            .0
+            .set_value(0);
+        let mask = test.build_uniform_mask();
+        assert!(!mask.is_uniform(2, 0));
+        assert!(!mask.is_uniform(1, 0));
+        assert!(mask.is_uniform(1, 1));
+    }
+
+    #[test]
+    fn build_uniform_mask_test() {
+        check_build::<BreathFirst>();
+        check_build::<DepthFirst>();
+    }
+
+    fn check_max_uniform_depth<L: OctreeLayout>() {
+        assert_eq!(
+            Octree::<u8, typenum::U5, L>::new(3).max_contiguous_uniform_depth(),
+            5
+        );
+        assert_eq!(
+            Octree::<u8, typenum::U0, L>::new(3).max_contiguous_uniform_depth(),
+            0
+        );
+
+        let mut test = Octree::<usize, U3, L>::new_with(|i| i);
+        assert_eq!(test.max_contiguous_uniform_depth(), 0);
+        test.child_mut::<OctantRUB>()
+            .child_mut::<OctantLDF>()
+            .set_value(1);
+        assert_eq!(test.max_contiguous_uniform_depth(), 1);
+        test.child_mut::<OctantLUB>().set_value(1);
+        assert_eq!(test.max_contiguous_uniform_depth(), 2);
+    }
+
+    #[test]
+    fn max_contiguous_uniform_depth_test() {
+        check_max_uniform_depth::<BreathFirst>();
+        check_max_uniform_depth::<DepthFirst>();
+    }
+
+    #[test]
+    fn uniform_mask_update_test() {
+        let mut logged = LoggedOctree::new(Octree::<u8, U3, DepthFirst>::new(0));
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = events.clone();
+        logged.set_change_listener(move |event| sink.lock().unwrap().push(event));
+        let mut mask = logged.build_uniform_mask();
+
+        let check = |logged: &LoggedOctree<u8, U3, DepthFirst>, mask: &mut UniformMask<U3>| {
+            for event in events.lock().unwrap().drain(..) {
+                mask.apply(logged, &event);
+            }
+            assert_eq!(*mask, logged.build_uniform_mask());
+        };
+        logged.set_leaf(1, 2, 3, 4);
+        check(&logged, &mut mask);
+        logged.set_value(&[Octant::RUB], 5);
+        check(&logged, &mut mask);
+        logged.set_region([0, 0, 0], [3, 3, 3], 0);
+        check(&logged, &mut mask);
+        logged.set_value(&[Octant::RUB, Octant::LDF], 6);
+        check(&logged, &mut mask);
+
+        assert!(mask.is_uniform(2, 0o70));
+        assert!(!mask.is_uniform(1, 7));
+        assert!(mask.is_uniform(1, 6));
+    }
+
+    /// Value that counts how many times it was compared.
+    #[derive(Debug, Clone)]
+    struct Counted(u8);
+
+    thread_local! {
+        static COMPARISONS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    impl PartialEq for Counted {
+        fn eq(&self, other: &Self) -> bool {
+            COMPARISONS.set(COMPARISONS.get() + 1);
+            self.0 == other.0
+        }
+    }
+
+    fn comparisons(f: impl FnOnce() -> usize) -> (usize, usize) {
+        COMPARISONS.set(0);
+        let result = f();
+        (result, COMPARISONS.get())
+    }
+
+    fn check_masked_queries<L: OctreeLayout>() {
+        let mut test = Octree::<Counted, typenum::U4, L>::new(Counted(0));
+        for (x, y, z) in [(1, 2, 3), (15, 15, 15), (8, 0, 9), (9, 0, 9)] {
+            *test.get_mut(x, y, z).unwrap() = Counted(1);
+        }
+        let mask = test.build_uniform_mask();
+
+        let solid = Counted(1);
+        let (plain, plain_visits) = comparisons(|| test.count_leaves_eq(&solid, None));
+        let (masked, masked_visits) = comparisons(|| test.count_leaves_eq(&solid, Some(&mask)));
+        assert_eq!(plain, 4);
+        assert_eq!(masked, plain);
+        assert!(masked_visits * 10 < plain_visits);
+
+        for (min, max) in [
+            ([0, 0, 0], [15, 15, 15]),
+            ([1, 0, 2], [9, 6, 12]),
+            ([9, 9, 9], [99, 99, 99]),
+        ] {
+            let plain: Vec<_> = test
+                .leaves_in_region(min, max, None)
+                .map(|(at, it)| (at, it.0))
+                .collect();
+            let masked: Vec<_> = test
+                .leaves_in_region(min, max, Some(&mask))
+                .map(|(at, it)| (at, it.0))
+                .collect();
+            assert_eq!(masked, plain);
+            let volume = (0..3)
+                .map(|i| max[i].min(15) + 1 - min[i])
+                .product::<usize>();
+            assert_eq!(plain.len(), volume);
+        }
+
+        let rays = [
+            ([-1.0, 0.15, 0.2], [1.0, 0.0, 0.0]),
+            ([0.55, -1.0, 0.6], [0.0, 1.0, 0.0]),
+            ([2.0, 2.0, 2.0], [-1.0, -1.0, -1.0]),
+            ([0.3, 0.7, -1.0], [0.1, -0.2, 1.0]),
+        ];
+        let (mut plain_total, mut masked_total, mut hits) = (0, 0, 0);
+        for (origin, dir) in rays {
+            let visits = std::cell::Cell::new(0);
+            let solid = |it: &Counted| {
+                visits.set(visits.get() + 1);
+                it.0 == 1
+            };
+            let plain = test
+                .raycast_first(origin, dir, solid, None)
+                .map(|it| (it.1, it.2));
+            let plain_visits = visits.replace(0);
+            let masked = test
+                .raycast_first(origin, dir, solid, Some(&mask))
+                .map(|it| (it.1, it.2));
+            assert_eq!(masked, plain);
+            hits += plain.is_some() as usize;
+            plain_total += plain_visits;
+            masked_total += visits.get();
+        }
+        assert_eq!(hits, 3);
+        assert!(masked_total < plain_total);
+    }
+
+    #[test]
+    fn masked_queries_test() {
+        check_masked_queries::<BreathFirst>();
+        check_masked_queries::<DepthFirst>();
+    }
+}
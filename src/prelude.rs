@@ -0,0 +1,40 @@
+pub use typenum::{U0, U1, U2, U3, U4, U5, U6, U7, U8};
+
+pub use crate::{
+    layout::{BreathFirst, DepthFirst, OctreeLayout, BF, DF},
+    octant::{
+        Octant, OctantLDB, OctantLDF, OctantLUB, OctantLUF, OctantRDB, OctantRDF, OctantRUB,
+        OctantRUF, OctantT,
+    },
+    util::{layer_length, subtree_layout, subtree_length, subtree_size},
+    Octree, OctreeNode, PlainElement,
+};
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    fn check_basic_api<L: OctreeLayout>() {
+        let mut test = Octree::<u8, U2, L>::new(0);
+        test.child_mut::<OctantRUB>().set_value(1);
+        assert_eq!(**test.child::<OctantRUB>().child::<OctantLDF>(), 1);
+        assert_eq!(test.child::<OctantRUB>().octant(), Octant::RUB);
+        assert_eq!(Octree::<u8, U2, L>::size(), subtree_size::<u8>(2));
+        assert_eq!(Octree::<u8, U2, L>::node_count(), subtree_length(2));
+        assert_eq!(test.count_leaves_satisfying(|v| *v == 1), layer_length(1));
+    }
+
+    #[test]
+    fn prelude_test() {
+        check_basic_api::<BF>();
+        check_basic_api::<DF>();
+        check_basic_api::<BreathFirst>();
+        check_basic_api::<DepthFirst>();
+
+        let tree = Octree::<u32, U1>::new(3);
+        let node: &OctreeNode<u32, U1, BF> = tree.root();
+        assert_eq!(**node, 3);
+        assert_eq!(OctantLUF::VALUE, Octant::LUF);
+        assert_eq!(subtree_layout::<u32>(1).size(), 36);
+    }
+}
@@ -0,0 +1,185 @@
+/// A trait for computing parent node values from values of its children.
+pub trait Propagator<T> {
+    /// Returns the value of a parent node with the given `children` values,
+    /// ordered as [`Octant::ALL`](crate::octant::Octant::ALL).
+    fn propagate(&self, children: [&T; 8]) -> T;
+}
+
+impl<T, F: Fn([&T; 8]) -> T> Propagator<T> for F {
+    fn propagate(&self, children: [&T; 8]) -> T {
+        self(children)
+    }
+}
+
+/// Propagates the most frequent child value.
+///
+/// When multiple values are equally frequent, the one belonging to the lowest
+/// octant is chosen.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MostCommon;
+impl<T: Clone + PartialEq> Propagator<T> for MostCommon {
+    fn propagate(&self, children: [&T; 8]) -> T {
+        let mut counts = [0u8; 8];
+        'outer: for (i, child) in children.iter().enumerate() {
+            for (count, other) in counts.iter_mut().zip(children).take(i) {
+                if *child == other {
+                    *count += 1;
+                    continue 'outer;
+                }
+            }
+            counts[i] += 1;
+        }
+
+        let mut largest = 0;
+        for (i, count) in counts.iter().enumerate() {
+            if *count > counts[largest] {
+                largest = i;
+            }
+        }
+        children[largest].clone()
+    }
+}
+
+/// Propagates the average of child values.
+///
+/// As all children cover the same volume, this is the volume-weighted average
+/// of the field, which is the correct way to downsample continuous values
+/// such as density or signed distance.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WeightedAverage;
+
+macro_rules! impl_weighted_average {
+    ($($float: ty),*) => {$(
+        impl Propagator<$float> for WeightedAverage {
+            fn propagate(&self, children: [&$float; 8]) -> $float {
+                children.iter().copied().sum::<$float>() / 8.0
+            }
+        }
+
+        impl<const N: usize> Propagator<[$float; N]> for WeightedAverage {
+            fn propagate(&self, children: [&[$float; N]; 8]) -> [$float; N] {
+                std::array::from_fn(|i| children.iter().map(|it| it[i]).sum::<$float>() / 8.0)
+            }
+        }
+    )*};
+}
+impl_weighted_average!(f32, f64);
+
+/// Axis-aligned bounding box, as inclusive `(min, max)` corners.
+pub type Aabb = ([f32; 3], [f32; 3]);
+
+/// A value with (possibly empty) axis-aligned bounds.
+pub trait Bounded {
+    /// Returns bounds of the value, or `None` if it doesn't occupy any space.
+    fn bounds(&self) -> Option<Aabb>;
+    /// Returns a value with the given `bounds`, used for interior nodes.
+    fn from_bounds(bounds: Option<Aabb>) -> Self;
+}
+
+impl Bounded for Option<Aabb> {
+    fn bounds(&self) -> Option<Aabb> {
+        *self
+    }
+
+    fn from_bounds(bounds: Option<Aabb>) -> Self {
+        bounds
+    }
+}
+
+/// Propagates the smallest box enclosing bounds of all children, which turns
+/// an octree into a bounding volume hierarchy.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BoundingBox;
+impl<T: Bounded> Propagator<T> for BoundingBox {
+    fn propagate(&self, children: [&T; 8]) -> T {
+        let bounds = children.iter().filter_map(|child| child.bounds()).reduce(
+            |(a_min, a_max), (b_min, b_max)| {
+                (
+                    std::array::from_fn(|i| a_min[i].min(b_min[i])),
+                    std::array::from_fn(|i| a_max[i].max(b_max[i])),
+                )
+            },
+        );
+        T::from_bounds(bounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typenum::U3;
+
+    use super::*;
+    use crate::{layout::DepthFirst, octant::*, Octree};
+
+    #[test]
+    fn most_common_test() {
+        assert_eq!(MostCommon.propagate([&1, &2, &2, &3, &1, &2, &4, &5]), 2);
+        assert_eq!(MostCommon.propagate([&1, &2, &2, &3, &1, &6, &4, &5]), 1);
+        assert_eq!(
+            (|children: [&u8; 8]| *children[7]).propagate([&0, &0, &0, &0, &0, &0, &0, &9]),
+            9
+        );
+    }
+
+    #[test]
+    fn bounding_box_test() {
+        let mut test = Octree::<Option<Aabb>, U3>::new(None);
+        let boxes = [
+            ((1, 2, 3), ([1.0, 2.0, 3.0], [1.5, 2.5, 3.5])),
+            ((6, 1, 0), ([6.2, 1.0, 0.0], [7.0, 1.1, 0.5])),
+            ((7, 7, 7), ([7.0, 7.0, 7.0], [7.5, 9.0, 7.5])),
+            ((0, 4, 6), ([-0.5, 4.0, 6.0], [0.5, 4.5, 6.5])),
+        ];
+        for ((x, y, z), bounds) in boxes {
+            *test.get_mut(x, y, z).unwrap() = Some(bounds);
+        }
+        test.reduce_bounding_box();
+
+        let (min, max) = (**test).unwrap();
+        for (_, (box_min, box_max)) in boxes {
+            assert!((0..3).all(|i| min[i] <= box_min[i] && box_max[i] <= max[i]));
+        }
+        assert_eq!((min, max), ([-0.5, 1.0, 0.0], [7.5, 9.0, 7.5]));
+        assert_eq!(
+            **test.child::<OctantRUB>(),
+            Some(([7.0, 7.0, 7.0], [7.5, 9.0, 7.5]))
+        );
+        assert_eq!(**test.child::<OctantLDF>(), Some(boxes[0].1));
+        assert_eq!(**test.child::<OctantLUF>(), None);
+    }
+
+    #[test]
+    fn weighted_average_test() {
+        let children = [&0.0, &1.0, &1.0, &1.0, &1.0, &1.0, &4.0, &5.0];
+        assert_eq!(WeightedAverage.propagate(children), 1.75);
+        assert_eq!(MostCommon.propagate(children), 1.0);
+        assert_eq!(WeightedAverage.propagate([&[1.0f64, 8.0]; 8]), [1.0, 8.0]);
+
+        // Averages of a linear field are values at cell centers.
+        let mut test = Octree::<f32, U3, DepthFirst>::new(0.0);
+        for z in 0..8 {
+            for y in 0..8 {
+                for x in 0..8 {
+                    *test.get_mut(x, y, z).unwrap() = x as f32 + 2.0 * y as f32 + 4.0 * z as f32;
+                }
+            }
+        }
+        test.propagate_up(&WeightedAverage);
+        assert_eq!(**test, 3.5 + 7.0 + 14.0);
+        assert_eq!(**test.child::<OctantRDF>(), 5.5 + 3.0 + 6.0);
+        assert_eq!(
+            **test.child::<OctantLUB>().child::<OctantRDF>(),
+            2.5 + 2.0 * 4.5 + 4.0 * 4.5
+        );
+
+        let mut common = test.to_dyn().shrink_depth(&MostCommon).unwrap();
+        let mut averaged = test.to_dyn().shrink_depth(&WeightedAverage).unwrap();
+        common = common.shrink_depth(&MostCommon).unwrap();
+        averaged = averaged.shrink_depth(&WeightedAverage).unwrap();
+        let leaf = |tree: &crate::DynOctree<f32, DepthFirst>| {
+            *tree.dyn_root().child(Octant::RUB).unwrap().value()
+        };
+        assert_eq!(leaf(&averaged), 5.5 + 11.0 + 22.0);
+        assert_ne!(leaf(&common), leaf(&averaged));
+    }
+}
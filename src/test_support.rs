@@ -0,0 +1,262 @@
+use std::{collections::HashSet, fmt::Debug};
+
+use typenum::Unsigned;
+
+use crate::{
+    layout::{BreathFirst, OctreeLayout},
+    octant::Octant,
+    util::{layer_length, subtree_length},
+    Octree,
+};
+
+/// Returns data offsets of all nodes in the subtree at `offset`, by walking
+/// [`OctreeLayout::child_offset`].
+fn subtree_offsets<L: OctreeLayout, T>(
+    size: usize,
+    offset: usize,
+    depth: usize,
+    index: usize,
+    result: &mut Vec<usize>,
+) {
+    result.push(offset);
+    if depth == 0 {
+        return;
+    }
+    for octant in Octant::ALL {
+        let child = offset + L::child_offset::<T>(octant, size, depth, index);
+        subtree_offsets::<L, T>(
+            size,
+            child,
+            depth - 1,
+            index * 8 + octant.as_usize(),
+            result,
+        );
+    }
+}
+
+/// Returns the data offset of the node at the given `layer` and `index`.
+fn node_offset<L: OctreeLayout, T>(size: usize, layer: usize, index: usize) -> usize {
+    (0..layer).fold(0, |offset, parent_layer| {
+        let shift = (layer - parent_layer) * 3;
+        let octant = Octant::ALL[(index >> (shift - 3)) & 0b111];
+        offset + L::child_offset::<T>(octant, size, size - parent_layer, index >> shift)
+    })
+}
+
+/// Advances the xorshift `state` and returns its new value.
+///
+/// Used as a small deterministic random number generator in tests.
+#[cfg(test)]
+pub(crate) fn xorshift(state: &mut u32) -> u32 {
+    *state ^= *state << 13;
+    *state ^= *state >> 17;
+    *state ^= *state << 5;
+    *state
+}
+
+/// Checks that the layout `L` upholds the requirements octrees place on it,
+/// for octrees of `T` values with the given `Depth`, and panics otherwise.
+///
+/// This verifies that:
+/// - offsets of all nodes are within the octree buffer and unique, so that
+///   every layer occupies a separate part of the buffer and all layers
+///   together cover all of it,
+/// - nodes are stored at [`BreathFirst::layer_range`] if
+///   [`OctreeLayout::CONTIGUOUS_LAYERS`] is set,
+/// - [`OctreeLayout::fill`] of any node writes exactly the nodes reachable
+///   from it through [`OctreeLayout::child_offset`],
+/// - conversion of an octree from [`BreathFirst`] layout and back preserves
+///   all values.
+///
+/// Every node is filled separately, so this takes quadratic time in the
+/// number of nodes and is meant for small depths.
+pub fn check_layout_invariants<L, T, Depth>()
+where
+    L: OctreeLayout,
+    T: Copy + PartialEq + Debug + From<u8>,
+    Depth: Unsigned,
+{
+    let size = Depth::USIZE;
+    let len = subtree_length(size);
+
+    let mut offsets = vec![Vec::new(); size + 1];
+    let mut seen = HashSet::new();
+    for (layer, layer_offsets) in offsets.iter_mut().enumerate() {
+        for index in 0..layer_length(layer) {
+            let offset = node_offset::<L, T>(size, layer, index);
+            assert!(
+                offset < len,
+                "{}: node {} at layer {} has offset {} outside of buffer with length {}",
+                L::NAME,
+                index,
+                layer,
+                offset,
+                len
+            );
+            assert!(
+                seen.insert(offset),
+                "{}: node {} at layer {} has the same offset {} as another node",
+                L::NAME,
+                index,
+                layer,
+                offset
+            );
+            if L::CONTIGUOUS_LAYERS {
+                assert_eq!(
+                    offset,
+                    BreathFirst::layer_range(layer, size).start + index,
+                    "{}: node {} at layer {} isn't stored contiguously with its layer",
+                    L::NAME,
+                    index,
+                    layer
+                );
+            }
+            layer_offsets.push(offset);
+        }
+    }
+    assert_eq!(seen.len(), len, "{}: nodes don't cover the buffer", L::NAME);
+
+    let empty = T::from(0);
+    let marker = T::from(1);
+    let mut buffer = vec![empty; len];
+    for (layer, layer_offsets) in offsets.iter().enumerate() {
+        for (index, offset) in layer_offsets.iter().enumerate() {
+            buffer.fill(empty);
+            unsafe {
+                // SAFETY: offset was checked to be within the buffer, and
+                // values are `Copy`, so overwriting them without dropping
+                // doesn't leak.
+                L::fill(
+                    buffer.as_mut_ptr().add(*offset),
+                    marker,
+                    size,
+                    size - layer,
+                    index,
+                );
+            }
+            let mut expected = Vec::new();
+            subtree_offsets::<L, T>(size, *offset, size - layer, index, &mut expected);
+            let expected: HashSet<_> = expected.into_iter().collect();
+            for (i, value) in buffer.iter().enumerate() {
+                assert_eq!(
+                    *value == marker,
+                    expected.contains(&i),
+                    "{}: filling node {} at layer {} doesn't match its subtree at offset {}",
+                    L::NAME,
+                    index,
+                    layer,
+                    i
+                );
+            }
+        }
+    }
+
+    let layers: Vec<Vec<T>> = (0..=size)
+        .map(|layer| {
+            (0..layer_length(layer))
+                .map(|index| T::from((layer * 31 + index) as u8))
+                .collect()
+        })
+        .collect();
+    let source = Octree::<T, Depth, BreathFirst>::from_layers(layers.clone())
+        .expect("layers match octree depth");
+    let converted = Octree::<T, Depth, L>::from_layers(source.into_layers())
+        .expect("layers match octree depth");
+    for (layer, values) in layers.iter().enumerate() {
+        for (index, value) in values.iter().enumerate() {
+            let offset = offsets[layer][index];
+            assert_eq!(
+                &AsRef::<[T]>::as_ref(&converted)[offset],
+                value,
+                "{}: node {} at layer {} changed after conversion",
+                L::NAME,
+                index,
+                layer
+            );
+        }
+    }
+    let round_trip = Octree::<T, Depth, BreathFirst>::from_layers(converted.into_layers())
+        .expect("layers match octree depth");
+    assert_eq!(
+        round_trip.into_layers(),
+        layers,
+        "{}: values changed after round trip",
+        L::NAME
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use typenum::{U0, U1, U2, U3};
+
+    use super::*;
+    use crate::layout::DepthFirst;
+
+    #[test]
+    fn crate_layouts_test() {
+        check_layout_invariants::<BreathFirst, u8, U0>();
+        check_layout_invariants::<BreathFirst, u16, U3>();
+        check_layout_invariants::<DepthFirst, u8, U1>();
+        check_layout_invariants::<DepthFirst, u32, U3>();
+    }
+
+    /// Depth-first layout with children stored in reverse order.
+    struct Reversed;
+    impl OctreeLayout for Reversed {
+        const NAME: &'static str = "reversed";
+
+        unsafe fn fill<T: Clone>(base: *mut T, value: T, size: usize, depth: usize, index: usize) {
+            DepthFirst::fill(base, value, size, depth, index)
+        }
+
+        fn child_offset<T>(octant: Octant, _size: usize, depth: usize, _index: usize) -> usize {
+            1 + subtree_length(depth - 1) * (7 - octant.as_usize())
+        }
+    }
+
+    /// Layout that forgets to skip the parent node.
+    struct Overlapping;
+    impl OctreeLayout for Overlapping {
+        const NAME: &'static str = "overlapping";
+
+        unsafe fn fill<T: Clone>(base: *mut T, value: T, size: usize, depth: usize, index: usize) {
+            DepthFirst::fill(base, value, size, depth, index)
+        }
+
+        fn child_offset<T>(octant: Octant, _size: usize, depth: usize, _index: usize) -> usize {
+            subtree_length(depth - 1) * octant.as_usize()
+        }
+    }
+
+    /// Depth-first layout that claims contiguous layers.
+    struct NotContiguous;
+    impl OctreeLayout for NotContiguous {
+        const NAME: &'static str = "not_contiguous";
+        const CONTIGUOUS_LAYERS: bool = true;
+
+        unsafe fn fill<T: Clone>(base: *mut T, value: T, size: usize, depth: usize, index: usize) {
+            DepthFirst::fill(base, value, size, depth, index)
+        }
+
+        fn child_offset<T>(octant: Octant, size: usize, depth: usize, index: usize) -> usize {
+            DepthFirst::child_offset::<T>(octant, size, depth, index)
+        }
+    }
+
+    #[test]
+    fn custom_layout_test() {
+        check_layout_invariants::<Reversed, u8, U3>();
+    }
+
+    #[test]
+    #[should_panic(expected = "overlapping")]
+    fn invalid_layout_test() {
+        check_layout_invariants::<Overlapping, u8, U1>();
+    }
+
+    #[test]
+    #[should_panic(expected = "not_contiguous: node 1 at layer 1 isn't stored contiguously")]
+    fn non_contiguous_layout_test() {
+        check_layout_invariants::<NotContiguous, u8, U2>();
+    }
+}
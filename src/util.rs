@@ -3,6 +3,8 @@ use std::{
     mem::{align_of, size_of},
 };
 
+use crate::octant::{Octant, OctantPath};
+
 /// Returns a length of an octree layer at the given `depth`.
 #[inline(always)]
 pub const fn layer_length(depth: usize) -> usize {
@@ -33,6 +35,134 @@ pub fn subtree_layout<T>(depth: usize) -> Layout {
     Layout::from_size_align(subtree_size::<T>(depth), align_of::<T>()).unwrap()
 }
 
+/// Returns the morton index of the leaf at the given `x`, `y` and `z`
+/// coordinates.
+///
+/// Bits of each coordinate are interleaved following the [`Octant`] bit
+/// order: `x` occupies the lowest bit of every octant (right), `y` the middle
+/// one (up), and `z` the highest (back). This is the index of a leaf within
+/// the leaf layer.
+pub const fn leaf_index_from_coords(x: usize, y: usize, z: usize) -> usize {
+    let mut result = 0;
+    let mut bit = 0;
+    while bit < usize::BITS as usize / 3 {
+        result |= ((x >> bit) & 1) << (bit * 3);
+        result |= ((y >> bit) & 1) << (bit * 3 + 1);
+        result |= ((z >> bit) & 1) << (bit * 3 + 2);
+        bit += 1;
+    }
+    result
+}
+
+/// Returns the `(x, y, z)` coordinates of the leaf with the given morton
+/// `index`.
+///
+/// This is the inverse of [`leaf_index_from_coords`].
+pub const fn leaf_index_to_coords(index: usize) -> (usize, usize, usize) {
+    let (mut x, mut y, mut z) = (0, 0, 0);
+    let mut bit = 0;
+    while bit < usize::BITS as usize / 3 {
+        x |= ((index >> (bit * 3)) & 1) << bit;
+        y |= ((index >> (bit * 3 + 1)) & 1) << bit;
+        z |= ((index >> (bit * 3 + 2)) & 1) << bit;
+        bit += 1;
+    }
+    (x, y, z)
+}
+
+/// Returns the index of the leaf at the given voxel coordinates within the
+/// leaf layer of an octree with the given `depth`.
+///
+/// Leaves are ordered by their morton index (see [`leaf_index_from_coords`])
+/// in both layouts, so for a [`BreathFirst`](crate::layout::BreathFirst)
+/// octree, the leaf value is at `BreathFirst::layer_range(depth, depth).start
+/// + index`. That's `subtree_length(depth - 1) + index` for non-zero `depth`,
+/// and `0` (the root) for `depth` of `0`.
+///
+/// # Panics
+///
+/// Panics if any of the coordinates is outside of the `2^depth` grid.
+pub const fn octree_index_from_coords(x: u32, y: u32, z: u32, depth: usize) -> usize {
+    let side = 1usize << depth;
+    assert!(
+        (x as usize) < side && (y as usize) < side && (z as usize) < side,
+        "coordinates out of range"
+    );
+    leaf_index_from_coords(x as usize, y as usize, z as usize)
+}
+
+/// Returns the data index of the node at the given `depth` (layer, with `0`
+/// being the root) and `index` within that layer, in a
+/// [`BreathFirst`](crate::layout::BreathFirst) octree of the given
+/// `tree_depth`.
+///
+/// # Panics
+///
+/// Panics if `depth` is larger than `tree_depth` or `index` is outside of the
+/// layer.
+pub const fn node_offset_bf(depth: usize, index: usize, tree_depth: usize) -> usize {
+    assert!(depth <= tree_depth, "depth is out of range");
+    assert!(index < layer_length(depth), "index out of range");
+    if depth == 0 {
+        return index;
+    }
+    subtree_length(depth - 1) + index
+}
+
+/// Returns the data index of the node at the given `depth` (layer, with `0`
+/// being the root) and `index` within that layer, in a
+/// [`DepthFirst`](crate::layout::DepthFirst) octree of the given
+/// `tree_depth`.
+///
+/// # Panics
+///
+/// Panics if `depth` is larger than `tree_depth` or `index` is outside of the
+/// layer.
+pub const fn node_offset_df(depth: usize, index: usize, tree_depth: usize) -> usize {
+    assert!(depth <= tree_depth, "depth is out of range");
+    assert!(index < layer_length(depth), "index out of range");
+    let mut offset = 0;
+    let mut layer = 0;
+    while layer < depth {
+        let octant = (index >> ((depth - 1 - layer) * 3)) & 0b111;
+        offset += 1 + subtree_length(tree_depth - layer - 1) * octant;
+        layer += 1;
+    }
+    offset
+}
+
+/// Returns the depth of the deepest common ancestor of nodes at the given
+/// octant paths, which is the length of their shared prefix.
+pub fn common_ancestor(a: &[Octant], b: &[Octant]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+/// Returns the layer index of the node at the given octant `path` from the
+/// root, matching the index of typed child nodes.
+pub fn path_to_index(path: &[Octant]) -> usize {
+    path.iter().fold(0, |acc, it| acc * 8 + it.as_usize())
+}
+
+/// Returns the octant path from the root to the node with the given layer
+/// `index` at `depth`.
+///
+/// # Panics
+///
+/// Panics if `index` is outside of the layer at `depth`.
+pub fn index_to_path(index: usize, depth: usize) -> OctantPath {
+    assert!(index < layer_length(depth), "index out of range");
+    (0..depth)
+        .rev()
+        .map(|layer| Octant::ALL[(index >> (layer * 3)) & 0b111])
+        .collect()
+}
+
+/// Returns an iterator over octant paths of all `8^depth` nodes at `depth`,
+/// in morton order.
+pub fn paths_at_depth(depth: usize) -> impl Iterator<Item = OctantPath> {
+    (0..layer_length(depth)).map(move |index| index_to_path(index, depth))
+}
+
 /// Provides a way to iterate over children tuple by unrolling the provided body
 /// 8 times for each.
 #[macro_export]
@@ -77,12 +207,161 @@ macro_rules! for_each_child {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::layout::{BreathFirst, DepthFirst, OctreeLayout};
 
     #[test]
+    #[allow(clippy::identity_op)]
     fn octree_size_test() {
         assert_eq!(subtree_size::<u8>(0), 1);
         assert_eq!(subtree_size::<u8>(1), 1 + 8 * 1);
         assert_eq!(subtree_size::<u8>(2), 1 + 8 * (1 + 8 * 1));
         assert_eq!(subtree_size::<u8>(3), 1 + 8 * (1 + 8 * (1 + 8 * 1)));
     }
+
+    const BF_OFFSETS: [usize; 4] = [
+        node_offset_bf(0, 0, 3),
+        node_offset_bf(1, 5, 3),
+        node_offset_bf(2, 12, 3),
+        node_offset_bf(3, 511, 3),
+    ];
+    const DF_OFFSETS: [usize; 4] = [
+        node_offset_df(0, 0, 3),
+        node_offset_df(1, 5, 3),
+        node_offset_df(2, 0o14, 3),
+        node_offset_df(3, 0o777, 3),
+    ];
+    const _: () = assert!(BF_OFFSETS[1] == 6 && BF_OFFSETS[3] == subtree_length(3) - 1);
+    const _: () = assert!(DF_OFFSETS[1] == 1 + 5 * 73 && DF_OFFSETS[3] == subtree_length(3) - 1);
+    const _: () = assert!(DepthFirst::child_offset::<u8>(Octant::RUB, 3, 2, 0) == 1 + 7 * 9);
+
+    /// Walks from the root to the node at `depth` and `index` using
+    /// [`OctreeLayout::child_offset`].
+    fn walk<L: OctreeLayout>(depth: usize, index: usize, tree_depth: usize) -> usize {
+        (0..depth).fold(0, |offset, layer| {
+            let shift = (depth - layer) * 3;
+            let octant = Octant::ALL[(index >> (shift - 3)) & 0b111];
+            offset + L::child_offset::<u8>(octant, tree_depth, tree_depth - layer, index >> shift)
+        })
+    }
+
+    #[test]
+    fn node_offset_test() {
+        let nodes = [(0, 0), (1, 5), (2, 0o14), (3, 0o777)];
+        for (i, (depth, index)) in nodes.into_iter().enumerate() {
+            assert_eq!(BF_OFFSETS[i], walk::<BreathFirst>(depth, index, 3));
+            assert_eq!(DF_OFFSETS[i], walk::<DepthFirst>(depth, index, 3));
+        }
+        for depth in 0..=3 {
+            for index in 0..layer_length(depth) {
+                assert_eq!(
+                    node_offset_bf(depth, index, 3),
+                    walk::<BreathFirst>(depth, index, 3)
+                );
+                assert_eq!(
+                    node_offset_df(depth, index, 3),
+                    walk::<DepthFirst>(depth, index, 3)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn path_index_round_trip_test() {
+        for depth in 0..=4 {
+            let paths: Vec<_> = paths_at_depth(depth).collect();
+            assert_eq!(paths.len(), layer_length(depth));
+            for (index, path) in paths.iter().enumerate() {
+                assert_eq!(path.len(), depth);
+                assert_eq!(path_to_index(path), index);
+                assert_eq!(index_to_path(index, depth), *path);
+            }
+        }
+        assert_eq!(paths_at_depth(0).collect::<Vec<_>>(), vec![vec![]]);
+        assert_eq!(
+            index_to_path(0o527, 3),
+            [Octant::RDB, Octant::LUF, Octant::RUB]
+        );
+    }
+
+    #[test]
+    fn path_index_child_test() {
+        use crate::octant::{OctantLUF, OctantRDB, OctantRUB};
+        use typenum::U3;
+
+        let test = crate::Octree::<usize, U3>::new_with(|i| i);
+        let node = test
+            .child::<OctantRDB>()
+            .child::<OctantLUF>()
+            .child::<OctantRUB>();
+        let index = path_to_index(&[Octant::RDB, Octant::LUF, Octant::RUB]);
+        assert_eq!(**node, node_offset_bf(3, index, 3));
+        assert_eq!(**test.child::<OctantRUB>(), node_offset_bf(1, 7, 3));
+        for depth in 0..=3 {
+            for (index, path) in paths_at_depth(depth).enumerate() {
+                assert_eq!(test.get_path(&path), Some(&node_offset_bf(depth, index, 3)));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of range")]
+    fn index_to_path_out_of_range_test() {
+        index_to_path(8, 1);
+    }
+
+    #[test]
+    fn common_ancestor_test() {
+        use Octant::*;
+
+        assert_eq!(common_ancestor(&[], &[]), 0);
+        assert_eq!(common_ancestor(&[LDF, RUB], &[]), 0);
+        assert_eq!(common_ancestor(&[LDF, RUB], &[RUB, RUB]), 0);
+        assert_eq!(common_ancestor(&[LDF, RUB], &[LDF, LUB]), 1);
+        assert_eq!(common_ancestor(&[LDF, RUB, RDF], &[LDF, RUB, RDF, LUF]), 3);
+        assert_eq!(common_ancestor(&[LDF, RUB, RDF, LUF], &[LDF, RUB, RDF]), 3);
+        assert_eq!(common_ancestor(&[RDB, LUB, LUB], &[RDB, LUB, LUB]), 3);
+    }
+
+    #[test]
+    fn leaf_coords_test() {
+        assert_eq!(leaf_index_from_coords(0, 0, 0), 0);
+        assert_eq!(leaf_index_from_coords(1, 0, 0), 0b001);
+        assert_eq!(leaf_index_from_coords(0, 1, 0), 0b010);
+        assert_eq!(leaf_index_from_coords(0, 0, 1), 0b100);
+        assert_eq!(leaf_index_from_coords(2, 3, 1), 0b011_110);
+
+        for i in 0..layer_length(3) {
+            let (x, y, z) = leaf_index_to_coords(i);
+            assert!(x < 8 && y < 8 && z < 8);
+            assert_eq!(leaf_index_from_coords(x, y, z), i);
+        }
+    }
+
+    #[test]
+    fn octree_index_from_coords_test() {
+        for i in 0..layer_length(3) {
+            let (x, y, z) = leaf_index_to_coords(i);
+            assert_eq!(octree_index_from_coords(x as u32, y as u32, z as u32, 3), i);
+        }
+        assert_eq!(octree_index_from_coords(0, 0, 0, 0), 0);
+        assert_eq!(octree_index_from_coords(1, 1, 1, 1), 7);
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of range")]
+    fn node_offset_bf_index_out_of_range_test() {
+        node_offset_bf(1, 8, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of range")]
+    fn node_offset_df_index_out_of_range_test() {
+        node_offset_df(2, 64, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "coordinates out of range")]
+    fn octree_index_from_coords_range_test() {
+        octree_index_from_coords(0, 8, 0, 3);
+    }
 }